@@ -0,0 +1,23 @@
+CREATE TABLE IF NOT EXISTS events (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  chat_id INTEGER NOT NULL,
+  user_id INTEGER NOT NULL,
+  name TEXT NOT NULL,
+  description TEXT,
+  location TEXT,
+  time TEXT,
+  max_capacity INTEGER,
+  message_id INTEGER
+);
+
+CREATE TABLE IF NOT EXISTS attendees (
+  event_id INTEGER NOT NULL REFERENCES events (id) ON DELETE CASCADE,
+  user_id INTEGER NOT NULL,
+  status TEXT NOT NULL CHECK (status IN ('accepted', 'declined', 'maybe', 'waitlist')),
+  PRIMARY KEY (event_id, user_id)
+);
+
+CREATE TABLE IF NOT EXISTS group_settings (
+  chat_id INTEGER PRIMARY KEY,
+  timezone TEXT NOT NULL DEFAULT 'UTC'
+);
@@ -0,0 +1 @@
+ALTER TABLE events ADD COLUMN reminders_sent INTEGER NOT NULL DEFAULT 0;
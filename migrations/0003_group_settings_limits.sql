@@ -0,0 +1,3 @@
+ALTER TABLE group_settings ADD COLUMN max_events INTEGER NOT NULL DEFAULT 10;
+
+ALTER TABLE group_settings ADD COLUMN require_admin INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1 @@
+ALTER TABLE group_settings ADD COLUMN notify_creator INTEGER NOT NULL DEFAULT 1;
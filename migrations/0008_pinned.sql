@@ -0,0 +1 @@
+ALTER TABLE group_settings ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0;
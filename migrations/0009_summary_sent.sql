@@ -0,0 +1 @@
+ALTER TABLE events ADD COLUMN summary_sent INTEGER NOT NULL DEFAULT 0;
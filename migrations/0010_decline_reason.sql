@@ -0,0 +1 @@
+ALTER TABLE attendees ADD COLUMN decline_reason TEXT;
@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS drafts (
+  user_id INTEGER PRIMARY KEY,
+  state TEXT NOT NULL,
+  draft_json TEXT NOT NULL,
+  origin_chat_id INTEGER,
+  timezone TEXT
+);
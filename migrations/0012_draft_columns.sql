@@ -0,0 +1,34 @@
+CREATE TABLE drafts_new (
+  user_id INTEGER PRIMARY KEY,
+  state TEXT NOT NULL,
+  origin_chat_id INTEGER,
+  title TEXT NOT NULL,
+  description TEXT NOT NULL,
+  location TEXT NOT NULL,
+  datetime TEXT NOT NULL,
+  max_capacity INTEGER,
+  rsvp_deadline TEXT,
+  url TEXT,
+  timezone TEXT,
+  updated_at TEXT NOT NULL
+);
+
+INSERT INTO drafts_new
+SELECT
+  user_id,
+  state,
+  origin_chat_id,
+  json_extract(draft_json, '$.name'),
+  json_extract(draft_json, '$.description'),
+  json_extract(draft_json, '$.location'),
+  json_extract(draft_json, '$.time'),
+  json_extract(draft_json, '$.max_capacity'),
+  json_extract(draft_json, '$.rsvp_deadline'),
+  json_extract(draft_json, '$.url'),
+  timezone,
+  strftime('%Y-%m-%d %H:%M', 'now')
+FROM drafts;
+
+DROP TABLE drafts;
+
+ALTER TABLE drafts_new RENAME TO drafts;
@@ -0,0 +1,3 @@
+ALTER TABLE events ADD COLUMN anonymous INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE drafts ADD COLUMN anonymous INTEGER NOT NULL DEFAULT 0;
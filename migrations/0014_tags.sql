@@ -0,0 +1,3 @@
+ALTER TABLE events ADD COLUMN tags TEXT;
+
+ALTER TABLE drafts ADD COLUMN tags TEXT;
@@ -0,0 +1 @@
+ALTER TABLE events ADD COLUMN last_reminded_at TEXT;
@@ -0,0 +1,7 @@
+ALTER TABLE group_settings ADD COLUMN trusted_creators_only INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS trusted_creators (
+  chat_id INTEGER NOT NULL,
+  user_id INTEGER NOT NULL,
+  PRIMARY KEY (chat_id, user_id)
+);
@@ -0,0 +1,5 @@
+ALTER TABLE events ADD COLUMN guests_per_rsvp INTEGER NOT NULL DEFAULT 1;
+
+ALTER TABLE attendees ADD COLUMN guests INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE drafts ADD COLUMN guests_per_rsvp INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS feedback_log (
+  user_id INTEGER NOT NULL,
+  sent_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS feedback_log_user_id ON feedback_log (user_id, sent_at);
@@ -0,0 +1,4 @@
+CREATE TABLE IF NOT EXISTS chat_names (
+  chat_id INTEGER PRIMARY KEY,
+  title TEXT NOT NULL
+);
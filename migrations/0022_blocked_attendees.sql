@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS blocked_attendees (
+  creator_id INTEGER NOT NULL,
+  blocked_user_id INTEGER NOT NULL,
+  PRIMARY KEY (creator_id, blocked_user_id)
+);
@@ -0,0 +1 @@
+ALTER TABLE events ADD COLUMN message_hash TEXT;
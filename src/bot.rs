@@ -0,0 +1,4351 @@
+use crate::config::Config;
+use crate::conflict::EventConflictChecker;
+use crate::db;
+use crate::db::drafts::{self, StoredDraft};
+use crate::db::group_settings::{self, GroupSettings};
+use crate::db::{CancelMode, EventSort};
+use crate::error::BotError;
+use crate::event::csv;
+use crate::event::ical;
+use crate::event::json;
+use crate::event::{
+    excess_length, is_valid_url, parse_tags, sanitize_text, Event, EventContext, EventEditContext,
+    EventEditState, EventField, ParseModeStrategy, RsvpStatus, UserDisplay, UserState,
+    CANCEL_DELETE_CALLBACK_PREFIX, CHECK_IN_CALLBACK_PREFIX, CONFIRM_DELETE_CALLBACK_PREFIX,
+    DELETE_CALLBACK_PREFIX, EDIT_START_CALLBACK_PREFIX, ENDED_CALLBACK_PREFIX,
+    MAX_DECLINE_REASON_LEN, MAX_GUESTS_PER_RSVP,
+};
+use crate::reminder::REMINDER_WINDOW;
+use crate::settings::{SettingsContext, SettingsState};
+use crate::telegram::{LiveClient, TelegramClient};
+use crate::util::{
+    api_call_with_retry, command_args, local_to_utc, parse_datetime_string, parse_page_number,
+    split_page_number, DATETIME_FORMAT, MAX_API_RETRIES,
+};
+use crate::webhook::WebhookServer;
+use chrono::{NaiveDateTime, Utc};
+use chrono_tz::Tz;
+use frankenstein::AllowedUpdate;
+use frankenstein::AnswerCallbackQueryParams;
+use frankenstein::AnswerInlineQueryParams;
+use frankenstein::BotCommand;
+use frankenstein::CallbackQuery;
+use frankenstein::ChatMember;
+use frankenstein::ChatMemberUpdated;
+use frankenstein::ChatType;
+use frankenstein::DeleteMessageParams;
+use frankenstein::DeleteMyCommandsParams;
+use frankenstein::DeleteWebhookParams;
+use frankenstein::EditMessageReplyMarkupParams;
+use frankenstein::EditMessageTextParams;
+use frankenstein::GetChatAdministratorsParams;
+use frankenstein::GetChatMemberParams;
+use frankenstein::GetUpdatesParams;
+use frankenstein::InlineKeyboardButton;
+use frankenstein::InlineKeyboardMarkup;
+use frankenstein::InlineQuery;
+use frankenstein::InlineQueryResult;
+use frankenstein::InlineQueryResultArticle;
+use frankenstein::InputFile;
+use frankenstein::InputMessageContent;
+use frankenstein::InputTextMessageContent;
+use frankenstein::MaybeInaccessibleMessage;
+use frankenstein::Message;
+use frankenstein::PinChatMessageParams;
+use frankenstein::ReplyMarkup;
+use frankenstein::ReplyParameters;
+use frankenstein::SendDocumentParams;
+use frankenstein::SendMessageParams;
+use frankenstein::SetMyCommandsParams;
+use frankenstein::SetWebhookParams;
+use frankenstein::UnpinChatMessageParams;
+use frankenstein::Update;
+use frankenstein::UpdateContent;
+use frankenstein::User;
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc, watch, Notify};
+use tracing::{debug, error, info, instrument, warn};
+
+/// How long a chat's administrator list is trusted before it is fetched again.
+const ADMIN_CACHE_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// How long a user's display name is trusted before it is fetched again.
+const CACHE_TTL_SECS: u64 = 3600;
+
+/// How many days ahead `/upcoming` looks by default, and at most.
+const DEFAULT_UPCOMING_DAYS: u32 = 7;
+const MAX_UPCOMING_DAYS: u32 = 365;
+
+/// How many days ago events must have taken place for `/pruneold` to delete
+/// them, unless told otherwise.
+const DEFAULT_PRUNE_DAYS: u32 = 90;
+
+/// The callback data prefixes of the `/myevents` buttons, followed by the
+/// ID of the user who asked and, each after a `_`, the sort order and page
+/// they asked for. Buttons sent before sorting existed have neither.
+const MY_EVENTS_CREATED_PREFIX: &str = "myevents_created_";
+const MY_EVENTS_ATTENDING_PREFIX: &str = "myevents_attending_";
+
+/// How long polling waits after its first failure in a row; the wait doubles
+/// with every further one, up to `MAX_POLL_ERROR_BACKOFF`.
+const POLL_ERROR_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_POLL_ERROR_BACKOFF: Duration = Duration::from_secs(30);
+
+/// How many events a page of `/list`, `/upcoming`, `/pastevents` or
+/// `/myevents` has.
+const MAX_EVENTS_PER_PAGE: usize = 5;
+
+/// The callback data prefix of the buttons under a `/list` page, followed by
+/// the offset of the page to show, the chat ID and the tag filtered by, if any.
+const LIST_PAGE_CALLBACK_PREFIX: &str = "list_next_";
+
+/// The most bytes of callback data Telegram accepts.
+const MAX_CALLBACK_DATA_LEN: usize = 64;
+
+/// How many events `/search` lists at most.
+const MAX_SEARCH_RESULTS: u32 = 10;
+
+/// The longest keyword `/search` accepts, in characters.
+const MAX_SEARCH_KEYWORD_LEN: usize = 50;
+
+/// How long after a `/remind` the event's attendees can be reminded again.
+const MANUAL_REMINDER_COOLDOWN: chrono::Duration = chrono::Duration::hours(4);
+
+/// How many chats each `/listall` message lists.
+const LIST_ALL_PAGE_SIZE: usize = 10;
+
+/// How many messages a user can send with `/feedback` a day.
+const MAX_FEEDBACK_PER_DAY: i64 = 3;
+
+/// The longest `/feedback` message, in characters.
+const MAX_FEEDBACK_LEN: usize = 1000;
+
+/// How many events an inline query offers at most.
+const MAX_INLINE_RESULTS: u32 = 10;
+
+/// How many new event IDs can wait for the reminder task before the oldest
+/// are dropped.
+const NEW_EVENTS_CAPACITY: usize = 64;
+
+/// The time formats `parse_datetime_string` accepts, e.g. for prompts.
+const TIME_FORMATS_HINT: &str =
+    "YYYY-MM-DD HH:MM (e.g. 2025-08-15 19:00), YYYY/MM/DD HH:MM, DD-MM-YYYY HH:MM or YYYY-MM-DD HH:MM AM/PM";
+
+/// The answer to a button only an event's creator may use.
+const NOT_AUTHORIZED: &str = "⛔ You are not authorized to do this.";
+
+/// What became of an RSVP button press.
+enum RsvpOutcome {
+    Missing,
+    Cancelled,
+    Closed,
+    /// The event's creator blocked the user.
+    Blocked,
+    /// The RSVP was applied. `promoted` is the user moved off the waitlist,
+    /// if any, and `status` the RSVP the user ends up with.
+    Recorded {
+        promoted: Option<i64>,
+        status: Option<String>,
+    },
+}
+
+/// A group role allowed to create events, as listed in `CREATION_ALLOWED_ROLES`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CreationRole {
+    /// The chat creator and every administrator.
+    Admin,
+    /// The chat creator and administrators who can restrict members.
+    Moderator,
+    /// Any member of the chat.
+    All,
+}
+
+impl FromStr for CreationRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "admin" => Ok(CreationRole::Admin),
+            "moderator" => Ok(CreationRole::Moderator),
+            "all" => Ok(CreationRole::All),
+            other => Err(format!("unknown creation role {other:?}")),
+        }
+    }
+}
+
+/// The commands shown in Telegram's command menu, with their descriptions.
+const COMMANDS: &[(&str, &str)] = &[
+    ("start", "Create a new event"),
+    ("upcoming", "List this chat's events in the next days"),
+    ("pastevents", "List past and cancelled events"),
+    (
+        "list",
+        "List this chat's upcoming events, optionally by tag",
+    ),
+    ("tags", "Show the tags of this chat's upcoming events"),
+    ("search", "Find this chat's events by title"),
+    ("myevents", "List the events you created or are going to"),
+    ("clone", "Create a copy of an event at a new time"),
+    ("edit", "Edit one of your events"),
+    ("cancel", "Cancel one of your events"),
+    ("delete", "Delete one of your events"),
+    ("transfer", "Hand one of your events over to someone else"),
+    ("remind", "Remind the attendees of one of your events"),
+    ("block", "Stop someone from RSVPing to your events"),
+    ("unblock", "Let a blocked user RSVP to your events again"),
+    ("feedback", "Report a problem or suggest an improvement"),
+    ("ical", "Get an event as a calendar file"),
+    (
+        "export",
+        "Get the attendee list of one of your events, or back up all of the group's",
+    ),
+    ("timezone", "Set this chat's time zone"),
+    ("stats", "Show RSVP statistics for this group"),
+    (
+        "pruneold",
+        "Delete this group's events older than some days",
+    ),
+    ("settings", "Change this group's settings"),
+    ("addcreator", "Let someone create events in this group"),
+    (
+        "removecreator",
+        "Stop someone creating events in this group",
+    ),
+];
+
+/// Parses a comma-separated role list such as `admin,moderator`.
+pub fn parse_creation_roles(value: &str) -> Result<Vec<CreationRole>, String> {
+    value.split(',').map(CreationRole::from_str).collect()
+}
+
+pub struct Bot<C = LiveClient> {
+    api: C,
+    pool: SqlitePool,
+    creation_roles: Vec<CreationRole>,
+    event_contexts: HashMap<u64, EventContext>,
+    event_edit_contexts: HashMap<u64, EventEditContext>,
+    settings_contexts: HashMap<u64, SettingsContext>,
+    admin_cache: HashMap<i64, (Vec<i64>, Instant)>,
+    /// Display names by `(chat_id, user_id)`, with when they were looked up.
+    user_name_cache: HashMap<(i64, i64), (UserDisplay, Instant)>,
+    injected_updates: Option<mpsc::UnboundedReceiver<Update>>,
+    /// Users asked why they decline, with the `(event_id, chat_id,
+    /// message_id)` of the event message they pressed Decline on.
+    pending_declines: HashMap<i64, (i64, i64, i64)>,
+    /// Accepted users asked how many guests they bring, with the same
+    /// `(event_id, chat_id, message_id)` as `pending_declines`.
+    pending_guests: HashMap<i64, (i64, i64, i64)>,
+    /// Admins asked to confirm a `/pruneold`, by `(chat_id, user_id)`, with
+    /// the age in days of the events to delete.
+    pending_prunes: HashMap<(i64, u64), u32>,
+    draft_timeout: Duration,
+    poll_timeout: Duration,
+    poll_interval: Duration,
+    /// Who `/feedback` goes to, if anyone. Only they can see `/status`.
+    owner_id: Option<i64>,
+    started_at: Instant,
+    /// When a request to Telegram last succeeded, as shown by `/status`.
+    last_api_success: Arc<Mutex<Instant>>,
+    clear_commands: bool,
+    /// Set in test mode, where the command menu is left alone.
+    test_mode: bool,
+    /// Whether events are posted as replies to the `/start` they were
+    /// created with.
+    reply_to_create: bool,
+    parse_mode: ParseModeStrategy,
+    /// Pools opened by the health check to replace a failed one.
+    pool_updates: Option<watch::Receiver<SqlitePool>>,
+    /// The IDs of newly created events, for the reminder task.
+    new_events: broadcast::Sender<i64>,
+    /// Notified to shut the bot down, like SIGTERM does.
+    stop: Arc<Notify>,
+}
+
+// The bot has to be movable into spawned tasks.
+const _: fn() = || {
+    fn assert_send<T: Send>() {}
+    assert_send::<Bot>();
+};
+
+impl<C: TelegramClient> Bot<C> {
+    /// Creates the bot, picking up the saved event drafts.
+    ///
+    /// Fails if the database doesn't answer or Telegram rejects the token,
+    /// so that misconfiguration is caught before any updates are polled.
+    pub async fn new(api: C, pool: SqlitePool, config: &Config) -> Result<Self, BotError> {
+        sqlx::query("SELECT 1").execute(&pool).await?;
+        let me = api.get_me().await?.result;
+        info!(
+            "Bot started as @{}",
+            me.username.as_deref().unwrap_or(&me.first_name)
+        );
+
+        let mut bot = Self {
+            api,
+            pool,
+            creation_roles: config.creation_roles.clone(),
+            event_contexts: HashMap::new(),
+            event_edit_contexts: HashMap::new(),
+            settings_contexts: HashMap::new(),
+            admin_cache: HashMap::new(),
+            user_name_cache: HashMap::new(),
+            injected_updates: None,
+            pending_declines: HashMap::new(),
+            pending_guests: HashMap::new(),
+            pending_prunes: HashMap::new(),
+            draft_timeout: config.draft_timeout,
+            poll_timeout: config.poll_timeout,
+            poll_interval: config.poll_interval,
+            owner_id: config.owner_id,
+            started_at: Instant::now(),
+            last_api_success: Arc::new(Mutex::new(Instant::now())),
+            clear_commands: config.clear_commands,
+            test_mode: config.test_mode,
+            reply_to_create: config.reply_to_create,
+            parse_mode: config.parse_mode,
+            pool_updates: None,
+            new_events: broadcast::channel(NEW_EVENTS_CAPACITY).0,
+            stop: Arc::new(Notify::new()),
+        };
+        bot.restore_drafts().await;
+        Ok(bot)
+    }
+
+    /// Also handles updates received on `rx`, alongside those polled from Telegram.
+    pub fn with_injected_updates(mut self, rx: mpsc::UnboundedReceiver<Update>) -> Self {
+        self.injected_updates = Some(rx);
+        self
+    }
+
+    /// Switches to the pools received on `pools` when the database is reopened.
+    pub fn with_pool_updates(mut self, pools: watch::Receiver<SqlitePool>) -> Self {
+        self.pool_updates = Some(pools);
+        self
+    }
+
+    /// Returns a receiver of the IDs of the events created from now on,
+    /// clones included.
+    pub fn subscribe_new_events(&self) -> broadcast::Receiver<i64> {
+        self.new_events.subscribe()
+    }
+
+    /// Returns a handle that shuts the bot down gracefully when notified.
+    pub fn stop_handle(&self) -> Arc<Notify> {
+        self.stop.clone()
+    }
+
+    /// Picks up the pool the health check opened, if it reopened the database.
+    fn refresh_pool(&mut self) {
+        if let Some(pools) = &mut self.pool_updates {
+            if pools.has_changed().unwrap_or(false) {
+                self.pool = pools.borrow_and_update().clone();
+            }
+        }
+    }
+
+    /// Registers `COMMANDS` with Telegram so that clients can show them in
+    /// a menu, first clearing any old ones if `CLEAR_COMMANDS` is set.
+    /// Failures are only logged, the bot works without the menu. Skipped in
+    /// test mode, which usually runs with the token of the real bot.
+    async fn register_commands(&self) {
+        if self.test_mode {
+            info!("test mode, not registering bot commands");
+            return;
+        }
+
+        if self.clear_commands {
+            if let Err(err) = self
+                .api
+                .delete_my_commands(&DeleteMyCommandsParams::builder().build())
+                .await
+            {
+                warn!(%err, "failed to clear bot commands");
+            }
+        }
+
+        let commands = COMMANDS
+            .iter()
+            .map(|(command, description)| {
+                BotCommand::builder()
+                    .command(*command)
+                    .description(*description)
+                    .build()
+            })
+            .collect::<Vec<_>>();
+        let params = SetMyCommandsParams::builder().commands(commands).build();
+        if let Err(err) = self.api.set_my_commands(&params).await {
+            warn!(%err, "failed to register bot commands");
+        }
+    }
+
+    pub async fn run(&mut self) {
+        self.register_commands().await;
+
+        // Long polling keeps a request open until an update arrives or the
+        // timeout passes, so an idle bot makes one request per timeout.
+        let update_params_builder = GetUpdatesParams::builder()
+            .allowed_updates(allowed_updates())
+            .timeout(self.poll_timeout.as_secs() as u32);
+        let mut update_params = update_params_builder.clone().build();
+
+        let shutdown = Arc::new(AtomicBool::new(false));
+        tokio::spawn({
+            let shutdown = shutdown.clone();
+            let stop = self.stop.clone();
+            async move {
+                shutdown_signal(&stop).await;
+                shutdown.store(true, Ordering::SeqCst);
+            }
+        });
+
+        let mut error_backoff = POLL_ERROR_BACKOFF;
+        // The flag is only checked between batches, so that no update is
+        // dropped halfway through.
+        while !shutdown.load(Ordering::SeqCst) {
+            self.refresh_pool();
+            self.expire_drafts().await;
+            self.handle_injected_updates().await;
+
+            match self.api.get_updates(&update_params).await {
+                Ok(response) => {
+                    self.record_api_success();
+                    error_backoff = POLL_ERROR_BACKOFF;
+                    for update in response.result {
+                        update_params = update_params_builder
+                            .clone()
+                            .offset(update.update_id + 1)
+                            .build();
+                        self.handle_update(update).await;
+                    }
+
+                    // Long polls already wait for updates, the interval only
+                    // matters for short ones.
+                    if !self.poll_interval.is_zero() {
+                        tokio::time::sleep(self.poll_interval).await;
+                    }
+                }
+                Err(err) => {
+                    // Failures such as a dropped connection come back right
+                    // away, and would otherwise be retried in a busy loop.
+                    let wait = error_backoff.max(self.poll_interval);
+                    warn!(%err, ?wait, "failed to poll for updates");
+                    tokio::time::sleep(wait).await;
+                    error_backoff = (error_backoff * 2).min(MAX_POLL_ERROR_BACKOFF);
+                }
+            }
+        }
+
+        info!("shutting down");
+        self.shut_down().await;
+    }
+
+    /// Registers `url` as the bot's webhook and handles the updates Telegram
+    /// posts to it, via a server on `port`, until the process is interrupted.
+    /// The webhook is removed again on the way out.
+    pub async fn run_webhook(&mut self, url: &str, port: u16) -> Result<(), BotError> {
+        let params = SetWebhookParams::builder()
+            .url(url)
+            .allowed_updates(allowed_updates())
+            .build();
+        self.api.set_webhook(&params).await?;
+        info!(url, port, "webhook registered");
+        self.register_commands().await;
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let server = tokio::spawn(WebhookServer::new(port).run(tx));
+
+        let stop = self.stop.clone();
+        let shutdown = shutdown_signal(&stop);
+        tokio::pin!(shutdown);
+        loop {
+            tokio::select! {
+                update = rx.recv() => {
+                    let Some(update) = update else {
+                        break;
+                    };
+                    self.refresh_pool();
+                    self.expire_drafts().await;
+                    self.handle_injected_updates().await;
+                    self.handle_update(update).await;
+                }
+                _ = &mut shutdown => {
+                    info!("shutting down");
+                    break;
+                }
+            }
+        }
+        self.shut_down().await;
+
+        // Dropping the receiver stops the server.
+        drop(rx);
+        match server.await {
+            Ok(Err(err)) => error!(%err, "webhook server failed"),
+            Err(err) => error!(%err, "webhook server panicked"),
+            Ok(Ok(())) => {}
+        }
+
+        let params = DeleteWebhookParams::builder().build();
+        self.api.delete_webhook(&params).await?;
+
+        Ok(())
+    }
+
+    /// Closes the database. Event drafts need no saving, `store_draft`
+    /// keeps them up to date as they change.
+    async fn shut_down(&mut self) {
+        self.pool.close().await;
+    }
+
+    /// Saves the event draft of `user_id`, so that it survives a restart.
+    async fn store_draft(&self, user_id: u64) {
+        let Some(context) = self.event_contexts.get(&user_id) else {
+            return;
+        };
+        let stored = StoredDraft {
+            user_id: user_id as i64,
+            state: context.state,
+            origin_chat_id: context.clone_chat_id,
+            draft: context.draft.clone(),
+            timezone: context.timezone.map(|timezone| timezone.name().to_string()),
+            updated_at: Utc::now().naive_utc(),
+        };
+
+        if let Err(err) = drafts::upsert_draft(&self.pool, &stored).await {
+            error!(user_id, %err, "failed to save event draft");
+        }
+    }
+
+    /// Drops the event draft of `user_id`, both in memory and saved.
+    async fn forget_draft(&mut self, user_id: u64) {
+        self.event_contexts.remove(&user_id);
+
+        if let Err(err) = drafts::delete_draft(&self.pool, user_id as i64).await {
+            error!(user_id, %err, "failed to delete saved event draft");
+        }
+    }
+
+    /// Puts the drafts saved by `store_draft` back into `event_contexts`.
+    /// Their draft timeout counts from when they were last changed.
+    async fn restore_drafts(&mut self) {
+        let stored = match drafts::get_drafts(&self.pool).await {
+            Ok(stored) => stored,
+            Err(err) => {
+                error!(%err, "failed to load saved event drafts");
+                return;
+            }
+        };
+
+        let now = Utc::now().naive_utc();
+        for stored in stored {
+            let age = (now - stored.updated_at).to_std().unwrap_or_default();
+            let mut context =
+                EventContext::new(stored.timezone.and_then(|timezone| timezone.parse().ok()));
+            context.state = stored.state;
+            context.draft = stored.draft;
+            context.clone_chat_id = stored.origin_chat_id;
+            context.created_at = Instant::now()
+                .checked_sub(age)
+                .unwrap_or(context.created_at);
+            self.event_contexts.insert(stored.user_id as u64, context);
+        }
+
+        if !self.event_contexts.is_empty() {
+            info!(count = self.event_contexts.len(), "restored event drafts");
+        }
+    }
+
+    /// Drops the drafts older than `draft_timeout` and lets their users know.
+    async fn expire_drafts(&mut self) {
+        let timeout = self.draft_timeout;
+        let expired: Vec<u64> = self
+            .event_contexts
+            .iter()
+            .filter(|(_, context)| context.created_at.elapsed() >= timeout)
+            .map(|(user_id, _)| *user_id)
+            .collect();
+
+        for user_id in expired {
+            self.forget_draft(user_id).await;
+            debug!(user_id, "event draft expired");
+            self.send_message(
+                user_id as i64,
+                "Your event draft was cancelled because it wasn't finished in time. Use /start to begin again.",
+            ).await;
+        }
+    }
+
+    async fn handle_injected_updates(&mut self) {
+        while let Some(update) = self
+            .injected_updates
+            .as_mut()
+            .and_then(|rx| rx.try_recv().ok())
+        {
+            self.handle_update(update).await;
+        }
+    }
+
+    async fn handle_update(&mut self, update: Update) {
+        match update.content {
+            UpdateContent::Message(message) => self.handle_message(message).await,
+            UpdateContent::CallbackQuery(query) => match self.handle_callback_query(query).await {
+                Ok(()) => {}
+                Err(err @ BotError::PermissionDenied(_)) => {
+                    debug!(%err, "refused callback query");
+                }
+                Err(err) => error!(%err, "failed to handle callback query"),
+            },
+            UpdateContent::ChatMember(chat_member) => self.handle_chat_member(chat_member),
+            UpdateContent::MyChatMember(chat_member) => {
+                self.handle_my_chat_member(chat_member).await
+            }
+            UpdateContent::InlineQuery(query) => {
+                if let Err(err) = self.handle_inline_query(query).await {
+                    error!(%err, "failed to answer inline query");
+                }
+            }
+            _ => {}
+        }
+    }
+
+    #[instrument(skip_all, fields(chat_id = message.chat.id, message_id = message.message_id))]
+    async fn handle_message(&mut self, message: Message) {
+        // Telegram announces an upgrade both in the old group and in the new
+        // supergroup; whichever arrives first moves the data over.
+        if let Some(new_chat_id) = message.migrate_to_chat_id {
+            self.handle_chat_migration(message.chat.id, new_chat_id)
+                .await;
+            self.send_message(
+                new_chat_id,
+                "This group was upgraded to a supergroup. The events bot is still active here.",
+            )
+            .await;
+            return;
+        }
+        if let Some(old_chat_id) = message.migrate_from_chat_id {
+            self.handle_chat_migration(old_chat_id, message.chat.id)
+                .await;
+            return;
+        }
+
+        let from = message.from.unwrap();
+        let user_id = from.id;
+        let chat_id = message.chat.id;
+
+        if let Some(text) = message.text {
+            if text == "/start" {
+                self.handle_create(
+                    &message.chat.type_field,
+                    chat_id,
+                    user_id,
+                    message.message_id,
+                )
+                .await;
+            } else if let Some(args) = command_args(&text, "/timezone") {
+                self.handle_timezone(&message.chat.type_field, chat_id, user_id, args)
+                    .await;
+            } else if let Some(args) = command_args(&text, "/clone") {
+                self.handle_clone(chat_id, user_id, args).await;
+            } else if let Some(args) = command_args(&text, "/cancel") {
+                self.handle_delete(chat_id, user_id, args, CancelMode::Soft)
+                    .await;
+            } else if let Some(args) = command_args(&text, "/delete") {
+                self.handle_delete(chat_id, user_id, args, CancelMode::Hard)
+                    .await;
+            } else if let Some(args) = command_args(&text, "/transfer") {
+                self.handle_transfer(chat_id, user_id, args).await;
+            } else if let Some(args) = command_args(&text, "/remind") {
+                self.handle_remind(chat_id, user_id, args).await;
+            } else if text == "/status" {
+                self.handle_status(chat_id, user_id).await;
+            } else if text == "/listall" {
+                self.handle_list_all(&message.chat.type_field, chat_id, user_id)
+                    .await;
+            } else if let Some(args) = command_args(&text, "/block") {
+                self.handle_block(&message.chat.type_field, chat_id, user_id, args, true)
+                    .await;
+            } else if let Some(args) = command_args(&text, "/unblock") {
+                self.handle_block(&message.chat.type_field, chat_id, user_id, args, false)
+                    .await;
+            } else if let Some(args) = command_args(&text, "/feedback") {
+                self.handle_feedback(&message.chat.type_field, chat_id, &from, args)
+                    .await;
+            } else if let Some(args) = command_args(&text, "/pastevents") {
+                self.handle_past_events(chat_id, args).await;
+            } else if let Some(args) = command_args(&text, "/edit") {
+                self.handle_edit(chat_id, user_id, args).await;
+            } else if text == "/export" {
+                self.handle_export_all(&message.chat.type_field, chat_id, user_id)
+                    .await;
+            } else if let Some(args) = command_args(&text, "/export") {
+                self.handle_export(chat_id, user_id, args).await;
+            } else if let Some(args) = command_args(&text, "/ical") {
+                self.handle_ical(chat_id, user_id, args).await;
+            } else if let Some(args) = command_args(&text, "/list") {
+                self.handle_list(chat_id, args).await;
+            } else if text == "/tags" {
+                self.handle_tags(chat_id).await;
+            } else if let Some(args) = command_args(&text, "/search") {
+                self.handle_search(chat_id, args).await;
+            } else if let Some(args) = command_args(&text, "/upcoming") {
+                self.handle_upcoming(chat_id, args).await;
+            } else if let Some(args) = command_args(&text, "/myevents") {
+                self.handle_my_events(&message.chat.type_field, chat_id, user_id, args)
+                    .await;
+            } else if let Some(args) = command_args(&text, "/pruneold") {
+                self.handle_prune_old(&message.chat.type_field, chat_id, user_id, args)
+                    .await;
+            } else if text == "/stats" {
+                self.handle_stats(&message.chat.type_field, chat_id, user_id)
+                    .await;
+            } else if text == "/settings" {
+                self.handle_settings(&message.chat.type_field, chat_id, user_id)
+                    .await;
+            } else if let Some(args) = command_args(&text, "/addcreator") {
+                self.handle_trusted_creator(&message.chat.type_field, chat_id, user_id, args, true)
+                    .await;
+            } else if let Some(args) = command_args(&text, "/removecreator") {
+                self.handle_trusted_creator(
+                    &message.chat.type_field,
+                    chat_id,
+                    user_id,
+                    args,
+                    false,
+                )
+                .await;
+            } else if self.pending_prunes.contains_key(&(chat_id, user_id)) {
+                self.handle_prune_confirmation(chat_id, user_id, &text)
+                    .await;
+            } else if message.chat.type_field == ChatType::Private
+                && self.pending_declines.contains_key(&(user_id as i64))
+            {
+                self.handle_decline_reason(user_id as i64, text).await;
+            } else if message.chat.type_field == ChatType::Private
+                && self.pending_guests.contains_key(&(user_id as i64))
+            {
+                self.handle_guests(user_id as i64, text).await;
+            } else if message.chat.type_field == ChatType::Private
+                && self.settings_contexts.contains_key(&user_id)
+            {
+                self.handle_settings_input(user_id, text).await;
+            } else if message.chat.type_field == ChatType::Private
+                && self.event_edit_contexts.contains_key(&user_id)
+            {
+                self.handle_event_edit(user_id, text).await;
+            } else {
+                self.handle_event_creation(chat_id, user_id, text).await;
+            }
+        }
+    }
+
+    async fn handle_create(
+        &mut self,
+        chat_type: &ChatType,
+        chat_id: i64,
+        user_id: u64,
+        message_id: i32,
+    ) {
+        let mut timezone = None;
+
+        if *chat_type != ChatType::Private {
+            if !self.creation_roles.contains(&CreationRole::All) {
+                match self.can_create_events(chat_id, user_id).await {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        self.send_message(
+                            chat_id,
+                            "Event creation is restricted to admins in this chat.",
+                        )
+                        .await;
+                        return;
+                    }
+                    Err(err) => {
+                        error!(chat_id, user_id, %err, "failed to check creation permissions");
+                        self.send_message(
+                            chat_id,
+                            "Could not check your permissions in this chat.",
+                        )
+                        .await;
+                        return;
+                    }
+                }
+            }
+
+            match group_settings::get_settings(&self.pool, chat_id).await {
+                Ok(settings) => match self.creation_denied_by_settings(&settings, user_id).await {
+                    Ok(None) => {}
+                    Ok(Some(reply)) => {
+                        self.send_message(chat_id, reply).await;
+                        return;
+                    }
+                    Err(err) => {
+                        error!(chat_id, user_id, %err, "failed to check creation permissions");
+                        self.send_message(
+                            chat_id,
+                            "Could not check your permissions in this chat.",
+                        )
+                        .await;
+                        return;
+                    }
+                },
+                Err(err) => error!(chat_id, %err, "failed to load group settings"),
+            }
+
+            timezone = self.group_timezone(chat_id).await;
+        }
+
+        let mut context = EventContext::new(timezone);
+        context.origin_message_id = Some(message_id);
+        context.origin_message_chat_id = Some(chat_id);
+        self.event_contexts.insert(user_id, context);
+        self.store_draft(user_id).await;
+
+        self.send_message(chat_id, "Please enter the Name of the event.")
+            .await;
+    }
+
+    #[instrument(skip(self, text))]
+    async fn handle_event_creation(&mut self, chat_id: i64, user_id: u64, text: String) {
+        let Some(context) = self.event_contexts.get_mut(&user_id) else {
+            return;
+        };
+
+        let field = match context.state {
+            UserState::AwaitingName => Some(EventField::Title),
+            UserState::AwaitingDescription => Some(EventField::Description),
+            UserState::AwaitingLocation => Some(EventField::Location),
+            _ => None,
+        };
+        if let Some(field) = field {
+            if let Some(reply) = length_error(field, &text) {
+                self.send_message(chat_id, &reply).await;
+                return;
+            }
+        }
+
+        match context.state {
+            UserState::AwaitingName => {
+                context.draft.name = text;
+                context.state = UserState::AwaitingDescription;
+                self.store_draft(user_id).await;
+
+                self.send_message(chat_id, "Please enter an Event description.")
+                    .await;
+            }
+            UserState::AwaitingDescription => {
+                // Desktop clients let descriptions span several lines.
+                context.draft.description = sanitize_text(&text);
+                context.state = UserState::AwaitingLocation;
+                self.store_draft(user_id).await;
+
+                self.send_message(chat_id, "Please enter the Location of the event.")
+                    .await;
+            }
+            UserState::AwaitingLocation => {
+                context.draft.location = text;
+                context.state = UserState::AwaitingTime;
+                self.store_draft(user_id).await;
+
+                self.send_message(
+                    chat_id,
+                    &format!("Please enter the Time the event takes place, as {TIME_FORMATS_HINT}, optionally followed by a timezone such as America/Toronto."),
+                ).await;
+            }
+            UserState::AwaitingTime => {
+                let utc_time = match parse_event_time(&text, context.timezone) {
+                    Ok(utc_time) => utc_time,
+                    Err(reply) => {
+                        self.send_message(chat_id, &reply).await;
+                        return;
+                    }
+                };
+
+                context.draft.time = utc_time.format(DATETIME_FORMAT).to_string();
+                if context.clone_chat_id.is_some() {
+                    self.save_draft(chat_id, user_id).await;
+                    return;
+                }
+                context.state = UserState::AwaitingUrl;
+                self.store_draft(user_id).await;
+
+                self.send_message(
+                    chat_id,
+                    "Please enter a link for the event (starting with http:// or https://), or 'skip'.",
+                ).await;
+            }
+            UserState::AwaitingUrl => {
+                let url = text.trim();
+                if url.eq_ignore_ascii_case("skip") {
+                    context.draft.url = None;
+                } else if is_valid_url(url) {
+                    context.draft.url = Some(url.to_string());
+                } else {
+                    self.send_message(
+                        chat_id,
+                        "Links have to start with http:// or https://. Please enter another one, or 'skip'.",
+                    ).await;
+                    return;
+                }
+                context.state = UserState::AwaitingTags;
+                self.store_draft(user_id).await;
+
+                self.send_message(
+                    chat_id,
+                    "Please enter tags for the event, separated by commas (e.g. sports, social), or 'skip'.",
+                )
+                .await;
+            }
+            UserState::AwaitingTags => {
+                context.draft.tags = if text.trim().eq_ignore_ascii_case("skip") {
+                    Vec::new()
+                } else {
+                    parse_tags(&text)
+                };
+                context.state = UserState::AwaitingCapacity;
+                self.store_draft(user_id).await;
+
+                self.send_message(
+                    chat_id,
+                    "Please enter the maximum number of attendees, or 'unlimited'.",
+                )
+                .await;
+            }
+            UserState::AwaitingCapacity => {
+                context.draft.max_capacity = match text.trim().to_lowercase().as_str() {
+                    "unlimited" => None,
+                    number => match number.parse::<i64>() {
+                        Ok(max_capacity) if max_capacity > 0 => Some(max_capacity),
+                        _ => {
+                            self.send_message(
+                                chat_id,
+                                "Please enter a positive number of attendees, or 'unlimited'.",
+                            )
+                            .await;
+                            return;
+                        }
+                    },
+                };
+                context.state = UserState::AwaitingGuestPolicy;
+                self.store_draft(user_id).await;
+
+                self.send_message(
+                    chat_id,
+                    "How many people can each attendee RSVP for, themselves included? Enter 1 for no guests, or a bigger number.",
+                ).await;
+            }
+            UserState::AwaitingGuestPolicy => {
+                context.draft.guests_per_rsvp = match text.trim().parse::<i64>() {
+                    Ok(guests_per_rsvp) if (1..=MAX_GUESTS_PER_RSVP).contains(&guests_per_rsvp) => {
+                        guests_per_rsvp
+                    }
+                    _ => {
+                        self.send_message(
+                            chat_id,
+                            &format!("Please enter a number from 1 to {MAX_GUESTS_PER_RSVP}."),
+                        )
+                        .await;
+                        return;
+                    }
+                };
+                context.state = UserState::AwaitingDeadline;
+                self.store_draft(user_id).await;
+
+                self.send_message(
+                    chat_id,
+                    &format!("Please enter the time RSVPs close, as {TIME_FORMATS_HINT}, or 'skip' to keep them open."),
+                ).await;
+            }
+            UserState::AwaitingDeadline => {
+                if text.trim().eq_ignore_ascii_case("skip") {
+                    context.draft.rsvp_deadline = None;
+                } else {
+                    let deadline = match parse_event_time(&text, context.timezone) {
+                        Ok(deadline) => deadline.format(DATETIME_FORMAT).to_string(),
+                        Err(reply) => {
+                            self.send_message(chat_id, &reply).await;
+                            return;
+                        }
+                    };
+                    if deadline > context.draft.time {
+                        self.send_message(
+                            chat_id,
+                            "RSVPs have to close before the event starts. Please enter an earlier time, or 'skip'.",
+                        ).await;
+                        return;
+                    }
+                    context.draft.rsvp_deadline = Some(deadline);
+                }
+                context.state = UserState::AwaitingAnonymous;
+                self.store_draft(user_id).await;
+
+                self.send_message(
+                    chat_id,
+                    "Anonymous RSVPs? Only you will see who's coming, everyone else sees counts. yes/no",
+                )
+                .await;
+            }
+            UserState::AwaitingAnonymous => {
+                context.draft.anonymous = match text.trim().to_lowercase().as_str() {
+                    "yes" => true,
+                    "no" => false,
+                    _ => {
+                        self.send_message(chat_id, "Please answer 'yes' or 'no'.")
+                            .await;
+                        return;
+                    }
+                };
+
+                self.save_draft(chat_id, user_id).await;
+            }
+        }
+    }
+
+    /// Stores the finished draft of `user_id` and posts it to its chat.
+    /// `chat_id` is where the user is entering the draft.
+    async fn save_draft(&mut self, chat_id: i64, user_id: u64) {
+        let Some(context) = self.event_contexts.get_mut(&user_id) else {
+            return;
+        };
+        context.draft.sanitize();
+        let event_chat_id = context.clone_chat_id.unwrap_or(chat_id);
+        let reply_to = context.origin_message_id.filter(|_| {
+            self.reply_to_create && context.origin_message_chat_id == Some(event_chat_id)
+        });
+
+        match db::create_event(&self.pool, event_chat_id, user_id as i64, &context.draft).await {
+            Ok(event_id) => {
+                // Fails only when nobody is subscribed.
+                let _ = self.new_events.send(event_id);
+                self.forget_draft(user_id).await;
+                self.send_message(chat_id, "The Event has been saved.")
+                    .await;
+
+                if let Err(err) = self.post_event(event_id, reply_to).await {
+                    error!(chat_id = event_chat_id, event_id, %err, "failed to post event");
+                }
+            }
+            Err(BotError::DuplicateEvent) => {
+                self.forget_draft(user_id).await;
+                self.send_message(
+                    chat_id,
+                    "An event with this title and time already exists in the group.",
+                )
+                .await;
+            }
+            Err(err @ BotError::LimitExceeded(_)) => {
+                self.forget_draft(user_id).await;
+                self.send_message(user_id as i64, &err.to_string()).await;
+            }
+            Err(e) => {
+                self.send_message(chat_id, &format!("Failed to save event: {}", e))
+                    .await
+            }
+        }
+    }
+
+    /// Starts a copy of one of the user's events that only needs a new time.
+    async fn handle_clone(&mut self, chat_id: i64, user_id: u64, args: &str) {
+        let Ok(event_id) = args.parse::<i64>() else {
+            self.send_message(chat_id, "Usage: /clone <event_id>").await;
+            return;
+        };
+
+        let event = match db::get_event(&self.pool, event_id).await {
+            Ok(Some(event)) => event,
+            Ok(None) => {
+                self.send_message(chat_id, &format!("There is no event {event_id}."))
+                    .await;
+                return;
+            }
+            Err(e) => {
+                self.send_message(chat_id, &format!("Failed to load event: {}", e))
+                    .await;
+                return;
+            }
+        };
+
+        if event.creator != user_id as i64 {
+            self.send_message(chat_id, "Only the event's creator can clone it.")
+                .await;
+            return;
+        }
+
+        let timezone = self.group_timezone(event.chat_id).await;
+        self.event_contexts
+            .insert(user_id, EventContext::clone_of(&event, timezone));
+        self.store_draft(user_id).await;
+
+        self.send_message(
+            chat_id,
+            &format!(
+                "Cloning «{}». Please enter the Time of the new event, as {TIME_FORMATS_HINT}, optionally followed by a timezone such as America/Toronto.",
+                event.name
+            ),
+        ).await;
+    }
+
+    /// Records an RSVP button press and refreshes the event message it came from.
+    #[instrument(skip_all, fields(user_id = query.from.id, data = query.data))]
+    async fn handle_callback_query(&mut self, query: CallbackQuery) -> Result<(), BotError> {
+        if let Some(data) = query
+            .data
+            .as_deref()
+            .and_then(|data| data.strip_prefix(LIST_PAGE_CALLBACK_PREFIX))
+        {
+            self.handle_list_page_callback(&query, data).await;
+            return Ok(());
+        }
+
+        if let Some(data) = query.data.as_deref().filter(|data| {
+            data.starts_with(MY_EVENTS_CREATED_PREFIX)
+                || data.starts_with(MY_EVENTS_ATTENDING_PREFIX)
+        }) {
+            self.handle_my_events_callback(&query, data).await;
+            return Ok(());
+        }
+
+        if let Some(data) = query.data.as_deref() {
+            for prefix in [
+                DELETE_CALLBACK_PREFIX,
+                CONFIRM_DELETE_CALLBACK_PREFIX,
+                CANCEL_DELETE_CALLBACK_PREFIX,
+            ] {
+                if let Some(event_id) = data.strip_prefix(prefix).and_then(|id| id.parse().ok()) {
+                    return self.handle_delete_callback(&query, prefix, event_id).await;
+                }
+            }
+        }
+
+        if let Some(event_id) = query
+            .data
+            .as_deref()
+            .and_then(|data| data.strip_prefix(CHECK_IN_CALLBACK_PREFIX))
+            .and_then(|id| id.parse().ok())
+        {
+            return self.handle_check_in_callback(&query, event_id).await;
+        }
+
+        if let Some(event_id) = query
+            .data
+            .as_deref()
+            .and_then(|data| data.strip_prefix(EDIT_START_CALLBACK_PREFIX))
+            .and_then(|id| id.parse().ok())
+        {
+            self.handle_edit_start_callback(&query, event_id).await;
+            return Ok(());
+        }
+
+        if query
+            .data
+            .as_deref()
+            .is_some_and(|data| data.starts_with(ENDED_CALLBACK_PREFIX))
+        {
+            self.answer_callback_query(&query.id, "🏁 This event has already taken place.")
+                .await;
+            return Ok(());
+        }
+
+        let Some((status, event_id)) = query
+            .data
+            .as_deref()
+            .and_then(RsvpStatus::from_callback_data)
+        else {
+            self.answer_callback_query(&query.id, "This button is no longer supported.")
+                .await;
+            return Ok(());
+        };
+        let user_id = query.from.id as i64;
+
+        if status == RsvpStatus::Declined && self.ask_decline_reason(&query, event_id).await? {
+            self.answer_callback_query(
+                &query.id,
+                "Please tell me in our private chat why you can't come.",
+            )
+            .await;
+            return Ok(());
+        }
+
+        let (promoted, status) = match self.record_rsvp(event_id, user_id, status).await {
+            Ok(RsvpOutcome::Recorded { promoted, status }) => (promoted, status),
+            Ok(RsvpOutcome::Missing) => {
+                self.answer_callback_query(&query.id, "This event no longer exists.")
+                    .await;
+                return Ok(());
+            }
+            Ok(RsvpOutcome::Cancelled) => {
+                self.answer_callback_query(&query.id, "🚫 This event has been cancelled.")
+                    .await;
+                return Ok(());
+            }
+            Ok(RsvpOutcome::Closed) => {
+                self.answer_callback_query(&query.id, "⏰ RSVP for this event is closed.")
+                    .await;
+                return Ok(());
+            }
+            Ok(RsvpOutcome::Blocked) => {
+                self.answer_callback_query(
+                    &query.id,
+                    "You are not permitted to RSVP for this event.",
+                )
+                .await;
+                return Ok(());
+            }
+            Err(err) => {
+                self.answer_callback_query(
+                    &query.id,
+                    "Could not update your RSVP, please try again.",
+                )
+                .await;
+                return Err(err.into());
+            }
+        };
+
+        let answer = match status.as_deref() {
+            None => "Removed",
+            Some("waitlist") => "⏳ Added to the waitlist",
+            Some(_) => "✅ RSVP updated",
+        };
+        self.answer_callback_query(&query.id, answer).await;
+
+        let Some(event) = self.fetch_event(event_id).await? else {
+            return Ok(());
+        };
+
+        if user_id != event.creator {
+            self.notify_creator(&event, user_id, status.as_deref())
+                .await;
+        }
+        if status.as_deref() == Some(RsvpStatus::Accepted.as_str()) {
+            self.warn_about_conflicts(&event, user_id).await;
+        }
+
+        if let Some(promoted) = promoted {
+            self.send_message(
+                promoted,
+                &format!(
+                    "A spot opened up for «{}». You've been moved from the waitlist to the accepted list.",
+                    event.name
+                ),
+            ).await;
+        }
+
+        if let Some(MaybeInaccessibleMessage::Message(message)) = query.message {
+            if status.as_deref() == Some(RsvpStatus::Accepted.as_str()) && event.max_guests() > 0 {
+                self.ask_guests(&event, user_id, message.chat.id, message.message_id)
+                    .await;
+            }
+            self.edit_rsvp_message(&event, message.chat.id, message.message_id)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Lets a user who accepted `event` know, in a private chat, which of the
+    /// other events they accepted take place around the same time.
+    async fn warn_about_conflicts(&self, event: &Event, user_id: i64) {
+        let conflicts = match EventConflictChecker::default()
+            .check(user_id, event, &self.pool)
+            .await
+        {
+            Ok(conflicts) if conflicts.is_empty() => return,
+            Ok(conflicts) => conflicts,
+            Err(err) => {
+                error!(event_id = event.id, user_id, %err, "failed to check for conflicts");
+                return;
+            }
+        };
+
+        let names = conflicts
+            .iter()
+            .map(|other| format!("'{}'", other.name))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let params = SendMessageParams::builder()
+            .chat_id(user_id)
+            .text(format!(
+                "⚠️ This event overlaps with {names} you've already accepted."
+            ))
+            .build();
+        // Users who never started a private chat with the bot can't be warned.
+        if let Err(err) =
+            api_call_with_retry(|| self.api.send_message(&params), MAX_API_RETRIES).await
+        {
+            debug!(event_id = event.id, user_id, %err, "can't warn about conflicts");
+        }
+    }
+
+    /// Asks a user who accepted an event that allows guests how many they're
+    /// bringing, in a private chat.
+    async fn ask_guests(&mut self, event: &Event, user_id: i64, chat_id: i64, message_id: i32) {
+        let params = SendMessageParams::builder()
+            .chat_id(user_id)
+            .text(format!(
+                "How many guests are you bringing to «{}»? Enter a number from 0 to {}.",
+                event.name,
+                event.max_guests()
+            ))
+            .build();
+        if let Err(err) =
+            api_call_with_retry(|| self.api.send_message(&params), MAX_API_RETRIES).await
+        {
+            debug!(event_id = event.id, user_id, %err, "can't ask for guests");
+            return;
+        }
+
+        self.pending_guests
+            .insert(user_id, (event.id, chat_id, message_id as i64));
+    }
+
+    /// Records the number of guests a user was asked for.
+    async fn handle_guests(&mut self, user_id: i64, text: String) {
+        let Some((event_id, chat_id, message_id)) = self.pending_guests.remove(&user_id) else {
+            return;
+        };
+        let mut event = match self.fetch_event(event_id).await {
+            Ok(Some(event)) => event,
+            Ok(None) => {
+                self.send_message(user_id, "This event no longer exists.")
+                    .await;
+                return;
+            }
+            Err(e) => {
+                self.send_message(user_id, &format!("Failed to load event: {}", e))
+                    .await;
+                return;
+            }
+        };
+
+        let guests = match text.trim().parse::<i64>() {
+            Ok(guests) if (0..=event.max_guests()).contains(&guests) => guests,
+            _ => {
+                self.pending_guests
+                    .insert(user_id, (event_id, chat_id, message_id));
+                self.send_message(
+                    user_id,
+                    &format!("Please enter a number from 0 to {}.", event.max_guests()),
+                )
+                .await;
+                return;
+            }
+        };
+
+        match db::set_guests(&self.pool, event_id, user_id, guests).await {
+            Ok(true) => {}
+            Ok(false) => {
+                self.send_message(
+                    user_id,
+                    &format!("You're no longer on the accepted list of «{}».", event.name),
+                )
+                .await;
+                return;
+            }
+            Err(err) => {
+                error!(event_id, user_id, %err, "failed to save guests");
+                self.send_message(user_id, "Could not save your guests, please try again.")
+                    .await;
+                return;
+            }
+        }
+        let reply = match guests {
+            0 => "Thanks, see you there!".to_string(),
+            1 => "Thanks, you're bringing 1 guest.".to_string(),
+            n => format!("Thanks, you're bringing {n} guests."),
+        };
+        self.send_message(user_id, &reply).await;
+
+        if guests > 0 {
+            event.guests.insert(user_id, guests);
+        } else {
+            event.guests.remove(&user_id);
+        }
+        if let Err(err) = self
+            .edit_rsvp_message(&event, chat_id, message_id as i32)
+            .await
+        {
+            error!(event_id, %err, "failed to update event message");
+        }
+    }
+
+    /// Checks the user pressing an event's check-in button in, if they're
+    /// on its accepted or maybe list.
+    async fn handle_check_in_callback(
+        &mut self,
+        query: &CallbackQuery,
+        event_id: i64,
+    ) -> Result<(), BotError> {
+        let Some(mut event) = self.fetch_event(event_id).await? else {
+            self.answer_callback_query(&query.id, "This event no longer exists.")
+                .await;
+            return Ok(());
+        };
+        let now = Utc::now().naive_utc();
+        if !event.check_in_open(now) {
+            let answer = if event.cancelled_at.is_some() {
+                "🚫 This event has been cancelled."
+            } else if event.has_started(now) {
+                "🏁 This event has already taken place."
+            } else {
+                "Check-in opens on the day of the event."
+            };
+            self.answer_callback_query(&query.id, answer).await;
+            return Ok(());
+        }
+
+        let user_id = query.from.id as i64;
+        if !db::check_in(&self.pool, event_id, user_id).await? {
+            self.answer_callback_query(
+                &query.id,
+                "Only people who accepted or might come can check in.",
+            )
+            .await;
+            return Ok(());
+        }
+        self.answer_callback_query(&query.id, "✔️ Checked in").await;
+        if event.checked_in.contains(&user_id) {
+            return Ok(());
+        }
+        event.checked_in.push(user_id);
+
+        if let Some(MaybeInaccessibleMessage::Message(message)) = &query.message {
+            self.edit_rsvp_message(&event, message.chat.id, message.message_id)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Re-renders an event message with RSVP buttons after an RSVP.
+    async fn edit_rsvp_message(
+        &self,
+        event: &Event,
+        chat_id: i64,
+        message_id: i32,
+    ) -> Result<(), BotError> {
+        let timezone = self.group_timezone(event.chat_id).await.unwrap_or(Tz::UTC);
+        // Private chats have the ID of the user they're with.
+        let viewer = (chat_id > 0).then_some(chat_id);
+        let mut params = EditMessageTextParams::builder()
+            .chat_id(chat_id)
+            .message_id(message_id)
+            .text(event.format_message_with(timezone, viewer, self.parse_mode))
+            .reply_markup(event.create_keyboard(viewer == Some(event.creator)))
+            .build();
+        params.parse_mode = self.parse_mode.parse_mode();
+        api_call_with_retry(|| self.api.edit_message_text(&params), MAX_API_RETRIES).await?;
+
+        // Keeps `reload_event_message` from skipping an update the posted
+        // message needs.
+        if event.chat_id == chat_id && event.message_id == Some(message_id) {
+            let hash = format!("{:016x}", event.attendee_list_hash());
+            db::set_message_hash(&self.pool, event.id, &hash).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Asks the user pressing Decline for their reason in a private chat,
+    /// holding off the RSVP until they answer. Returns `false`, so that the
+    /// decline is recorded right away, if the event can't take RSVPs, the
+    /// user is already declined (and so removes their RSVP), or they can't
+    /// be messaged.
+    async fn ask_decline_reason(
+        &mut self,
+        query: &CallbackQuery,
+        event_id: i64,
+    ) -> Result<bool, BotError> {
+        let user_id = query.from.id as i64;
+        let Some(event) = db::get_event(&self.pool, event_id).await? else {
+            return Ok(false);
+        };
+        if event.cancelled_at.is_some()
+            || event.rsvp_closed(Utc::now().naive_utc())
+            || db::is_blocked(&self.pool, event.creator, user_id).await?
+        {
+            return Ok(false);
+        }
+        let status = db::get_attendee_status(&self.pool, event_id, user_id).await?;
+        if status.as_deref() == Some(RsvpStatus::Declined.as_str()) {
+            return Ok(false);
+        }
+
+        let (chat_id, message_id) = match &query.message {
+            Some(MaybeInaccessibleMessage::Message(message)) => {
+                (message.chat.id, message.message_id)
+            }
+            _ => match event.message_id {
+                Some(message_id) => (event.chat_id, message_id),
+                None => return Ok(false),
+            },
+        };
+
+        let params = SendMessageParams::builder()
+            .chat_id(user_id)
+            .text(format!(
+                "What's your reason for declining «{}»? (type anything or 'skip')",
+                event.name
+            ))
+            .build();
+        if let Err(err) =
+            api_call_with_retry(|| self.api.send_message(&params), MAX_API_RETRIES).await
+        {
+            debug!(event_id, user_id, %err, "can't ask for a decline reason");
+            return Ok(false);
+        }
+
+        self.pending_declines
+            .insert(user_id, (event_id, chat_id, message_id as i64));
+        Ok(true)
+    }
+
+    /// Records the decline a user was asked the reason for, along with
+    /// `text` as the reason unless it's 'skip'.
+    async fn handle_decline_reason(&mut self, user_id: i64, text: String) {
+        let Some((event_id, chat_id, message_id)) = self.pending_declines.remove(&user_id) else {
+            return;
+        };
+        if let Some(excess) = excess_length(&text, MAX_DECLINE_REASON_LEN) {
+            self.pending_declines
+                .insert(user_id, (event_id, chat_id, message_id));
+            self.send_message(
+                user_id,
+                &format!("The reason can be at most {MAX_DECLINE_REASON_LEN} characters long; yours is {excess} over. Please enter a shorter one, or 'skip'."),
+            ).await;
+            return;
+        }
+
+        match self
+            .record_rsvp(event_id, user_id, RsvpStatus::Declined)
+            .await
+        {
+            Ok(RsvpOutcome::Recorded { promoted, status }) => {
+                let reason = sanitize_text(&text);
+                if !reason.eq_ignore_ascii_case("skip") && !reason.is_empty() {
+                    if let Err(err) =
+                        db::set_decline_reason(&self.pool, event_id, user_id, &reason).await
+                    {
+                        error!(event_id, user_id, %err, "failed to save decline reason");
+                    }
+                }
+                self.send_message(user_id, "Thanks, you've declined the event.")
+                    .await;
+
+                let event = match self.fetch_event(event_id).await {
+                    Ok(Some(event)) => event,
+                    Ok(None) => return,
+                    Err(err) => {
+                        error!(event_id, %err, "failed to load event");
+                        return;
+                    }
+                };
+                if user_id != event.creator {
+                    self.notify_creator(&event, user_id, status.as_deref())
+                        .await;
+                }
+                if let Some(promoted) = promoted {
+                    self.send_message(
+                        promoted,
+                        &format!(
+                            "A spot opened up for «{}». You've been moved from the waitlist to the accepted list.",
+                            event.name
+                        ),
+                    ).await;
+                }
+                if let Err(err) = self
+                    .edit_rsvp_message(&event, chat_id, message_id as i32)
+                    .await
+                {
+                    error!(event_id, %err, "failed to update event message");
+                }
+            }
+            Ok(RsvpOutcome::Missing) => {
+                self.send_message(user_id, "This event no longer exists.")
+                    .await
+            }
+            Ok(RsvpOutcome::Cancelled) => {
+                self.send_message(user_id, "🚫 This event has been cancelled.")
+                    .await
+            }
+            Ok(RsvpOutcome::Closed) => {
+                self.send_message(user_id, "⏰ RSVP for this event is closed.")
+                    .await
+            }
+            Ok(RsvpOutcome::Blocked) => {
+                self.send_message(user_id, "You are not permitted to RSVP for this event.")
+                    .await
+            }
+            Err(e) => {
+                self.send_message(user_id, &format!("Failed to update your RSVP: {}", e))
+                    .await
+            }
+        }
+    }
+
+    /// Tells the creator of `event` about a user's new RSVP `status`, unless
+    /// the group opted out.
+    async fn notify_creator(&mut self, event: &Event, user_id: i64, status: Option<&str>) {
+        match group_settings::get_settings(&self.pool, event.chat_id).await {
+            Ok(settings) if !settings.notify_creator => return,
+            Ok(_) => {}
+            Err(err) => {
+                error!(chat_id = event.chat_id, %err, "failed to load group settings");
+                return;
+            }
+        }
+
+        let name = self.get_user_name(event.chat_id, user_id).await;
+        let action = match status {
+            Some("accepted") => "accepted your event",
+            Some("declined") => "declined your event",
+            Some("maybe") => "might come to your event",
+            Some("waitlist") => "joined the waitlist of your event",
+            _ => "removed their RSVP to your event",
+        };
+        self.send_message(
+            event.creator,
+            &format!("👤 {name} {action} «{}»", event.name),
+        )
+        .await;
+    }
+
+    /// Applies an RSVP, unless the event is gone or its RSVPs are closed.
+    async fn record_rsvp(
+        &self,
+        event_id: i64,
+        user_id: i64,
+        status: RsvpStatus,
+    ) -> Result<RsvpOutcome, sqlx::Error> {
+        let Some(event) = db::get_event(&self.pool, event_id).await? else {
+            return Ok(RsvpOutcome::Missing);
+        };
+        if event.cancelled_at.is_some() {
+            return Ok(RsvpOutcome::Cancelled);
+        }
+        if event.rsvp_closed(Utc::now().naive_utc()) {
+            return Ok(RsvpOutcome::Closed);
+        }
+        if db::is_blocked(&self.pool, event.creator, user_id).await? {
+            return Ok(RsvpOutcome::Blocked);
+        }
+
+        let promoted = db::update_attendance(&self.pool, event_id, user_id, status).await?;
+        let status = db::get_attendee_status(&self.pool, event_id, user_id).await?;
+
+        Ok(RsvpOutcome::Recorded { promoted, status })
+    }
+
+    /// Sends the message for `event_id`, with its RSVP buttons, to the chat
+    /// it was created in, as a reply to `reply_to` if given, and remembers the
+    /// message so it can be edited later.
+    async fn post_event(&mut self, event_id: i64, reply_to: Option<i32>) -> Result<(), BotError> {
+        if let Some(event) = self.fetch_event(event_id).await? {
+            let message_id = self
+                .send_event(event.chat_id, &event, true, reply_to)
+                .await?;
+            db::set_event_message_id(&self.pool, event_id, Some(message_id)).await?;
+
+            let settings = group_settings::get_settings(&self.pool, event.chat_id).await?;
+            if settings.pinned {
+                let params = PinChatMessageParams::builder()
+                    .chat_id(event.chat_id)
+                    .message_id(message_id)
+                    .disable_notification(true)
+                    .build();
+                // The bot may not be allowed to pin messages in the group.
+                if let Err(err) = self.api.pin_chat_message(&params).await {
+                    warn!(chat_id = event.chat_id, event_id, %err, "failed to pin event message");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Unpins the posted message of `event`, in case it was pinned.
+    async fn unpin_event_message(&self, event: &Event) {
+        let Some(message_id) = event.message_id else {
+            return;
+        };
+
+        let params = UnpinChatMessageParams::builder()
+            .chat_id(event.chat_id)
+            .message_id(message_id)
+            .build();
+        // Fails harmlessly for messages that weren't pinned.
+        if let Err(err) = self.api.unpin_chat_message(&params).await {
+            debug!(event_id = event.id, %err, "failed to unpin event message");
+        }
+    }
+
+    /// Sends `event` to `chat_id` and returns the ID of the sent message.
+    /// Only `public` messages, posted for the event's group, and the
+    /// creator's own private view of an upcoming event get RSVP buttons, the
+    /// latter with Edit and Delete buttons too. Others get an informational
+    /// button once the event has taken place.
+    async fn list_event(&self, chat_id: i64, event: &Event, public: bool) -> Result<i32, BotError> {
+        self.send_event(chat_id, event, public, None).await
+    }
+
+    /// Sends `event` like `list_event`, as a reply to `reply_to` if given.
+    /// The reply is dropped if that message is gone.
+    async fn send_event(
+        &self,
+        chat_id: i64,
+        event: &Event,
+        public: bool,
+        reply_to: Option<i32>,
+    ) -> Result<i32, BotError> {
+        let timezone = self.group_timezone(event.chat_id).await.unwrap_or(Tz::UTC);
+        // Private chats have the ID of the user they're with.
+        let viewer = (chat_id > 0).then_some(chat_id);
+        let mut params = SendMessageParams::builder()
+            .chat_id(chat_id)
+            .text(event.format_message_with(timezone, viewer, self.parse_mode))
+            .build();
+        params.parse_mode = self.parse_mode.parse_mode();
+        let owner_view = viewer == Some(event.creator);
+        let upcoming = event.cancelled_at.is_none() && !event.has_started(Utc::now().naive_utc());
+        if public || (owner_view && upcoming) {
+            params.reply_markup = Some(ReplyMarkup::InlineKeyboardMarkup(
+                event.create_keyboard(owner_view),
+            ));
+        } else if event.cancelled_at.is_none() && !upcoming {
+            params.reply_markup = Some(ReplyMarkup::InlineKeyboardMarkup(event.ended_keyboard()));
+        }
+        params.reply_parameters = reply_to.map(|message_id| {
+            ReplyParameters::builder()
+                .message_id(message_id)
+                .allow_sending_without_reply(true)
+                .build()
+        });
+        let response =
+            api_call_with_retry(|| self.api.send_message(&params), MAX_API_RETRIES).await?;
+
+        Ok(response.result.message_id)
+    }
+
+    /// Re-renders the posted message of `event_id` after the event changed.
+    async fn reload_event_message(&mut self, event_id: i64) -> Result<(), BotError> {
+        let Some(event) = self.fetch_event(event_id).await? else {
+            return Ok(());
+        };
+        let Some(message_id) = event.message_id else {
+            return Ok(());
+        };
+        // Edits other than RSVPs forget the hash, so this only skips updates
+        // that wouldn't change the message.
+        let hash = format!("{:016x}", event.attendee_list_hash());
+        if event.message_hash.as_deref() == Some(hash.as_str()) {
+            return Ok(());
+        }
+
+        let timezone = self.group_timezone(event.chat_id).await.unwrap_or(Tz::UTC);
+        let mut params = EditMessageTextParams::builder()
+            .chat_id(event.chat_id)
+            .message_id(message_id)
+            .text(event.format_message_with(timezone, None, self.parse_mode))
+            .build();
+        params.parse_mode = self.parse_mode.parse_mode();
+        // Leaving out the keyboard removes the RSVP buttons of cancelled events.
+        if event.cancelled_at.is_none() {
+            // Events posted in their creator's private chat are the owner's view.
+            params.reply_markup = Some(event.create_keyboard(event.chat_id == event.creator));
+        }
+        match api_call_with_retry(|| self.api.edit_message_text(&params), MAX_API_RETRIES).await {
+            Ok(_) => db::set_message_hash(&self.pool, event_id, &hash).await?,
+            // Only a failed edit is worth checking whether the message is gone.
+            Err(err @ BotError::Telegram(frankenstein::Error::Api(_))) => {
+                if self.verify_event_message_exists(event_id).await? {
+                    return Err(err);
+                }
+                warn!(event_id, "event message was deleted, posting it again");
+                db::set_event_message_id(&self.pool, event_id, None).await?;
+                if event.cancelled_at.is_none() {
+                    self.post_event(event_id, None).await?;
+                }
+            }
+            Err(err) => return Err(err),
+        }
+
+        Ok(())
+    }
+
+    /// Whether the message an event was posted as still exists, rather than
+    /// having been deleted from its chat. Telegram can't look messages up, so
+    /// this sets the keyboard the message should have anyway and sees whether
+    /// Telegram finds the message to edit.
+    async fn verify_event_message_exists(&mut self, event_id: i64) -> Result<bool, BotError> {
+        let Some(event) = db::get_event(&self.pool, event_id).await? else {
+            return Ok(false);
+        };
+        let Some(message_id) = event.message_id else {
+            return Ok(false);
+        };
+
+        let mut params = EditMessageReplyMarkupParams::builder()
+            .chat_id(event.chat_id)
+            .message_id(message_id)
+            .build();
+        if event.cancelled_at.is_none() {
+            params.reply_markup = Some(event.create_keyboard(event.chat_id == event.creator));
+        }
+        match self.api.edit_message_reply_markup(&params).await {
+            Ok(_) => Ok(true),
+            Err(frankenstein::Error::Api(response))
+                if response.description.contains("message is not modified") =>
+            {
+                Ok(true)
+            }
+            Err(frankenstein::Error::Api(response))
+                if response.description.contains("message to edit not found") =>
+            {
+                Ok(false)
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Cancels one of the user's events, or asks them to confirm deleting it.
+    async fn handle_delete(&mut self, chat_id: i64, user_id: u64, args: &str, mode: CancelMode) {
+        let command = match mode {
+            CancelMode::Soft => "cancel",
+            CancelMode::Hard => "delete",
+        };
+        let Ok(event_id) = args.parse::<i64>() else {
+            self.send_message(chat_id, &format!("Usage: /{command} <event_id>"))
+                .await;
+            return;
+        };
+
+        let event = match db::get_event(&self.pool, event_id).await {
+            Ok(Some(event)) => event,
+            Ok(None) => {
+                self.send_message(chat_id, &format!("There is no event {event_id}."))
+                    .await;
+                return;
+            }
+            Err(e) => {
+                self.send_message(chat_id, &format!("Failed to load event: {}", e))
+                    .await;
+                return;
+            }
+        };
+
+        if event.creator != user_id as i64 {
+            self.send_message(
+                chat_id,
+                &format!("Only the event's creator can {command} it."),
+            )
+            .await;
+            return;
+        }
+        if mode == CancelMode::Soft && event.cancelled_at.is_some() {
+            self.send_message(chat_id, &format!("«{}» is already cancelled.", event.name))
+                .await;
+            return;
+        }
+
+        // Deleting can't be undone, so it waits for the creator to confirm.
+        if mode == CancelMode::Hard {
+            let params = SendMessageParams::builder()
+                .chat_id(chat_id)
+                .text(format!(
+                    "Are you sure you want to delete «{}»? This can't be undone.",
+                    event.name
+                ))
+                .reply_markup(ReplyMarkup::InlineKeyboardMarkup(
+                    event.delete_confirmation_keyboard(),
+                ))
+                .build();
+            if let Err(err) =
+                api_call_with_retry(|| self.api.send_message(&params), MAX_API_RETRIES).await
+            {
+                error!(chat_id, event_id, %err, "failed to ask for delete confirmation");
+            }
+            return;
+        }
+
+        let reply = match self.remove_event(&event, mode).await {
+            Ok(()) => format!("«{}» has been cancelled.", event.name),
+            Err(e) => format!("Failed to {command} event: {}", e),
+        };
+        self.send_message(chat_id, &reply).await;
+    }
+
+    /// Cancels or deletes `event`. Cancelling tells everyone who accepted and
+    /// marks the event's message as cancelled; deleting removes the message.
+    async fn remove_event(&mut self, event: &Event, mode: CancelMode) -> Result<(), BotError> {
+        db::delete_event(&self.pool, event.id, mode).await?;
+        self.unpin_event_message(event).await;
+
+        match mode {
+            CancelMode::Soft => {
+                self.notify_cancellation(event).await;
+                if let Err(err) = self.reload_event_message(event.id).await {
+                    error!(event_id = event.id, %err, "failed to update event message");
+                }
+            }
+            CancelMode::Hard => {
+                if let Some(message_id) = event.message_id {
+                    let params = DeleteMessageParams::builder()
+                        .chat_id(event.chat_id)
+                        .message_id(message_id)
+                        .build();
+
+                    // Bots can't delete messages older than 48 hours in groups.
+                    if let Err(err) = self.api.delete_message(&params).await {
+                        warn!(event_id = event.id, %err, "failed to delete event message");
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Handles an event's Delete button, which asks for confirmation in
+    /// place of the message's buttons, and the buttons answering it.
+    async fn handle_delete_callback(
+        &mut self,
+        query: &CallbackQuery,
+        prefix: &str,
+        event_id: i64,
+    ) -> Result<(), BotError> {
+        let Some(MaybeInaccessibleMessage::Message(message)) = &query.message else {
+            self.answer_callback_query(&query.id, "This message is too old to change.")
+                .await;
+            return Ok(());
+        };
+        let (chat_id, message_id) = (message.chat.id, message.message_id);
+
+        let Some(event) = self.fetch_event(event_id).await? else {
+            self.answer_callback_query(&query.id, "This event no longer exists.")
+                .await;
+            return Ok(());
+        };
+        if event.creator != query.from.id as i64 {
+            self.answer_callback_query(&query.id, NOT_AUTHORIZED).await;
+            return Err(BotError::PermissionDenied(format!(
+                "user {} isn't the creator of event {event_id}",
+                query.from.id
+            )));
+        }
+        // The event's own message is removed on deletion and gets its RSVP
+        // buttons back otherwise, as do the creator's private views of it;
+        // others are replaced by a note.
+        let is_event_message = event.chat_id == chat_id && event.message_id == Some(message_id);
+        let is_owner_view = chat_id == event.creator;
+
+        if prefix == DELETE_CALLBACK_PREFIX {
+            let params = EditMessageReplyMarkupParams::builder()
+                .chat_id(chat_id)
+                .message_id(message_id)
+                .reply_markup(event.delete_confirmation_keyboard())
+                .build();
+            self.api.edit_message_reply_markup(&params).await?;
+            self.answer_callback_query(&query.id, "Are you sure?").await;
+        } else if prefix == CONFIRM_DELETE_CALLBACK_PREFIX {
+            self.remove_event(&event, CancelMode::Hard).await?;
+            self.answer_callback_query(&query.id, "🗑️ Deleted.").await;
+            if !is_event_message {
+                self.edit_message_text(
+                    chat_id,
+                    message_id,
+                    &format!("«{}» has been deleted.", event.name),
+                )
+                .await?;
+            }
+        } else {
+            self.answer_callback_query(&query.id, "Kept.").await;
+            if is_event_message || is_owner_view {
+                self.edit_rsvp_message(&event, chat_id, message_id).await?;
+            } else {
+                self.edit_message_text(chat_id, message_id, &format!("«{}» was kept.", event.name))
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the text of a message with plain `text`, dropping its buttons.
+    async fn edit_message_text(
+        &self,
+        chat_id: i64,
+        message_id: i32,
+        text: &str,
+    ) -> Result<(), BotError> {
+        let params = EditMessageTextParams::builder()
+            .chat_id(chat_id)
+            .message_id(message_id)
+            .text(text)
+            .build();
+        api_call_with_retry(|| self.api.edit_message_text(&params), MAX_API_RETRIES).await?;
+
+        Ok(())
+    }
+
+    /// Sends everyone who accepted or might come to one of the user's events
+    /// a private reminder, at most once every `MANUAL_REMINDER_COOLDOWN`.
+    async fn handle_remind(&mut self, chat_id: i64, user_id: u64, args: &str) {
+        let Ok(event_id) = args.parse::<i64>() else {
+            self.send_message(chat_id, "Usage: /remind <event_id>")
+                .await;
+            return;
+        };
+
+        let event = match self.fetch_event(event_id).await {
+            Ok(Some(event)) => event,
+            Ok(None) => {
+                self.send_message(chat_id, &format!("There is no event {event_id}."))
+                    .await;
+                return;
+            }
+            Err(e) => {
+                self.send_message(chat_id, &format!("Failed to load event: {}", e))
+                    .await;
+                return;
+            }
+        };
+
+        if event.creator != user_id as i64 {
+            self.send_message(
+                chat_id,
+                "Only the event's creator can send reminders for it.",
+            )
+            .await;
+            return;
+        }
+
+        let now = Utc::now().naive_utc();
+        match db::claim_manual_reminder(&self.pool, event_id, now, now - MANUAL_REMINDER_COOLDOWN)
+            .await
+        {
+            Ok(true) => {}
+            Ok(false) => {
+                self.send_message(
+                    chat_id,
+                    &format!(
+                        "A reminder for «{}» was already sent in the last {} hours.",
+                        event.name,
+                        MANUAL_REMINDER_COOLDOWN.num_hours()
+                    ),
+                )
+                .await;
+                return;
+            }
+            Err(e) => {
+                self.send_message(chat_id, &format!("Failed to send reminder: {}", e))
+                    .await;
+                return;
+            }
+        }
+
+        let timezone = self.group_timezone(event.chat_id).await.unwrap_or(Tz::UTC);
+        let mut text = format!(
+            "📣 Reminder: «{}» is on {}",
+            event.name,
+            Event::format_time(&event.time, timezone)
+        );
+        if !event.location.is_empty() {
+            text.push_str(&format!(" at {}", event.location));
+        }
+        text.push_str(". See you there!");
+
+        let mut reminded = 0;
+        for (attendee, _) in event.accepted.iter().chain(&event.maybe) {
+            let params = SendMessageParams::builder()
+                .chat_id(*attendee)
+                .text(&text)
+                .build();
+            match api_call_with_retry(|| self.api.send_message(&params), MAX_API_RETRIES).await {
+                Ok(_) => reminded += 1,
+                // Users who never started a private chat with the bot can't be messaged.
+                Err(err) => debug!(event_id, attendee, %err, "failed to send reminder"),
+            }
+        }
+
+        if reminded == 0 {
+            if let Err(err) = db::release_manual_reminder(&self.pool, event_id, now).await {
+                error!(event_id, %err, "failed to release reminder cooldown");
+            }
+            self.send_message(
+                chat_id,
+                "No attendees could be reminded. They need to start a private chat with the bot first.",
+            )
+            .await;
+            return;
+        }
+
+        self.send_message(chat_id, &format!("Reminder sent to {reminded} attendees."))
+            .await;
+    }
+
+    /// Makes someone else the creator of an event, after checking they're in
+    /// its chat and can be messaged privately.
+    async fn handle_transfer(&mut self, chat_id: i64, user_id: u64, args: &str) {
+        let mut args = args.split_whitespace();
+        let (Some(event_id), Some(new_owner), None) = (args.next(), args.next(), args.next())
+        else {
+            self.send_message(chat_id, "Usage: /transfer <event_id> <user_id>")
+                .await;
+            return;
+        };
+        let Ok(event_id) = event_id.parse::<i64>() else {
+            self.send_message(chat_id, "Usage: /transfer <event_id> <user_id>")
+                .await;
+            return;
+        };
+        if new_owner.starts_with('@') {
+            self.send_message(
+                chat_id,
+                "Bots can't look users up by @username, please give the new organizer's numeric user ID.",
+            ).await;
+            return;
+        }
+        let Ok(new_owner) = new_owner.parse::<u64>() else {
+            self.send_message(chat_id, "Usage: /transfer <event_id> <user_id>")
+                .await;
+            return;
+        };
+
+        let event = match db::get_event(&self.pool, event_id).await {
+            Ok(Some(event)) => event,
+            Ok(None) => {
+                self.send_message(chat_id, &format!("There is no event {event_id}."))
+                    .await;
+                return;
+            }
+            Err(e) => {
+                self.send_message(chat_id, &format!("Failed to load event: {}", e))
+                    .await;
+                return;
+            }
+        };
+
+        if event.creator != user_id as i64 {
+            self.send_message(chat_id, "Only the event's creator can transfer it.")
+                .await;
+            return;
+        }
+        if new_owner == user_id {
+            self.send_message(chat_id, &format!("You already organize «{}».", event.name))
+                .await;
+            return;
+        }
+
+        let params = GetChatMemberParams::builder()
+            .chat_id(event.chat_id)
+            .user_id(new_owner)
+            .build();
+        let is_member = match self.api.get_chat_member(&params).await {
+            Ok(response) => in_chat(&response.result),
+            Err(err) => {
+                warn!(event_id, new_owner, %err, "failed to look up chat member");
+                false
+            }
+        };
+        if !is_member {
+            self.send_message(
+                chat_id,
+                &format!("User {new_owner} isn't a member of the event's chat."),
+            )
+            .await;
+            return;
+        }
+
+        if let Err(e) = db::set_event_creator(&self.pool, event_id, new_owner as i64).await {
+            self.send_message(chat_id, &format!("Failed to transfer event: {}", e))
+                .await;
+            return;
+        }
+
+        // Transferred back if the new owner can't be told, so nobody ends up
+        // organizing an event without knowing it.
+        let params = SendMessageParams::builder()
+            .chat_id(new_owner as i64)
+            .text(format!("You are now the organizer of «{}».", event.name))
+            .build();
+        if let Err(err) =
+            api_call_with_retry(|| self.api.send_message(&params), MAX_API_RETRIES).await
+        {
+            warn!(event_id, new_owner, %err, "failed to message the new organizer");
+            if let Err(err) = db::set_event_creator(&self.pool, event_id, event.creator).await {
+                error!(event_id, %err, "failed to undo event transfer");
+            }
+            self.send_message(
+                chat_id,
+                &format!(
+                    "I can't message user {new_owner}, so «{}» wasn't transferred. They need to start a private chat with me first.",
+                    event.name
+                ),
+            ).await;
+            return;
+        }
+
+        let new_owner_name = self.get_user_name(event.chat_id, new_owner as i64).await;
+        self.send_message(
+            chat_id,
+            &format!("«{}» is now organized by {new_owner_name}.", event.name),
+        )
+        .await;
+    }
+
+    /// Lets everyone who accepted `event` know it was cancelled.
+    async fn notify_cancellation(&self, event: &Event) {
+        let attendees = match db::get_attendees(&self.pool, event.id).await {
+            Ok(attendees) => attendees,
+            Err(err) => {
+                error!(event_id = event.id, %err, "failed to load attendees");
+                return;
+            }
+        };
+
+        for (user_id, _) in attendees.iter().filter(|(_, status)| status == "accepted") {
+            self.send_message(
+                *user_id,
+                &format!("🚫 «{}» has been cancelled.", event.name),
+            )
+            .await;
+        }
+    }
+
+    /// Lists the chat's 10 latest past and cancelled events.
+    async fn handle_past_events(&mut self, chat_id: i64, args: &str) {
+        let page = parse_page_number(args);
+        let events = match db::fetch_past_events(&self.pool, chat_id, Utc::now().naive_utc()).await
+        {
+            Ok(events) => events,
+            Err(e) => {
+                self.send_message(chat_id, &format!("Failed to load events: {}", e))
+                    .await;
+                return;
+            }
+        };
+
+        if events.is_empty() {
+            self.send_message(chat_id, "There are no past events.")
+                .await;
+            return;
+        }
+        let (events, footer) = paginate(events, page, "/pastevents");
+        if events.is_empty() {
+            self.send_message(chat_id, &format!("There is no page {page}."))
+                .await;
+            return;
+        }
+
+        for mut event in events {
+            let result = match self.fetch_attendees(&mut event).await {
+                Ok(_) => self.list_event(chat_id, &event, false).await.map(|_| ()),
+                Err(err) => Err(err),
+            };
+            if let Err(err) = result {
+                error!(chat_id, event_id = event.id, %err, "failed to list event");
+            }
+        }
+        if let Some(footer) = footer {
+            self.send_message(chat_id, &footer).await;
+        }
+    }
+
+    /// Shows the bot's owner how the bot is doing, in a private chat. Figures
+    /// that can't be read from the database show as "DB error".
+    async fn handle_status(&mut self, chat_id: i64, user_id: u64) {
+        if self.owner_id != Some(user_id as i64) {
+            self.send_message(chat_id, "Only the bot's owner can see its status.")
+                .await;
+            return;
+        }
+
+        let count = |result: Result<i64, sqlx::Error>| match result {
+            Ok(count) => count.to_string(),
+            Err(err) => {
+                warn!(%err, "failed to count rows for /status");
+                "DB error".to_string()
+            }
+        };
+        let events = count(db::stats::count_events(&self.pool).await);
+        let attendees = count(db::stats::count_attendees(&self.pool).await);
+        let last_api_success = self
+            .last_api_success
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .elapsed();
+
+        let text = format!(
+            "Bot status\nUptime: {}\nEvents: {events}\nRSVPs: {attendees}\nDrafts in progress: {}\nLast successful Telegram request: {} ago",
+            format_duration(self.started_at.elapsed()),
+            self.event_contexts.len(),
+            format_duration(last_api_success),
+        );
+        self.send_message(user_id as i64, &text).await;
+    }
+
+    /// Blocks (`block`) or unblocks a user from RSVPing to the sender's
+    /// events. Blocking also removes their RSVPs to the sender's upcoming
+    /// events, so their name is gone from the attendee lists.
+    async fn handle_block(
+        &mut self,
+        chat_type: &ChatType,
+        chat_id: i64,
+        user_id: u64,
+        args: &str,
+        block: bool,
+    ) {
+        let command = if block { "block" } else { "unblock" };
+        if *chat_type != ChatType::Private {
+            self.send_message(
+                chat_id,
+                &format!("Use /{command} in a private chat with me."),
+            )
+            .await;
+            return;
+        }
+        let Ok(blocked) = args.parse::<i64>() else {
+            self.send_message(chat_id, &format!("Usage: /{command} <user_id>"))
+                .await;
+            return;
+        };
+        let creator = user_id as i64;
+        if blocked == creator {
+            self.send_message(chat_id, "You can't block yourself.")
+                .await;
+            return;
+        }
+
+        if !block {
+            let reply = match db::unblock_attendee(&self.pool, creator, blocked).await {
+                Ok(true) => format!("User {blocked} can RSVP to your events again."),
+                Ok(false) => format!("User {blocked} isn't blocked."),
+                Err(e) => format!("Failed to unblock the user: {}", e),
+            };
+            self.send_message(chat_id, &reply).await;
+            return;
+        }
+
+        match db::block_attendee(&self.pool, creator, blocked).await {
+            Ok(true) => {}
+            Ok(false) => {
+                self.send_message(chat_id, &format!("User {blocked} is already blocked."))
+                    .await;
+                return;
+            }
+            Err(e) => {
+                self.send_message(chat_id, &format!("Failed to block the user: {}", e))
+                    .await;
+                return;
+            }
+        }
+
+        let event_ids = match db::get_created_event_ids_with_attendee(
+            &self.pool,
+            creator,
+            blocked,
+            Utc::now().naive_utc(),
+        )
+        .await
+        {
+            Ok(event_ids) => event_ids,
+            Err(err) => {
+                error!(user_id, blocked, %err, "failed to load the blocked user's RSVPs");
+                Vec::new()
+            }
+        };
+        for &event_id in &event_ids {
+            match db::remove_attendee(&self.pool, event_id, blocked).await {
+                Ok(Some(promoted)) => {
+                    if let Ok(Some(event)) = db::get_event(&self.pool, event_id).await {
+                        self.send_message(
+                            promoted,
+                            &format!(
+                                "A spot opened up for «{}». You've been moved from the waitlist to the accepted list.",
+                                event.name
+                            ),
+                        ).await;
+                    }
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    error!(event_id, blocked, %err, "failed to remove blocked user's RSVP");
+                    continue;
+                }
+            }
+            if let Err(err) = self.reload_event_message(event_id).await {
+                error!(event_id, %err, "failed to update event message");
+            }
+        }
+
+        let reply = match event_ids.len() {
+            0 => format!("User {blocked} can no longer RSVP to your events."),
+            1 => format!("User {blocked} can no longer RSVP to your events. Their RSVP to 1 of your upcoming events was removed."),
+            n => format!("User {blocked} can no longer RSVP to your events. Their RSVPs to {n} of your upcoming events were removed."),
+        };
+        self.send_message(chat_id, &reply).await;
+    }
+
+    /// Shows the bot's owner a line about every chat with events, in messages
+    /// of `LIST_ALL_PAGE_SIZE` lines.
+    async fn handle_list_all(&mut self, chat_type: &ChatType, chat_id: i64, user_id: u64) {
+        if *chat_type != ChatType::Private {
+            self.send_message(chat_id, "Use /listall in a private chat with me.")
+                .await;
+            return;
+        }
+        if self.owner_id != Some(user_id as i64) {
+            self.send_message(chat_id, "Only the bot's owner can list all events.")
+                .await;
+            return;
+        }
+
+        let summaries =
+            match db::stats::get_chat_summaries(&self.pool, Utc::now().naive_utc()).await {
+                Ok(summaries) => summaries,
+                Err(e) => {
+                    self.send_message(chat_id, &format!("Failed to load events: {}", e))
+                        .await;
+                    return;
+                }
+            };
+        if summaries.is_empty() {
+            self.send_message(chat_id, "No chat has any events yet.")
+                .await;
+            return;
+        }
+
+        let lines = summaries
+            .iter()
+            .map(|summary| {
+                let chat = match &summary.title {
+                    Some(title) => title.clone(),
+                    None => format!("Chat {}", summary.chat_id),
+                };
+                let events = match summary.events {
+                    1 => "1 event".to_string(),
+                    n => format!("{n} events"),
+                };
+                match &summary.next_event {
+                    Some((name, time)) => {
+                        let date = time.split(' ').next().unwrap_or(time);
+                        format!("{chat}: {events}, next: {name} on {date}")
+                    }
+                    None => format!("{chat}: {events}, none upcoming"),
+                }
+            })
+            .collect::<Vec<_>>();
+        for page in lines.chunks(LIST_ALL_PAGE_SIZE) {
+            self.send_message(chat_id, &page.join("\n")).await;
+        }
+    }
+
+    /// Passes a user's feedback on to the bot's owner, at most
+    /// `MAX_FEEDBACK_PER_DAY` times a day.
+    async fn handle_feedback(
+        &mut self,
+        chat_type: &ChatType,
+        chat_id: i64,
+        from: &User,
+        args: &str,
+    ) {
+        if *chat_type != ChatType::Private {
+            self.send_message(chat_id, "Use /feedback in a private chat with me.")
+                .await;
+            return;
+        }
+        let user_id = from.id as i64;
+        let Some(owner_id) = self.owner_id else {
+            self.send_message(user_id, "Feedback isn't set up for this bot.")
+                .await;
+            return;
+        };
+        let feedback = sanitize_text(args);
+        if feedback.is_empty() {
+            self.send_message(user_id, "Usage: /feedback <message>")
+                .await;
+            return;
+        }
+        if let Some(excess) = excess_length(&feedback, MAX_FEEDBACK_LEN) {
+            self.send_message(
+                user_id,
+                &format!("Feedback can be at most {MAX_FEEDBACK_LEN} characters long; yours is {excess} over."),
+            ).await;
+            return;
+        }
+
+        let now = Utc::now().naive_utc();
+        match db::claim_feedback(
+            &self.pool,
+            user_id,
+            now,
+            now - chrono::Duration::days(1),
+            MAX_FEEDBACK_PER_DAY,
+        )
+        .await
+        {
+            Ok(true) => {}
+            Ok(false) => {
+                self.send_message(
+                    user_id,
+                    &format!("You can send at most {MAX_FEEDBACK_PER_DAY} feedback messages a day. Please try again tomorrow."),
+                ).await;
+                return;
+            }
+            Err(err) => {
+                error!(user_id, %err, "failed to log feedback");
+                self.send_message(user_id, "Could not send your feedback, please try again.")
+                    .await;
+                return;
+            }
+        }
+
+        let sender = match &from.username {
+            Some(username) => format!("@{username}"),
+            None => from.first_name.clone(),
+        };
+        let params = SendMessageParams::builder()
+            .chat_id(owner_id)
+            .text(format!("Feedback from {sender} ({user_id}): {feedback}"))
+            .build();
+        if let Err(err) =
+            api_call_with_retry(|| self.api.send_message(&params), MAX_API_RETRIES).await
+        {
+            error!(user_id, owner_id, %err, "failed to forward feedback");
+            self.send_message(user_id, "Could not send your feedback, please try again.")
+                .await;
+            return;
+        }
+        self.send_message(user_id, "Thanks for your feedback!")
+            .await;
+    }
+
+    /// Sends an event's .ics file to the user's private chat. From a group,
+    /// only that group's events can be exported; in private, the user has to
+    /// have created or answered the event.
+    async fn handle_ical(&mut self, chat_id: i64, user_id: u64, args: &str) {
+        let Ok(event_id) = args.parse::<i64>() else {
+            self.send_message(chat_id, "Usage: /ical <event_id>").await;
+            return;
+        };
+
+        let event = match db::get_event(&self.pool, event_id).await {
+            Ok(Some(event)) => event,
+            Ok(None) => {
+                self.send_message(chat_id, &format!("There is no event {event_id}."))
+                    .await;
+                return;
+            }
+            Err(e) => {
+                self.send_message(chat_id, &format!("Failed to load event: {}", e))
+                    .await;
+                return;
+            }
+        };
+
+        let private = chat_id == user_id as i64;
+        let allowed = if private {
+            event.creator == user_id as i64
+                || matches!(
+                    db::get_attendee_status(&self.pool, event_id, user_id as i64).await,
+                    Ok(Some(_))
+                )
+        } else {
+            event.chat_id == chat_id
+        };
+        if !allowed {
+            self.send_message(chat_id, &format!("There is no event {event_id}."))
+                .await;
+            return;
+        }
+
+        let calendar = match ical::to_ical(&event, Utc::now().naive_utc()) {
+            Ok(calendar) => calendar,
+            Err(err) => {
+                error!(event_id, %err, "failed to export event");
+                self.send_message(chat_id, "This event's time can't be exported.")
+                    .await;
+                return;
+            }
+        };
+
+        let filename = format!("event-{event_id}.ics");
+        match self
+            .send_document_bytes(user_id as i64, calendar.into_bytes(), &filename)
+            .await
+        {
+            Ok(_) if !private => {
+                self.send_message(
+                    chat_id,
+                    "I've sent you the calendar file in a private chat.",
+                )
+                .await
+            }
+            Ok(_) => {}
+            Err(err) => {
+                warn!(event_id, user_id, %err, "failed to send calendar file");
+                self.send_message(
+                    chat_id,
+                    "Could not send you the calendar file. Start a private chat with me and try again.",
+                ).await;
+            }
+        }
+    }
+
+    /// Sends the creator of an event its attendee list as a CSV file, in
+    /// their private chat.
+    async fn handle_export(&mut self, chat_id: i64, user_id: u64, args: &str) {
+        let Ok(event_id) = args.parse::<i64>() else {
+            self.send_message(chat_id, "Usage: /export <event_id>")
+                .await;
+            return;
+        };
+
+        let event = match self.fetch_event(event_id).await {
+            Ok(Some(event)) => event,
+            Ok(None) => {
+                self.send_message(chat_id, &format!("There is no event {event_id}."))
+                    .await;
+                return;
+            }
+            Err(e) => {
+                self.send_message(chat_id, &format!("Failed to load event: {}", e))
+                    .await;
+                return;
+            }
+        };
+
+        if event.creator != user_id as i64 {
+            self.send_message(chat_id, "Only the event's creator can export it.")
+                .await;
+            return;
+        }
+        if event.accepted.is_empty()
+            && event.waitlist.is_empty()
+            && event.declined.is_empty()
+            && event.maybe.is_empty()
+        {
+            self.send_message(
+                chat_id,
+                &format!("Nobody has RSVP'd to «{}» yet.", event.name),
+            )
+            .await;
+            return;
+        }
+
+        let filename = format!("attendees_{event_id}.csv");
+        let private = chat_id == user_id as i64;
+        match self
+            .send_document_bytes(user_id as i64, csv::to_csv(&event).into_bytes(), &filename)
+            .await
+        {
+            Ok(_) if !private => {
+                self.send_message(
+                    chat_id,
+                    "I've sent you the attendee list in a private chat.",
+                )
+                .await
+            }
+            Ok(_) => {}
+            Err(err) => {
+                warn!(event_id, user_id, %err, "failed to send attendee list");
+                self.send_message(
+                    chat_id,
+                    "Could not send you the attendee list. Start a private chat with me and try again.",
+                ).await;
+            }
+        }
+    }
+
+    /// Sends a group admin all of the group's events and their attendees as
+    /// a JSON file, in their private chat.
+    async fn handle_export_all(&mut self, chat_type: &ChatType, chat_id: i64, user_id: u64) {
+        if *chat_type == ChatType::Private {
+            self.send_message(
+                chat_id,
+                "Use /export in the group chat to back up its events, or /export <event_id> for one of yours.",
+            )
+            .await;
+            return;
+        }
+
+        match self.is_group_admin(chat_id, user_id).await {
+            Ok(true) => {}
+            Ok(false) => {
+                self.send_message(chat_id, "Only group administrators can export all events.")
+                    .await;
+                return;
+            }
+            Err(err) => {
+                error!(chat_id, user_id, %err, "failed to check export permissions");
+                self.send_message(chat_id, "Could not check your permissions in this chat.")
+                    .await;
+                return;
+            }
+        }
+
+        let mut events = match db::fetch_all_events(&self.pool, chat_id).await {
+            Ok(events) => events,
+            Err(e) => {
+                self.send_message(chat_id, &format!("Failed to load events: {}", e))
+                    .await;
+                return;
+            }
+        };
+        for event in &mut events {
+            if let Err(e) = self.fetch_attendees(event).await {
+                self.send_message(chat_id, &format!("Failed to load events: {}", e))
+                    .await;
+                return;
+            }
+        }
+        let json = match json::to_json(&events) {
+            Ok(json) => json,
+            Err(err) => {
+                error!(chat_id, %err, "failed to serialize events");
+                self.send_message(chat_id, "Could not export the events.")
+                    .await;
+                return;
+            }
+        };
+
+        let filename = format!(
+            "events_{chat_id}_{}.json",
+            Utc::now().date_naive().format("%Y-%m-%d")
+        );
+        match self
+            .send_document_bytes(user_id as i64, json.into_bytes(), &filename)
+            .await
+        {
+            Ok(_) => {
+                self.send_message(chat_id, "I've sent you the events in a private chat.")
+                    .await
+            }
+            Err(err) => {
+                warn!(chat_id, user_id, %err, "failed to send event export");
+                self.send_message(
+                    chat_id,
+                    "Could not send you the events. Start a private chat with me and try again.",
+                )
+                .await;
+            }
+        }
+    }
+
+    /// Offers buttons to list the upcoming events the user created or is
+    /// going to, by date (`/myevents` or `/myevents date`) or in the order
+    /// they were created (`/myevents created`).
+    async fn handle_my_events(
+        &mut self,
+        chat_type: &ChatType,
+        chat_id: i64,
+        user_id: u64,
+        args: &str,
+    ) {
+        if *chat_type != ChatType::Private {
+            self.send_message(chat_id, "Use /myevents in a private chat with me.")
+                .await;
+            return;
+        }
+        let (sort, page) = split_page_number(args);
+        let Some(sort) = parse_event_sort(sort) else {
+            self.send_message(chat_id, "Usage: /myevents [date|created] [page]")
+                .await;
+            return;
+        };
+
+        let sort = event_sort_name(sort);
+        let button = |text: &str, prefix: &str| {
+            InlineKeyboardButton::builder()
+                .text(text)
+                .callback_data(format!("{prefix}{user_id}_{sort}_{page}"))
+                .build()
+        };
+        let keyboard = InlineKeyboardMarkup::builder()
+            .inline_keyboard(vec![vec![
+                button("📋 Created", MY_EVENTS_CREATED_PREFIX),
+                button("🎟 Attending", MY_EVENTS_ATTENDING_PREFIX),
+            ]])
+            .build();
+        let params = SendMessageParams::builder()
+            .chat_id(chat_id)
+            .text("Which of your events?")
+            .reply_markup(ReplyMarkup::InlineKeyboardMarkup(keyboard))
+            .build();
+
+        if let Err(err) =
+            api_call_with_retry(|| self.api.send_message(&params), MAX_API_RETRIES).await
+        {
+            error!(chat_id, %err, "failed to send my events menu");
+        }
+    }
+
+    /// Handles the buttons of `handle_my_events`, whose callback data is a
+    /// prefix followed by the ID of the user they are for, the sort order and
+    /// the page.
+    async fn handle_my_events_callback(&mut self, query: &CallbackQuery, data: &str) {
+        let user_id = query.from.id as i64;
+        let (created, owner) = match data.strip_prefix(MY_EVENTS_CREATED_PREFIX) {
+            Some(owner) => (true, owner),
+            None => (
+                false,
+                data.strip_prefix(MY_EVENTS_ATTENDING_PREFIX)
+                    .unwrap_or_default(),
+            ),
+        };
+        let mut parts = owner.splitn(3, '_');
+        let owner = parts.next().unwrap_or_default();
+        let sort = parse_event_sort(parts.next().unwrap_or_default()).unwrap_or_default();
+        let page = parse_page_number(parts.next().unwrap_or_default());
+        if owner.parse::<i64>() != Ok(user_id) {
+            self.answer_callback_query(&query.id, "These buttons are for someone else.")
+                .await;
+            return;
+        }
+        self.answer_callback_query(&query.id, "").await;
+
+        let now = Utc::now().naive_utc();
+        let (event_ids, empty_reply) = if created {
+            (
+                db::get_created_event_ids(&self.pool, user_id, now, sort).await,
+                "You haven't created any upcoming events.",
+            )
+        } else {
+            (
+                db::get_attending_event_ids(&self.pool, user_id, now, sort).await,
+                "You haven't RSVP'd to any upcoming events.",
+            )
+        };
+        let event_ids = match event_ids {
+            Ok(event_ids) => event_ids,
+            Err(e) => {
+                self.send_message(user_id, &format!("Failed to load your events: {}", e))
+                    .await;
+                return;
+            }
+        };
+
+        if event_ids.is_empty() {
+            self.send_message(user_id, empty_reply).await;
+            return;
+        }
+        let command = format!("/myevents {}", event_sort_name(sort));
+        let (event_ids, footer) = paginate(event_ids, page, &command);
+        if event_ids.is_empty() {
+            self.send_message(user_id, &format!("There is no page {page}."))
+                .await;
+            return;
+        }
+
+        for event_id in event_ids {
+            let result = match self.fetch_event(event_id).await {
+                Ok(Some(event)) => self.list_event(user_id, &event, false).await.map(|_| ()),
+                Ok(None) => Ok(()),
+                Err(err) => Err(err),
+            };
+            if let Err(err) = result {
+                error!(user_id, event_id, %err, "failed to list event");
+            }
+        }
+        if let Some(footer) = footer {
+            self.send_message(user_id, &footer).await;
+        }
+    }
+
+    /// Offers the user's upcoming events whose name contains the query, so
+    /// that they can be shared in any chat through `@botname <query>`.
+    #[instrument(skip_all, fields(user_id = query.from.id))]
+    async fn handle_inline_query(&mut self, query: InlineQuery) -> Result<(), BotError> {
+        let event_ids = db::search_user_events(
+            &self.pool,
+            query.from.id as i64,
+            query.query.trim(),
+            Utc::now().naive_utc(),
+            MAX_INLINE_RESULTS,
+        )
+        .await?;
+
+        let mut results = Vec::new();
+        for event_id in event_ids {
+            let Some(event) = self.fetch_event(event_id).await? else {
+                continue;
+            };
+            let timezone = self.group_timezone(event.chat_id).await.unwrap_or(Tz::UTC);
+            let mut content = InputTextMessageContent::builder()
+                .message_text(event.format_message_with(timezone, None, self.parse_mode))
+                .build();
+            content.parse_mode = self.parse_mode.parse_mode();
+            let article = InlineQueryResultArticle::builder()
+                .id(event.id.to_string())
+                .title(event.name.clone())
+                .description(Event::format_time(&event.time, timezone))
+                .input_message_content(InputMessageContent::Text(content))
+                .build();
+            results.push(InlineQueryResult::Article(article));
+        }
+
+        // The results depend on who asks, and change with every RSVP.
+        let params = AnswerInlineQueryParams::builder()
+            .inline_query_id(query.id)
+            .results(results)
+            .is_personal(true)
+            .cache_time(0u32)
+            .build();
+        self.api.answer_inline_query(&params).await?;
+
+        Ok(())
+    }
+
+    /// Starts editing an event. Only its creator may edit it; the rest of
+    /// the conversation happens in a private chat with them.
+    async fn handle_edit(&mut self, chat_id: i64, user_id: u64, args: &str) {
+        let Ok(event_id) = args.parse::<i64>() else {
+            self.send_message(chat_id, "Usage: /edit <event_id>").await;
+            return;
+        };
+
+        self.start_edit(chat_id, user_id, event_id).await;
+    }
+
+    /// Handles an event's Edit button like `/edit`.
+    async fn handle_edit_start_callback(&mut self, query: &CallbackQuery, event_id: i64) {
+        self.answer_callback_query(&query.id, "").await;
+        let user_id = query.from.id;
+        self.start_edit(user_id as i64, user_id, event_id).await;
+    }
+
+    /// Asks the creator of an event which field they want to change, in
+    /// their private chat. `chat_id` is where they asked to.
+    async fn start_edit(&mut self, chat_id: i64, user_id: u64, event_id: i64) {
+        let event = match db::get_event(&self.pool, event_id).await {
+            Ok(Some(event)) => event,
+            Ok(None) => {
+                self.send_message(chat_id, &format!("There is no event {event_id}."))
+                    .await;
+                return;
+            }
+            Err(e) => {
+                self.send_message(chat_id, &format!("Failed to load event: {}", e))
+                    .await;
+                return;
+            }
+        };
+
+        if event.creator != user_id as i64 {
+            self.send_message(chat_id, "Only the event's creator can edit it.")
+                .await;
+            return;
+        }
+
+        self.event_edit_contexts
+            .insert(user_id, EventEditContext::new(event_id));
+
+        self.send_message(
+            user_id as i64,
+            &format!(
+                "Editing «{}». Which field do you want to change: title, description, location or time?",
+                event.name
+            ),
+        ).await;
+    }
+
+    async fn handle_event_edit(&mut self, user_id: u64, text: String) {
+        let Some(context) = self.event_edit_contexts.get_mut(&user_id) else {
+            return;
+        };
+        let chat_id = user_id as i64;
+
+        let field = match context.state {
+            EventEditState::SelectField => {
+                let Some(field) = EventField::parse(&text) else {
+                    self.send_message(
+                        chat_id,
+                        "Please choose title, description, location or time.",
+                    )
+                    .await;
+                    return;
+                };
+                context.state = EventEditState::Editing(field);
+
+                let prompt = match field {
+                    EventField::Time => format!("Please enter the new time, as {TIME_FORMATS_HINT}, optionally followed by a timezone such as America/Toronto."),
+                    field => format!("Please enter the new {}.", field.label()),
+                };
+                self.send_message(chat_id, &prompt).await;
+                return;
+            }
+            EventEditState::Editing(field) => field,
+        };
+        let event_id = context.event_id;
+
+        let value = match field {
+            EventField::Time => {
+                let event = match db::get_event(&self.pool, event_id).await {
+                    Ok(Some(event)) => event,
+                    Ok(None) => {
+                        self.event_edit_contexts.remove(&user_id);
+                        self.send_message(chat_id, "That event no longer exists.")
+                            .await;
+                        return;
+                    }
+                    Err(e) => {
+                        self.send_message(chat_id, &format!("Failed to load event: {}", e))
+                            .await;
+                        return;
+                    }
+                };
+                let timezone = self.group_timezone(event.chat_id).await;
+
+                let time = match parse_event_time(&text, timezone) {
+                    Ok(utc_time) => utc_time.format(DATETIME_FORMAT).to_string(),
+                    Err(reply) => {
+                        self.send_message(chat_id, &reply).await;
+                        return;
+                    }
+                };
+                // Times are stored as DATETIME_FORMAT strings, which sort chronologically.
+                if let Some(deadline) = event.rsvp_deadline.filter(|deadline| time < *deadline) {
+                    let deadline = Event::format_time(&deadline, timezone.unwrap_or(Tz::UTC));
+                    self.send_message(
+                        chat_id,
+                        &format!("RSVPs close on {deadline}, so the event can't start before then. Please enter a later time."),
+                    )
+                    .await;
+                    return;
+                }
+                time
+            }
+            _ => {
+                if let Some(reply) = length_error(field, &text) {
+                    self.send_message(chat_id, &reply).await;
+                    return;
+                }
+                sanitize_text(&text)
+            }
+        };
+
+        if field == EventField::Title && value.is_empty() {
+            self.send_message(
+                chat_id,
+                "The title can't be empty. Please enter the new title.",
+            )
+            .await;
+            return;
+        }
+
+        let result = if field == EventField::Time {
+            db::update_event_time(&self.pool, event_id, &value).await
+        } else {
+            db::update_event_field(&self.pool, event_id, field, &value)
+                .await
+                .map_err(BotError::from)
+        };
+        match result {
+            Ok(_) => {
+                self.event_edit_contexts.remove(&user_id);
+                self.send_message(chat_id, "The Event has been updated.")
+                    .await;
+
+                if let Err(err) = self.reload_event_message(event_id).await {
+                    error!(event_id, %err, "failed to update event message");
+                }
+            }
+            Err(BotError::DuplicateEvent) => {
+                self.send_message(
+                    chat_id,
+                    "An event with this title and time already exists in the group. Please enter a different time.",
+                )
+                .await;
+            }
+            Err(e) => {
+                self.send_message(chat_id, &format!("Failed to update event: {}", e))
+                    .await
+            }
+        }
+    }
+
+    /// Loads an event along with the names of everyone who RSVP'd.
+    async fn fetch_event(&mut self, event_id: i64) -> Result<Option<Event>, BotError> {
+        let Some(mut event) = db::get_event(&self.pool, event_id).await? else {
+            return Ok(None);
+        };
+        self.fetch_attendees(&mut event).await?;
+
+        Ok(Some(event))
+    }
+
+    /// Fills in the attendee lists of an event loaded without them.
+    async fn fetch_attendees(&mut self, event: &mut Event) -> Result<(), BotError> {
+        for (user_id, status) in db::get_attendees(&self.pool, event.id).await? {
+            let attendee = (user_id, self.get_user_name(event.chat_id, user_id).await);
+            match status.as_str() {
+                "accepted" => event.accepted.push(attendee),
+                "declined" => event.declined.push(attendee),
+                "maybe" => event.maybe.push(attendee),
+                "waitlist" => event.waitlist.push(attendee),
+                _ => {}
+            }
+        }
+        event.decline_reasons = db::get_decline_reasons(&self.pool, event.id)
+            .await?
+            .into_iter()
+            .collect();
+        event.checked_in = db::get_checked_in(&self.pool, event.id).await?;
+        event.guests = db::get_guests(&self.pool, event.id)
+            .await?
+            .into_iter()
+            .collect();
+
+        Ok(())
+    }
+
+    /// Sends each of `events` to `chat_id`, with RSVP buttons, logging failures.
+    async fn list_events(&mut self, chat_id: i64, events: Vec<Event>) {
+        for mut event in events {
+            let result = match self.fetch_attendees(&mut event).await {
+                Ok(_) => self.list_event(chat_id, &event, true).await.map(|_| ()),
+                Err(err) => Err(err),
+            };
+            if let Err(err) = result {
+                error!(chat_id, event_id = event.id, %err, "failed to list event");
+            }
+        }
+    }
+
+    /// Lists the chat's events in the next `days` days, 7 if not given, as
+    /// in `/upcoming [days] [page]`.
+    async fn handle_upcoming(&mut self, chat_id: i64, args: &str) {
+        let (days, page) = match args.split_once(char::is_whitespace) {
+            Some((days, page)) => (days, parse_page_number(page)),
+            None => (args, 1),
+        };
+        let days = if days.is_empty() {
+            DEFAULT_UPCOMING_DAYS
+        } else {
+            match days.parse::<u32>() {
+                Ok(days) if (1..=MAX_UPCOMING_DAYS).contains(&days) => days,
+                _ => {
+                    self.send_message(
+                        chat_id,
+                        &format!("Usage: /upcoming [days] [page], with at most {MAX_UPCOMING_DAYS} days."),
+                    )
+                    .await;
+                    return;
+                }
+            }
+        };
+
+        let now = Utc::now().naive_utc();
+        let events = match db::fetch_upcoming_events(&self.pool, chat_id, now, days).await {
+            Ok(events) => events,
+            Err(e) => {
+                self.send_message(chat_id, &format!("Failed to load events: {}", e))
+                    .await;
+                return;
+            }
+        };
+
+        if events.is_empty() {
+            let period = if days == 1 {
+                "day".to_string()
+            } else {
+                format!("{days} days")
+            };
+            self.send_message(
+                chat_id,
+                &format!("There are no events in the next {period}."),
+            )
+            .await;
+            return;
+        }
+        let (events, footer) = paginate(events, page, &format!("/upcoming {days}"));
+        if events.is_empty() {
+            self.send_message(chat_id, &format!("There is no page {page}."))
+                .await;
+            return;
+        }
+
+        self.list_events(chat_id, events).await;
+        if let Some(footer) = footer {
+            self.send_message(chat_id, &footer).await;
+        }
+    }
+
+    /// Lists the chat's upcoming events, only those tagged with a tag if
+    /// given, as in `/list [tag] [page]`.
+    async fn handle_list(&mut self, chat_id: i64, args: &str) {
+        let (tag, page) = split_page_number(args);
+        let tag = parse_tags(tag).into_iter().next();
+        let offset = (page - 1).saturating_mul(MAX_EVENTS_PER_PAGE);
+        self.list_page(chat_id, tag.as_deref(), offset as i64).await;
+    }
+
+    /// Handles the buttons under a `/list` page, which list another page of
+    /// the chat's events.
+    async fn handle_list_page_callback(&mut self, query: &CallbackQuery, data: &str) {
+        let mut parts = data.splitn(3, '_');
+        let (Some(Ok(offset)), Some(Ok(chat_id))) = (
+            parts.next().map(str::parse::<i64>),
+            parts.next().map(str::parse::<i64>),
+        ) else {
+            self.answer_callback_query(&query.id, "This button is no longer supported.")
+                .await;
+            return;
+        };
+        let tag = parts.next();
+
+        // Only list a chat's events in that chat.
+        let in_chat = matches!(
+            &query.message,
+            Some(MaybeInaccessibleMessage::Message(message)) if message.chat.id == chat_id
+        );
+        if !in_chat || offset < 0 {
+            self.answer_callback_query(&query.id, "This button is no longer supported.")
+                .await;
+            return;
+        }
+
+        self.answer_callback_query(&query.id, "").await;
+        self.list_page(chat_id, tag, offset).await;
+    }
+
+    /// Sends `MAX_EVENTS_PER_PAGE` of the chat's upcoming events, starting at
+    /// `offset`, followed by buttons for the pages before and after.
+    async fn list_page(&mut self, chat_id: i64, tag: Option<&str>, offset: i64) {
+        let now = Utc::now().naive_utc();
+        let page_size = MAX_EVENTS_PER_PAGE as i64;
+        let result = match db::count_listed_events(&self.pool, chat_id, now, tag).await {
+            Ok(total) => db::fetch_events_paged(&self.pool, chat_id, now, tag, offset, page_size)
+                .await
+                .map(|events| (total, events)),
+            Err(e) => Err(e),
+        };
+        let (total, events) = match result {
+            Ok(page) => page,
+            Err(e) => {
+                self.send_message(chat_id, &format!("Failed to load events: {}", e))
+                    .await;
+                return;
+            }
+        };
+
+        if events.is_empty() {
+            let reply = match tag {
+                _ if offset > 0 => "There are no more upcoming events.".to_string(),
+                Some(tag) => format!("There are no upcoming events tagged '{tag}'."),
+                None => "There are no upcoming events.".to_string(),
+            };
+            self.send_message(chat_id, &reply).await;
+            return;
+        }
+
+        let shown = events.len() as i64;
+        let has_next = offset + shown < total;
+        self.list_events(chat_id, events).await;
+        if offset == 0 && !has_next {
+            return;
+        }
+
+        let callback = |offset: i64| {
+            let mut data = format!("{LIST_PAGE_CALLBACK_PREFIX}{offset}_{chat_id}");
+            if let Some(tag) = tag {
+                data.push_str(&format!("_{tag}"));
+            }
+            data
+        };
+        let mut buttons = Vec::new();
+        if offset > 0 {
+            buttons.push(
+                InlineKeyboardButton::builder()
+                    .text("◀ Show previous")
+                    .callback_data(callback((offset - page_size).max(0)))
+                    .build(),
+            );
+        }
+        if has_next {
+            buttons.push(
+                InlineKeyboardButton::builder()
+                    .text("Show more ▶")
+                    .callback_data(callback(offset + page_size))
+                    .build(),
+            );
+        }
+
+        let next = has_next.then(|| {
+            let page = offset / page_size + 2;
+            match tag {
+                Some(tag) => format!("/list {tag} {page}"),
+                None => format!("/list {page}"),
+            }
+        });
+        let mut params = SendMessageParams::builder()
+            .chat_id(chat_id)
+            .text(page_footer(
+                offset as usize,
+                shown as usize,
+                total as usize,
+                next.as_deref(),
+            ))
+            .build();
+        // Telegram rejects callback data over 64 bytes, as long tags could make
+        // it, in which case the command in the footer has to do.
+        if buttons.iter().all(|button| {
+            button
+                .callback_data
+                .as_ref()
+                .is_some_and(|data| data.len() <= MAX_CALLBACK_DATA_LEN)
+        }) {
+            params.reply_markup = Some(ReplyMarkup::InlineKeyboardMarkup(
+                InlineKeyboardMarkup::builder()
+                    .inline_keyboard(vec![buttons])
+                    .build(),
+            ));
+        }
+        if let Err(err) =
+            api_call_with_retry(|| self.api.send_message(&params), MAX_API_RETRIES).await
+        {
+            error!(chat_id, %err, "failed to send list buttons");
+        }
+    }
+
+    /// Lists the tags of the chat's upcoming events.
+    async fn handle_tags(&mut self, chat_id: i64) {
+        let reply = match db::get_chat_tags(&self.pool, chat_id, Utc::now().naive_utc()).await {
+            Ok(tags) if tags.is_empty() => "No upcoming events have tags.".to_string(),
+            Ok(tags) => format!("Tags in use: {}", tags.join(", ")),
+            Err(e) => format!("Failed to load tags: {}", e),
+        };
+
+        self.send_message(chat_id, &reply).await;
+    }
+
+    /// Lists the chat's upcoming events whose title contains `keyword`, with
+    /// their RSVP buttons.
+    async fn handle_search(&mut self, chat_id: i64, keyword: &str) {
+        if keyword.is_empty() {
+            self.send_message(chat_id, "Usage: /search <keyword>").await;
+            return;
+        }
+        if keyword.chars().count() > MAX_SEARCH_KEYWORD_LEN {
+            self.send_message(
+                chat_id,
+                &format!("Keywords can be at most {MAX_SEARCH_KEYWORD_LEN} characters."),
+            )
+            .await;
+            return;
+        }
+
+        let now = Utc::now().naive_utc();
+        let event_ids =
+            match db::search_events(&self.pool, chat_id, keyword, now, MAX_SEARCH_RESULTS).await {
+                Ok(event_ids) => event_ids,
+                Err(e) => {
+                    self.send_message(chat_id, &format!("Failed to search events: {}", e))
+                        .await;
+                    return;
+                }
+            };
+
+        let mut events = Vec::with_capacity(event_ids.len());
+        for event_id in event_ids {
+            match db::get_event(&self.pool, event_id).await {
+                Ok(Some(event)) => events.push(event),
+                Ok(None) => {}
+                Err(err) => error!(chat_id, event_id, %err, "failed to load event"),
+            }
+        }
+
+        if events.is_empty() {
+            self.send_message(chat_id, &format!("No events found matching '{keyword}'."))
+                .await;
+            return;
+        }
+
+        self.list_events(chat_id, events).await;
+    }
+
+    /// Returns a member's full name and username, or a placeholder if they
+    /// can't be looked up. Names are cached for `CACHE_TTL_SECS`.
+    async fn get_user_name(&mut self, chat_id: i64, user_id: i64) -> UserDisplay {
+        let ttl = Duration::from_secs(CACHE_TTL_SECS);
+        self.user_name_cache
+            .retain(|_, (_, fetched_at)| fetched_at.elapsed() < ttl);
+
+        if let Some((name, _)) = self.user_name_cache.get(&(chat_id, user_id)) {
+            return name.clone();
+        }
+
+        let params = GetChatMemberParams::builder()
+            .chat_id(chat_id)
+            .user_id(user_id as u64)
+            .build();
+
+        match self.api.get_chat_member(&params).await {
+            Ok(response) => {
+                let user = member_user(&response.result);
+                let name = UserDisplay {
+                    name: match &user.last_name {
+                        Some(last_name) => format!("{} {last_name}", user.first_name),
+                        None => user.first_name.clone(),
+                    },
+                    username: user.username.clone(),
+                };
+                self.user_name_cache
+                    .insert((chat_id, user_id), (name.clone(), Instant::now()));
+                name
+            }
+            Err(err) => {
+                warn!(chat_id, user_id, %err, "failed to look up user name");
+                UserDisplay::from("Unknown user")
+            }
+        }
+    }
+
+    /// Shows or, for group admins, changes the timezone event times are
+    /// entered and shown in for this group.
+    async fn handle_timezone(
+        &mut self,
+        chat_type: &ChatType,
+        chat_id: i64,
+        user_id: u64,
+        args: &str,
+    ) {
+        if *chat_type == ChatType::Private {
+            self.send_message(
+                chat_id,
+                "Timezones are set per group. Use /timezone in the group chat.",
+            )
+            .await;
+            return;
+        }
+
+        if args.is_empty() {
+            let timezone = self.group_timezone(chat_id).await.unwrap_or(Tz::UTC);
+            self.send_message(
+                chat_id,
+                &format!("This group's timezone is {timezone}. Admins can change it with /timezone <name>, e.g. /timezone America/Toronto."),
+            ).await;
+            return;
+        }
+
+        match self.is_group_admin(chat_id, user_id).await {
+            Ok(true) => {}
+            Ok(false) => {
+                self.send_message(
+                    chat_id,
+                    "Only group administrators can change the timezone.",
+                )
+                .await;
+                return;
+            }
+            Err(err) => {
+                error!(chat_id, user_id, %err, "failed to check timezone permissions");
+                self.send_message(chat_id, "Could not check your permissions in this chat.")
+                    .await;
+                return;
+            }
+        }
+
+        let Ok(timezone) = args.parse::<Tz>() else {
+            self.send_message(
+                chat_id,
+                &format!(
+                    "Unknown timezone '{args}'. Use a name like America/Toronto or Europe/Berlin."
+                ),
+            )
+            .await;
+            return;
+        };
+
+        match db::set_group_timezone(&self.pool, chat_id, timezone.name()).await {
+            Ok(_) => {
+                self.send_message(
+                    chat_id,
+                    &format!("This group's timezone is now {timezone}."),
+                )
+                .await
+            }
+            Err(e) => {
+                self.send_message(chat_id, &format!("Failed to save timezone: {}", e))
+                    .await
+            }
+        }
+    }
+
+    /// Deletes a group's events that took place more than the given number
+    /// of days ago, 90 by default, once the admin confirms it. With `dry`
+    /// they are only counted.
+    async fn handle_prune_old(
+        &mut self,
+        chat_type: &ChatType,
+        chat_id: i64,
+        user_id: u64,
+        args: &str,
+    ) {
+        if *chat_type == ChatType::Private {
+            self.send_message(chat_id, "Use /pruneold in a group chat.")
+                .await;
+            return;
+        }
+
+        match self.is_group_admin(chat_id, user_id).await {
+            Ok(true) => {}
+            Ok(false) => {
+                self.send_message(chat_id, "Only group administrators can prune old events.")
+                    .await;
+                return;
+            }
+            Err(err) => {
+                error!(chat_id, user_id, %err, "failed to check prune permissions");
+                self.send_message(chat_id, "Could not check your permissions in this chat.")
+                    .await;
+                return;
+            }
+        }
+
+        let args: Vec<_> = args.split_whitespace().collect();
+        let (dry_run, days) = match args[..] {
+            ["dry", ref days @ ..] => (true, days),
+            ref days => (false, days),
+        };
+        let days = match days {
+            [] => Some(DEFAULT_PRUNE_DAYS),
+            [days] => days.parse::<u32>().ok().filter(|&days| days > 0),
+            _ => None,
+        };
+        let Some(days) = days else {
+            self.send_message(chat_id, "Usage: /pruneold [dry] [days]")
+                .await;
+            return;
+        };
+
+        let cutoff = Utc::now().naive_utc() - chrono::Duration::days(days.into());
+        let count = match db::get_event_ids_before(&self.pool, cutoff, Some(chat_id)).await {
+            Ok(event_ids) => event_ids.len(),
+            Err(e) => {
+                self.send_message(chat_id, &format!("Failed to count events: {}", e))
+                    .await;
+                return;
+            }
+        };
+
+        if count == 0 {
+            self.send_message(
+                chat_id,
+                &format!("There are no events older than {days} days."),
+            )
+            .await;
+        } else if dry_run {
+            self.send_message(
+                chat_id,
+                &format!("{count} events are older than {days} days and would be deleted."),
+            )
+            .await;
+        } else {
+            self.pending_prunes.insert((chat_id, user_id), days);
+            self.send_message(
+                chat_id,
+                &format!("⚠️ This will delete {count} events. Reply 'yes' to confirm."),
+            )
+            .await;
+        }
+    }
+
+    /// Carries out the `/pruneold` an admin was asked to confirm, if they
+    /// answered `yes`, and forgets it either way.
+    async fn handle_prune_confirmation(&mut self, chat_id: i64, user_id: u64, text: &str) {
+        let Some(days) = self.pending_prunes.remove(&(chat_id, user_id)) else {
+            return;
+        };
+        if !text.trim().eq_ignore_ascii_case("yes") {
+            self.send_message(chat_id, "Nothing was pruned.").await;
+            return;
+        }
+
+        let cutoff = Utc::now().naive_utc() - chrono::Duration::days(days.into());
+        match db::delete_events_before(&self.pool, cutoff, Some(chat_id)).await {
+            Ok(deleted) => {
+                info!(chat_id, user_id, deleted, days, "pruned old events");
+                self.send_message(chat_id, &format!("Pruned {deleted} events."))
+                    .await;
+            }
+            Err(e) => {
+                self.send_message(chat_id, &format!("Failed to prune events: {}", e))
+                    .await
+            }
+        }
+    }
+
+    /// Shows a group's admins how its events have been received.
+    async fn handle_stats(&mut self, chat_type: &ChatType, chat_id: i64, user_id: u64) {
+        if *chat_type == ChatType::Private {
+            self.send_message(chat_id, "Use /stats in a group chat.")
+                .await;
+            return;
+        }
+
+        match self.is_group_admin(chat_id, user_id).await {
+            Ok(true) => {}
+            Ok(false) => {
+                self.send_message(chat_id, "Only group administrators can see the stats.")
+                    .await;
+                return;
+            }
+            Err(err) => {
+                error!(chat_id, user_id, %err, "failed to check stats permissions");
+                self.send_message(chat_id, "Could not check your permissions in this chat.")
+                    .await;
+                return;
+            }
+        }
+
+        let stats =
+            match db::stats::get_group_stats(&self.pool, chat_id, Utc::now().naive_utc()).await {
+                Ok(stats) => stats,
+                Err(e) => {
+                    self.send_message(chat_id, &format!("Failed to load stats: {}", e))
+                        .await;
+                    return;
+                }
+            };
+
+        let mut text = format!(
+            "{}\n{}",
+            self.parse_mode.bold("Event stats"),
+            self.parse_mode.escape(&format!(
+                "Events: {}\nThis month: {}\nAverage accepted per event: {:.1}\nUnique attendees: {}\n",
+                stats.total_events,
+                stats.events_this_month,
+                stats.average_accepted,
+                stats.unique_attendees,
+            ))
+        );
+        if let Some((name, accepted)) = stats.top_event {
+            text.push_str(
+                &self
+                    .parse_mode
+                    .escape(&format!("Highest turnout: {name} ({accepted} accepted)\n")),
+            );
+        }
+
+        let mut params = SendMessageParams::builder()
+            .chat_id(chat_id)
+            .text(text)
+            .build();
+        params.parse_mode = self.parse_mode.parse_mode();
+        if let Err(err) =
+            api_call_with_retry(|| self.api.send_message(&params), MAX_API_RETRIES).await
+        {
+            error!(chat_id, %err, "failed to send stats");
+        }
+    }
+
+    /// Starts walking a group admin through the group's settings, in a
+    /// private chat.
+    async fn handle_settings(&mut self, chat_type: &ChatType, chat_id: i64, user_id: u64) {
+        if *chat_type == ChatType::Private {
+            self.send_message(
+                chat_id,
+                "Settings are per group. Use /settings in the group chat.",
+            )
+            .await;
+            return;
+        }
+
+        match self.is_group_admin(chat_id, user_id).await {
+            Ok(true) => {}
+            Ok(false) => {
+                self.send_message(
+                    chat_id,
+                    "Only group administrators can change the settings.",
+                )
+                .await;
+                return;
+            }
+            Err(err) => {
+                error!(chat_id, user_id, %err, "failed to check settings permissions");
+                self.send_message(chat_id, "Could not check your permissions in this chat.")
+                    .await;
+                return;
+            }
+        }
+
+        let settings = match group_settings::get_settings(&self.pool, chat_id).await {
+            Ok(settings) => settings,
+            Err(e) => {
+                self.send_message(chat_id, &format!("Failed to load settings: {}", e))
+                    .await;
+                return;
+            }
+        };
+
+        let params = SendMessageParams::builder()
+            .chat_id(user_id as i64)
+            .text(format!(
+                "Changing this group's settings. Reply 'skip' to keep a value.\n\nHow many upcoming events can the group have at once? Currently {}.",
+                settings.max_events
+            ))
+            .build();
+        if let Err(err) =
+            api_call_with_retry(|| self.api.send_message(&params), MAX_API_RETRIES).await
+        {
+            warn!(chat_id, user_id, %err, "failed to send the settings");
+            self.send_message(
+                chat_id,
+                "Could not send you the settings. Start a private chat with me and try again.",
+            )
+            .await;
+            return;
+        }
+        self.record_api_success();
+        self.settings_contexts
+            .insert(user_id, SettingsContext::new(settings));
+
+        self.send_message(chat_id, "I've sent you the settings in a private chat.")
+            .await;
+    }
+
+    /// Adds a user to the group's trusted creators with `/addcreator`, or
+    /// removes them with `/removecreator`.
+    async fn handle_trusted_creator(
+        &mut self,
+        chat_type: &ChatType,
+        chat_id: i64,
+        user_id: u64,
+        args: &str,
+        add: bool,
+    ) {
+        let usage = if add {
+            "Usage: /addcreator <user_id>"
+        } else {
+            "Usage: /removecreator <user_id>"
+        };
+        if *chat_type == ChatType::Private {
+            self.send_message(
+                chat_id,
+                "Trusted organizers are per group. Use this command in the group chat.",
+            )
+            .await;
+            return;
+        }
+        let Ok(creator) = args.parse::<i64>() else {
+            self.send_message(chat_id, usage).await;
+            return;
+        };
+
+        match self.is_group_admin(chat_id, user_id).await {
+            Ok(true) => {}
+            Ok(false) => {
+                self.send_message(
+                    chat_id,
+                    "Only group administrators can change who creates events.",
+                )
+                .await;
+                return;
+            }
+            Err(err) => {
+                error!(chat_id, user_id, %err, "failed to check trusted creator permissions");
+                self.send_message(chat_id, "Could not check your permissions in this chat.")
+                    .await;
+                return;
+            }
+        }
+
+        let result = if add {
+            group_settings::add_trusted_creator(&self.pool, chat_id, creator).await
+        } else {
+            group_settings::remove_trusted_creator(&self.pool, chat_id, creator).await
+        };
+        let reply = match (result, add) {
+            (Ok(true), true) => format!("User {creator} can now create events."),
+            (Ok(false), true) => format!("User {creator} can already create events."),
+            (Ok(true), false) => format!("User {creator} can no longer create events."),
+            (Ok(false), false) => format!("User {creator} isn't a trusted organizer."),
+            (Err(e), _) => format!("Failed to update trusted organizers: {}", e),
+        };
+        self.send_message(chat_id, &reply).await;
+    }
+
+    async fn handle_settings_input(&mut self, user_id: u64, text: String) {
+        let Some(context) = self.settings_contexts.get_mut(&user_id) else {
+            return;
+        };
+        let chat_id = user_id as i64;
+        let text = text.trim();
+        let skip = text.eq_ignore_ascii_case("skip");
+
+        match context.state {
+            SettingsState::AwaitingMaxEvents => {
+                if !skip {
+                    match text.parse::<i64>() {
+                        Ok(max_events) if max_events > 0 => {
+                            context.settings.max_events = max_events;
+                        }
+                        _ => {
+                            self.send_message(
+                                chat_id,
+                                "Please enter a positive number, or 'skip'.",
+                            )
+                            .await;
+                            return;
+                        }
+                    }
+                }
+                context.state = SettingsState::AwaitingRequireAdmin;
+
+                let current = if context.settings.require_admin {
+                    "yes"
+                } else {
+                    "no"
+                };
+                self.send_message(
+                    chat_id,
+                    &format!("Should only administrators be able to create events (yes/no)? Currently {current}."),
+                ).await;
+            }
+            SettingsState::AwaitingRequireAdmin => {
+                if !skip {
+                    let Some(require_admin) = parse_yes_no(text) else {
+                        self.send_message(chat_id, "Please answer yes, no or 'skip'.")
+                            .await;
+                        return;
+                    };
+                    context.settings.require_admin = require_admin;
+                }
+                context.state = SettingsState::AwaitingTrustedCreatorsOnly;
+
+                let current = if context.settings.trusted_creators_only {
+                    "yes"
+                } else {
+                    "no"
+                };
+                self.send_message(
+                    chat_id,
+                    &format!("Should only administrators and the organizers added with /addcreator be able to create events (yes/no)? Currently {current}."),
+                ).await;
+            }
+            SettingsState::AwaitingTrustedCreatorsOnly => {
+                if !skip {
+                    let Some(trusted_creators_only) = parse_yes_no(text) else {
+                        self.send_message(chat_id, "Please answer yes, no or 'skip'.")
+                            .await;
+                        return;
+                    };
+                    context.settings.trusted_creators_only = trusted_creators_only;
+                }
+                context.state = SettingsState::AwaitingNotifyCreator;
+
+                let current = if context.settings.notify_creator {
+                    "yes"
+                } else {
+                    "no"
+                };
+                self.send_message(
+                    chat_id,
+                    &format!("Should event creators get a message when someone RSVPs (yes/no)? Currently {current}."),
+                ).await;
+            }
+            SettingsState::AwaitingNotifyCreator => {
+                if !skip {
+                    let Some(notify_creator) = parse_yes_no(text) else {
+                        self.send_message(chat_id, "Please answer yes, no or 'skip'.")
+                            .await;
+                        return;
+                    };
+                    context.settings.notify_creator = notify_creator;
+                }
+                context.state = SettingsState::AwaitingPinned;
+
+                let current = if context.settings.pinned { "yes" } else { "no" };
+                self.send_message(
+                    chat_id,
+                    &format!("Should new events be pinned in the group (yes/no)? I need permission to pin messages for this. Currently {current}."),
+                ).await;
+            }
+            SettingsState::AwaitingPinned => {
+                if !skip {
+                    let Some(pinned) = parse_yes_no(text) else {
+                        self.send_message(chat_id, "Please answer yes, no or 'skip'.")
+                            .await;
+                        return;
+                    };
+                    context.settings.pinned = pinned;
+                }
+                context.state = SettingsState::AwaitingTimezone;
+
+                let prompt = format!(
+                    "Which timezone are event times in, e.g. America/Toronto? Currently {}.",
+                    context.settings.timezone
+                );
+                self.send_message(chat_id, &prompt).await;
+            }
+            SettingsState::AwaitingTimezone => {
+                if !skip {
+                    let Ok(timezone) = text.parse::<Tz>() else {
+                        self.send_message(
+                            chat_id,
+                            &format!("Unknown timezone '{text}'. Use a name like America/Toronto or Europe/Berlin, or 'skip'."),
+                        ).await;
+                        return;
+                    };
+                    context.settings.timezone = timezone.name().to_string();
+                }
+
+                match group_settings::upsert_settings(&self.pool, &context.settings).await {
+                    Ok(_) => {
+                        self.settings_contexts.remove(&user_id);
+                        self.send_message(chat_id, "The settings have been saved.")
+                            .await;
+                    }
+                    Err(e) => {
+                        self.send_message(chat_id, &format!("Failed to save settings: {}", e))
+                            .await
+                    }
+                }
+            }
+        }
+    }
+
+    /// Looks up the timezone configured for a group, if any.
+    async fn group_timezone(&self, chat_id: i64) -> Option<Tz> {
+        match db::get_group_timezone(&self.pool, chat_id).await {
+            Ok(timezone) => timezone.and_then(|timezone| timezone.parse().ok()),
+            Err(err) => {
+                error!(chat_id, %err, "failed to load group timezone");
+                None
+            }
+        }
+    }
+
+    /// Checks `user_id` against the group's `require_admin` and
+    /// `trusted_creators_only` settings, returning the reply explaining why
+    /// they can't create events if they can't. Administrators always can.
+    async fn creation_denied_by_settings(
+        &mut self,
+        settings: &GroupSettings,
+        user_id: u64,
+    ) -> Result<Option<&'static str>, BotError> {
+        if !settings.require_admin && !settings.trusted_creators_only {
+            return Ok(None);
+        }
+        if self.is_group_admin(settings.chat_id, user_id).await? {
+            return Ok(None);
+        }
+        if settings.require_admin {
+            return Ok(Some("Only group administrators can create events."));
+        }
+        if group_settings::is_trusted_creator(&self.pool, settings.chat_id, user_id as i64).await? {
+            return Ok(None);
+        }
+
+        Ok(Some(
+            "Only group administrators and trusted organizers can create events.",
+        ))
+    }
+
+    /// Checks `user_id` against the roles in `creation_roles`.
+    async fn can_create_events(&mut self, chat_id: i64, user_id: u64) -> Result<bool, BotError> {
+        if !self
+            .get_chat_admin_ids(chat_id)
+            .await?
+            .contains(&(user_id as i64))
+        {
+            return Ok(false);
+        }
+
+        if self.creation_roles.contains(&CreationRole::Admin) {
+            return Ok(true);
+        }
+
+        let params = GetChatMemberParams::builder()
+            .chat_id(chat_id)
+            .user_id(user_id)
+            .build();
+
+        Ok(match self.api.get_chat_member(&params).await?.result {
+            ChatMember::Creator(_) => true,
+            ChatMember::Administrator(admin) => admin.can_restrict_members,
+            _ => false,
+        })
+    }
+
+    /// Follows a group that was upgraded to a supergroup and got a new chat
+    /// ID, moving its events, settings and drafts over to it.
+    async fn handle_chat_migration(&mut self, old_chat_id: i64, new_chat_id: i64) {
+        self.admin_cache.remove(&old_chat_id);
+        for context in self.event_contexts.values_mut() {
+            if context.clone_chat_id == Some(old_chat_id) {
+                context.clone_chat_id = Some(new_chat_id);
+            }
+        }
+
+        match db::migrate_chat(&self.pool, old_chat_id, new_chat_id).await {
+            Ok(true) => info!(old_chat_id, new_chat_id, "chat migrated to a supergroup"),
+            Ok(false) => debug!(old_chat_id, new_chat_id, "nothing left to migrate"),
+            Err(err) => error!(old_chat_id, new_chat_id, %err, "failed to migrate chat"),
+        }
+    }
+
+    /// Drops the cached admin list of a chat whenever someone is promoted to
+    /// or demoted from an administrator role there.
+    fn handle_chat_member(&mut self, update: ChatMemberUpdated) {
+        if is_admin(&update.old_chat_member) || is_admin(&update.new_chat_member) {
+            self.admin_cache.remove(&update.chat.id);
+        }
+    }
+
+    /// Cancels a group's upcoming events when the bot is removed from it, as
+    /// it could no longer post or remind about them, and starts the group over
+    /// with default settings when the bot is added back. The group's title is
+    /// kept for `/listall`.
+    async fn handle_my_chat_member(&mut self, update: ChatMemberUpdated) {
+        let chat_id = update.chat.id;
+        if update.chat.type_field == ChatType::Private {
+            return;
+        }
+        if let (true, Some(title)) = (in_chat(&update.new_chat_member), &update.chat.title) {
+            if let Err(err) = db::stats::upsert_chat_name(&self.pool, chat_id, title).await {
+                error!(chat_id, %err, "failed to store chat title");
+            }
+        }
+
+        match (
+            in_chat(&update.old_chat_member),
+            in_chat(&update.new_chat_member),
+        ) {
+            (true, false) => {
+                self.admin_cache.remove(&chat_id);
+                let events = match db::cancel_chat_events(
+                    &self.pool,
+                    chat_id,
+                    Utc::now().naive_utc(),
+                    "Bot removed from group",
+                )
+                .await
+                {
+                    Ok(events) => events,
+                    Err(err) => {
+                        error!(chat_id, %err, "failed to cancel the events of a group the bot left");
+                        return;
+                    }
+                };
+                info!(chat_id, cancelled = events.len(), "removed from group");
+
+                let group = update.chat.title.as_deref().unwrap_or("a group");
+                for event in &events {
+                    self.send_message(
+                        event.creator,
+                        &format!(
+                            "🚫 I was removed from {group}, so «{}» has been cancelled.",
+                            event.name
+                        ),
+                    )
+                    .await;
+                }
+
+                if let Err(err) = group_settings::delete_settings(&self.pool, chat_id).await {
+                    error!(chat_id, %err, "failed to delete group settings");
+                }
+            }
+            (false, true) => {
+                info!(chat_id, "added to group");
+                if let Err(err) =
+                    group_settings::upsert_settings(&self.pool, &GroupSettings::new(chat_id)).await
+                {
+                    error!(chat_id, %err, "failed to create group settings");
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Whether `user_id` is an owner or administrator of `chat_id`.
+    async fn is_group_admin(&mut self, chat_id: i64, user_id: u64) -> Result<bool, BotError> {
+        Ok(self
+            .get_chat_admin_ids(chat_id)
+            .await?
+            .contains(&(user_id as i64)))
+    }
+
+    /// Returns the user IDs of the creator and administrators of `chat_id`.
+    ///
+    /// The list is cached per chat for `ADMIN_CACHE_TTL` so repeated checks
+    /// don't each cost a `getChatAdministrators` call.
+    pub async fn get_chat_admin_ids(&mut self, chat_id: i64) -> Result<Vec<i64>, BotError> {
+        if let Some((admin_ids, fetched_at)) = self.admin_cache.get(&chat_id) {
+            if fetched_at.elapsed() < ADMIN_CACHE_TTL {
+                return Ok(admin_ids.clone());
+            }
+        }
+
+        let params = GetChatAdministratorsParams::builder()
+            .chat_id(chat_id)
+            .build();
+        let response = self.api.get_chat_administrators(&params).await?;
+
+        let admin_ids: Vec<i64> = response
+            .result
+            .iter()
+            .filter_map(|member| match member {
+                ChatMember::Creator(owner) => Some(owner.user.id as i64),
+                ChatMember::Administrator(admin) => Some(admin.user.id as i64),
+                _ => None,
+            })
+            .collect();
+
+        self.admin_cache
+            .insert(chat_id, (admin_ids.clone(), Instant::now()));
+
+        Ok(admin_ids)
+    }
+
+    /// Returns the IDs of events starting after `now` (in UTC) and within
+    /// `REMINDER_WINDOW` of it whose reminders haven't been sent, soonest first.
+    pub async fn get_events_needing_reminder(
+        &self,
+        now: NaiveDateTime,
+    ) -> Result<Vec<i64>, BotError> {
+        Ok(db::get_events_needing_reminder(&self.pool, now, now + REMINDER_WINDOW).await?)
+    }
+
+    /// Uploads `bytes` to `chat_id` as a document called `filename`.
+    ///
+    /// The bytes are written to a temporary directory that is removed once
+    /// the upload finishes, whether or not it succeeded. There is no way to
+    /// pass a content type: frankenstein's `InputFile` is only a path, and
+    /// Telegram works the type out from the extension of `filename`.
+    pub async fn send_document_bytes(
+        &self,
+        chat_id: i64,
+        bytes: Vec<u8>,
+        filename: &str,
+    ) -> Result<(), BotError> {
+        // Not a `NamedTempFile`, whose random name would be what the
+        // recipient sees, as frankenstein uploads files under their own name.
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join(filename);
+        std::fs::write(&path, bytes)?;
+
+        let params = SendDocumentParams::builder()
+            .chat_id(chat_id)
+            .document(InputFile { path })
+            .build();
+        self.api.send_document(&params).await?;
+
+        Ok(())
+    }
+
+    /// Stops the loading spinner on a pressed button and shows `text` to the
+    /// user who pressed it.
+    async fn answer_callback_query(&self, callback_query_id: &str, text: &str) {
+        let params = AnswerCallbackQueryParams::builder()
+            .callback_query_id(callback_query_id)
+            .text(text)
+            .build();
+
+        if let Err(err) =
+            api_call_with_retry(|| self.api.answer_callback_query(&params), MAX_API_RETRIES).await
+        {
+            error!(%err, "failed to answer callback query");
+        }
+    }
+
+    async fn send_message(&self, chat_id: i64, text: &str) {
+        let send_message_params = SendMessageParams::builder()
+            .chat_id(chat_id)
+            .text(text)
+            .build();
+
+        match api_call_with_retry(
+            || self.api.send_message(&send_message_params),
+            MAX_API_RETRIES,
+        )
+        .await
+        {
+            Ok(_) => self.record_api_success(),
+            Err(err) => error!(chat_id, %err, "failed to send message"),
+        }
+    }
+
+    fn record_api_success(&self) {
+        *self
+            .last_api_success
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Instant::now();
+    }
+}
+
+/// The page (from 1) of `items` a list command shows and, if they don't all
+/// fit on one page, the footer to send after it. `command` followed by a
+/// page number lists that page.
+fn paginate<T>(mut items: Vec<T>, page: usize, command: &str) -> (Vec<T>, Option<String>) {
+    let total = items.len();
+    let offset = (page - 1).saturating_mul(MAX_EVENTS_PER_PAGE).min(total);
+    let items: Vec<T> = items.drain(offset..).take(MAX_EVENTS_PER_PAGE).collect();
+
+    let footer = (total > MAX_EVENTS_PER_PAGE && !items.is_empty()).then(|| {
+        let next = (offset + items.len() < total).then(|| format!("{command} {}", page + 1));
+        page_footer(offset, items.len(), total, next.as_deref())
+    });
+
+    (items, footer)
+}
+
+/// The note after a page of a list command showing `shown` of `total`
+/// events from `offset` on, pointing to the `next` page if there is one.
+fn page_footer(offset: usize, shown: usize, total: usize, next: Option<&str>) -> String {
+    let mut footer = format!(
+        "Showing {}–{} of {total} events.",
+        offset + 1,
+        offset + shown
+    );
+    if let Some(next) = next {
+        footer.push_str(&format!(" Use {next} to see more."));
+    }
+
+    footer
+}
+
+/// Renders `duration` as e.g. `2d 3h 4m` for `/status`, or in seconds if it's
+/// under a minute.
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs < 60 {
+        return format!("{secs}s");
+    }
+    let (days, hours, mins) = (secs / 86_400, secs % 86_400 / 3600, secs % 3600 / 60);
+    match (days, hours) {
+        (0, 0) => format!("{mins}m"),
+        (0, _) => format!("{hours}h {mins}m"),
+        _ => format!("{days}d {hours}h {mins}m"),
+    }
+}
+
+/// Returns the reply asking for a shorter value if `text` is too long for `field`.
+fn length_error(field: EventField, text: &str) -> Option<String> {
+    let max_len = field.max_len()?;
+    let excess = excess_length(text, max_len)?;
+
+    Some(format!(
+        "The {} can be at most {max_len} characters long; yours is {excess} over. Please enter a shorter one.",
+        field.label()
+    ))
+}
+
+/// The word `/myevents` takes for `sort`.
+fn event_sort_name(sort: EventSort) -> &'static str {
+    match sort {
+        EventSort::ByDate => "date",
+        EventSort::ByCreation => "created",
+    }
+}
+
+/// Parses the sort order `/myevents` takes: `date`, the default, or `created`.
+fn parse_event_sort(text: &str) -> Option<EventSort> {
+    match text.to_lowercase().as_str() {
+        "" | "date" => Some(EventSort::ByDate),
+        "created" => Some(EventSort::ByCreation),
+        _ => None,
+    }
+}
+
+fn parse_yes_no(text: &str) -> Option<bool> {
+    match text.to_lowercase().as_str() {
+        "yes" => Some(true),
+        "no" => Some(false),
+        _ => None,
+    }
+}
+
+/// The kinds of update the bot asks Telegram for.
+fn allowed_updates() -> Vec<AllowedUpdate> {
+    vec![
+        AllowedUpdate::Message,
+        AllowedUpdate::CallbackQuery,
+        AllowedUpdate::ChatMember,
+        AllowedUpdate::MyChatMember,
+        AllowedUpdate::InlineQuery,
+    ]
+}
+
+/// Resolves once the process is asked to stop, with SIGTERM or Ctrl-C, or
+/// `stop` is notified.
+async fn shutdown_signal(stop: &Notify) {
+    tokio::select! {
+        _ = os_shutdown_signal() => {}
+        _ = stop.notified() => {}
+    }
+}
+
+async fn os_shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = terminate.recv() => {}
+                    _ = tokio::signal::ctrl_c() => {}
+                }
+            }
+            Err(err) => {
+                warn!(%err, "failed to listen for SIGTERM");
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
+
+/// Parses an event time entered in `timezone` (UTC if `None`, unless the
+/// text names its own) and converts it to UTC. On failure, returns the reply
+/// to send to the user.
+fn parse_event_time(text: &str, timezone: Option<Tz>) -> Result<NaiveDateTime, String> {
+    let (time, timezone) = match parse_datetime_string(text) {
+        Ok((time, own_timezone)) => (time, own_timezone.or(timezone).unwrap_or(Tz::UTC)),
+        Err(err) => {
+            debug!(text, %err, "rejected event time");
+            return Err(format!("Please enter the Time as {TIME_FORMATS_HINT}."));
+        }
+    };
+
+    local_to_utc(time, timezone).ok_or_else(|| {
+        format!("That time doesn't exist in {timezone} because of a daylight saving change. Please enter another Time.")
+    })
+}
+
+fn member_user(member: &ChatMember) -> &frankenstein::User {
+    match member {
+        ChatMember::Creator(member) => &member.user,
+        ChatMember::Administrator(member) => &member.user,
+        ChatMember::Member(member) => &member.user,
+        ChatMember::Restricted(member) => &member.user,
+        ChatMember::Left(member) => &member.user,
+        ChatMember::Kicked(member) => &member.user,
+    }
+}
+
+/// Whether `member` is still part of the chat, even if restricted.
+fn in_chat(member: &ChatMember) -> bool {
+    match member {
+        ChatMember::Left(_) | ChatMember::Kicked(_) => false,
+        ChatMember::Restricted(member) => member.is_member,
+        _ => true,
+    }
+}
+
+fn is_admin(member: &ChatMember) -> bool {
+    matches!(
+        member,
+        ChatMember::Creator(_) | ChatMember::Administrator(_)
+    )
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,2224 @@
+use super::*;
+use crate::db::group_settings::GroupSettings;
+use crate::event::{EventDraft, MAX_DESCRIPTION_LEN, MAX_LOCATION_LEN, MAX_TITLE_LEN};
+use crate::reminder::Reminders;
+use crate::telegram::MockClient;
+use serde_json::json;
+
+fn user(id: u64) -> serde_json::Value {
+    json!({ "id": id, "is_bot": false, "first_name": "Test" })
+}
+
+fn test_config() -> Config {
+    Config {
+        database_url: db::TEST_DB_URL.to_string(),
+        ..Default::default()
+    }
+}
+
+async fn bot_with_admins() -> Bot<MockClient> {
+    let api = MockClient::new().respond(
+        "getChatAdministrators",
+        json!([
+            { "status": "creator", "user": user(1), "is_anonymous": false },
+            {
+                "status": "administrator",
+                "user": user(2),
+                "can_be_edited": false,
+                "is_anonymous": false,
+                "can_manage_chat": true,
+                "can_delete_messages": true,
+                "can_manage_video_chats": false,
+                "can_restrict_members": true,
+                "can_promote_members": false,
+                "can_change_info": false,
+                "can_invite_users": true
+            }
+        ]),
+    );
+    let config = test_config();
+    let pool = db::init_db(&config).await.unwrap();
+
+    Bot::new(api, pool, &config).await.unwrap()
+}
+
+#[tokio::test]
+async fn creation_can_be_restricted_to_admins() {
+    let mut bot = bot_with_admins().await;
+    bot.creation_roles = vec![CreationRole::Admin];
+
+    bot.handle_create(&ChatType::Supergroup, -100, 3, 1).await;
+    assert!(bot.event_contexts.is_empty());
+    assert_eq!(
+        bot.api.recorded("sendMessage")[0]["text"],
+        "Event creation is restricted to admins in this chat."
+    );
+
+    bot.handle_create(&ChatType::Supergroup, -100, 2, 1).await;
+    assert!(bot.event_contexts.contains_key(&2));
+}
+
+#[test]
+fn creation_roles_parse() {
+    assert_eq!(
+        parse_creation_roles("admin, Moderator").unwrap(),
+        vec![CreationRole::Admin, CreationRole::Moderator]
+    );
+    assert_eq!(
+        parse_creation_roles("all").unwrap(),
+        vec![CreationRole::All]
+    );
+    assert!(parse_creation_roles("admin,owner").is_err());
+}
+
+#[tokio::test]
+async fn admin_ids_are_cached() {
+    let mut bot = bot_with_admins().await;
+
+    assert_eq!(bot.get_chat_admin_ids(-100).await.unwrap(), vec![1, 2]);
+    assert_eq!(bot.get_chat_admin_ids(-100).await.unwrap(), vec![1, 2]);
+    assert_eq!(bot.api.calls_to("getChatAdministrators"), 1);
+}
+
+#[tokio::test]
+async fn admin_change_evicts_cache() {
+    let mut bot = bot_with_admins().await;
+    bot.get_chat_admin_ids(-100).await.unwrap();
+
+    let update: ChatMemberUpdated = serde_json::from_value(json!({
+        "chat": { "id": -100, "type": "supergroup" },
+        "from": user(1),
+        "date": 0,
+        "old_chat_member": { "status": "member", "user": user(3) },
+        "new_chat_member": {
+            "status": "administrator",
+            "user": user(3),
+            "can_be_edited": false,
+            "is_anonymous": false,
+            "can_manage_chat": true,
+            "can_delete_messages": false,
+            "can_manage_video_chats": false,
+            "can_restrict_members": false,
+            "can_promote_members": false,
+            "can_change_info": false,
+            "can_invite_users": true
+        }
+    }))
+    .unwrap();
+    bot.handle_chat_member(update);
+
+    bot.get_chat_admin_ids(-100).await.unwrap();
+    assert_eq!(bot.api.calls_to("getChatAdministrators"), 2);
+}
+
+#[tokio::test]
+async fn reminders_cover_the_next_day() {
+    let bot = bot_with_admins().await;
+    for (name, time) in [
+        ("past", "2025-08-14 18:00"),
+        ("now", "2025-08-15 12:00"),
+        ("tonight", "2025-08-15 19:00"),
+        ("soon", "2025-08-15 12:30"),
+        ("window end", "2025-08-16 12:00"),
+        ("too far", "2025-08-16 12:01"),
+    ] {
+        let draft = EventDraft {
+            name: name.to_string(),
+            time: time.to_string(),
+            ..Default::default()
+        };
+        db::create_event(&bot.pool, -100, 1, &draft).await.unwrap();
+    }
+
+    let now = parse_datetime_string("2025-08-15 12:00").unwrap().0;
+
+    assert_eq!(
+        bot.get_events_needing_reminder(now).await.unwrap(),
+        vec![4, 3, 5]
+    );
+}
+
+#[tokio::test]
+async fn reminders_are_sent_once() {
+    let bot = bot_with_admins().await;
+    let draft = EventDraft {
+        name: "Dinner".to_string(),
+        time: "2025-08-15 19:00".to_string(),
+        ..Default::default()
+    };
+    let event_id = db::create_event(&bot.pool, -100, 1, &draft).await.unwrap();
+    let now = parse_datetime_string("2025-08-15 12:00").unwrap().0;
+
+    assert_eq!(
+        bot.get_events_needing_reminder(now).await.unwrap(),
+        vec![event_id]
+    );
+
+    db::mark_reminders_sent(&bot.pool, event_id).await.unwrap();
+    assert!(bot
+        .get_events_needing_reminder(now)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn summaries_are_due_twelve_hours_ahead() {
+    let bot = bot_with_admins().await;
+    let mut event_ids = Vec::new();
+    for time in [
+        "2025-08-15 22:59",
+        "2025-08-15 23:00",
+        "2025-08-16 00:00",
+        "2025-08-16 01:00",
+        "2025-08-16 01:01",
+    ] {
+        let draft = EventDraft {
+            time: time.to_string(),
+            ..Default::default()
+        };
+        event_ids.push(db::create_event(&bot.pool, -100, 1, &draft).await.unwrap());
+    }
+    let now = parse_datetime_string("2025-08-15 12:00").unwrap().0;
+    let (from, until) = (
+        now + chrono::Duration::hours(11),
+        now + chrono::Duration::hours(13),
+    );
+
+    assert_eq!(
+        db::get_events_needing_summary(&bot.pool, from, until)
+            .await
+            .unwrap(),
+        event_ids[1..4]
+    );
+
+    db::mark_summary_sent(&bot.pool, event_ids[2])
+        .await
+        .unwrap();
+    assert_eq!(
+        db::get_events_needing_summary(&bot.pool, from, until)
+            .await
+            .unwrap(),
+        vec![event_ids[1], event_ids[3]]
+    );
+}
+
+#[tokio::test]
+async fn new_events_are_passed_to_the_reminders() {
+    let mut bot = bot_with_admins().await;
+    let mut new_events = bot.subscribe_new_events();
+    let reminders = Reminders::new(
+        MockClient::new(),
+        bot.pool.clone(),
+        Duration::from_secs(3600),
+    );
+    let now = Utc::now().naive_utc();
+
+    for (name, starts_in) in [("Dinner", 2), ("Picnic", 48)] {
+        let mut context = EventContext::new(None);
+        context.draft.name = name.to_string();
+        context.draft.time = (now + chrono::Duration::hours(starts_in))
+            .format(DATETIME_FORMAT)
+            .to_string();
+        bot.event_contexts.insert(1, context);
+        bot.save_draft(-100, 1).await;
+    }
+
+    assert_eq!(new_events.try_recv().unwrap(), 1);
+    assert_eq!(new_events.try_recv().unwrap(), 2);
+    assert!(reminders.send_due_for(1, now).await.unwrap());
+    assert!(!reminders.send_due_for(1, now).await.unwrap());
+    assert!(!reminders.send_due_for(2, now).await.unwrap());
+}
+
+#[tokio::test]
+async fn no_reminders_without_events() {
+    let bot = bot_with_admins().await;
+    let now = parse_datetime_string("2025-08-15 12:00").unwrap().0;
+
+    assert!(bot
+        .get_events_needing_reminder(now)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn full_events_waitlist_and_promote() {
+    let bot = bot_with_admins().await;
+    let draft = EventDraft {
+        name: "Dinner".to_string(),
+        max_capacity: Some(1),
+        ..Default::default()
+    };
+    let event_id = db::create_event(&bot.pool, -100, 1, &draft).await.unwrap();
+
+    let accept =
+        |user_id| db::update_attendance(&bot.pool, event_id, user_id, RsvpStatus::Accepted);
+    assert_eq!(accept(10).await.unwrap(), None);
+    assert_eq!(accept(11).await.unwrap(), None);
+    assert_eq!(accept(12).await.unwrap(), None);
+
+    let statuses = db::get_attendees(&bot.pool, event_id).await.unwrap();
+    assert_eq!(
+        statuses,
+        vec![
+            (10, "accepted".to_string()),
+            (11, "waitlist".to_string()),
+            (12, "waitlist".to_string()),
+        ]
+    );
+
+    let promoted = db::update_attendance(&bot.pool, event_id, 10, RsvpStatus::Declined)
+        .await
+        .unwrap();
+    assert_eq!(promoted, Some(11));
+
+    // Accepting again from the waitlist leaves it.
+    assert_eq!(accept(12).await.unwrap(), None);
+    let statuses = db::get_attendees(&bot.pool, event_id).await.unwrap();
+    assert_eq!(
+        statuses,
+        vec![(10, "declined".to_string()), (11, "accepted".to_string())]
+    );
+}
+
+#[tokio::test]
+async fn creator_edits_event_and_message() {
+    let mut bot = bot_with_admins().await;
+    let draft = EventDraft {
+        name: "Dinnr".to_string(),
+        ..Default::default()
+    };
+    let event_id = db::create_event(&bot.pool, -100, 1, &draft).await.unwrap();
+    db::set_event_message_id(&bot.pool, event_id, Some(42))
+        .await
+        .unwrap();
+
+    bot.handle_edit(-100, 2, &event_id.to_string()).await;
+    assert!(bot.event_edit_contexts.is_empty());
+
+    bot.handle_edit(-100, 1, &event_id.to_string()).await;
+    bot.handle_event_edit(1, "Title".to_string()).await;
+    bot.handle_event_edit(1, " Dinner ".to_string()).await;
+
+    let event = db::get_event(&bot.pool, event_id).await.unwrap().unwrap();
+    assert_eq!(event.name, "Dinner");
+    assert!(bot.event_edit_contexts.is_empty());
+    assert_eq!(bot.api.calls_to("editMessageText"), 1);
+}
+
+#[tokio::test]
+async fn user_names_are_cached() {
+    let mut bot = bot_with_admins().await;
+    let mut member = user(3);
+    member["last_name"] = json!("User");
+    member["username"] = json!("tester");
+    bot.api = MockClient::new().respond(
+        "getChatMember",
+        json!({ "status": "member", "user": member }),
+    );
+
+    for _ in 0..2 {
+        let name = bot.get_user_name(-100, 3).await;
+        assert_eq!(name.to_string(), "Test User (@tester)");
+    }
+    assert_eq!(bot.api.calls_to("getChatMember"), 1);
+
+    bot.get_user_name(-200, 3).await;
+    assert_eq!(bot.api.calls_to("getChatMember"), 2);
+}
+
+#[tokio::test]
+async fn rsvp_presses_are_answered() {
+    let mut bot = bot_with_admins().await;
+    let draft = EventDraft {
+        name: "Dinner".to_string(),
+        ..Default::default()
+    };
+    let event_id = db::create_event(&bot.pool, -100, 1, &draft).await.unwrap();
+
+    for data in [format!("accept_{event_id}"), "accept_999".to_string()] {
+        let query: CallbackQuery = serde_json::from_value(json!({
+            "id": "1",
+            "from": user(3),
+            "chat_instance": "1",
+            "data": data
+        }))
+        .unwrap();
+        bot.handle_callback_query(query).await.unwrap();
+    }
+
+    assert_eq!(bot.api.calls_to("answerCallbackQuery"), 2);
+    // The creator hears about the RSVP.
+    assert_eq!(bot.api.calls_to("sendMessage"), 1);
+    assert_eq!(
+        db::get_attendees(&bot.pool, event_id).await.unwrap(),
+        vec![(3, "accepted".to_string())]
+    );
+}
+
+#[tokio::test]
+async fn stale_drafts_expire() {
+    let mut bot = bot_with_admins().await;
+    bot.draft_timeout = Duration::from_secs(60);
+    bot.event_contexts.insert(1, EventContext::new(None));
+    let mut stale = EventContext::new(None);
+    stale.created_at -= Duration::from_secs(61);
+    bot.event_contexts.insert(2, stale);
+
+    bot.expire_drafts().await;
+
+    assert!(bot.event_contexts.contains_key(&1));
+    assert!(!bot.event_contexts.contains_key(&2));
+    assert_eq!(bot.api.calls_to("sendMessage"), 1);
+}
+
+#[tokio::test]
+async fn clones_copy_everything_but_the_time() {
+    let mut bot = bot_with_admins().await;
+    let draft = EventDraft {
+        name: "Book club".to_string(),
+        location: "Library".to_string(),
+        time: "2025-08-15 19:00".to_string(),
+        max_capacity: Some(8),
+        ..Default::default()
+    };
+    let event_id = db::create_event(&bot.pool, -100, 1, &draft).await.unwrap();
+
+    // From a private chat, the clone still belongs to the original group.
+    bot.handle_clone(1, 1, &event_id.to_string()).await;
+    bot.handle_event_creation(1, 1, "2025-09-12 19:00".to_string())
+        .await;
+
+    let clone = db::get_event(&bot.pool, event_id + 1)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(clone.chat_id, -100);
+    assert_eq!(clone.name, "Book club");
+    assert_eq!(clone.location, "Library");
+    assert_eq!(clone.time, "2025-09-12 19:00");
+    assert_eq!(clone.max_capacity, Some(8));
+    assert!(bot.event_contexts.is_empty());
+}
+
+#[tokio::test]
+async fn creators_delete_events_and_messages() {
+    let mut bot = bot_with_admins().await;
+    let draft = EventDraft {
+        name: "Dinner".to_string(),
+        ..Default::default()
+    };
+    let event_id = db::create_event(&bot.pool, -100, 1, &draft).await.unwrap();
+    db::set_event_message_id(&bot.pool, event_id, Some(42))
+        .await
+        .unwrap();
+    db::update_attendance(&bot.pool, event_id, 3, RsvpStatus::Accepted)
+        .await
+        .unwrap();
+
+    bot.handle_delete(-100, 2, &event_id.to_string(), CancelMode::Hard)
+        .await;
+    assert!(db::get_event(&bot.pool, event_id).await.unwrap().is_some());
+
+    // The creator is asked to confirm, and may change their mind.
+    bot.handle_delete(-100, 1, &event_id.to_string(), CancelMode::Hard)
+        .await;
+    assert!(db::get_event(&bot.pool, event_id).await.unwrap().is_some());
+    let press = |prefix: &str| -> CallbackQuery {
+        serde_json::from_value(json!({
+            "id": "1",
+            "from": user(1),
+            "chat_instance": "1",
+            "message": { "message_id": 43, "date": 0, "chat": { "id": -100, "type": "group" } },
+            "data": format!("{prefix}{event_id}")
+        }))
+        .unwrap()
+    };
+    bot.handle_callback_query(press(CANCEL_DELETE_CALLBACK_PREFIX))
+        .await
+        .unwrap();
+    assert!(db::get_event(&bot.pool, event_id).await.unwrap().is_some());
+
+    bot.handle_callback_query(press(CONFIRM_DELETE_CALLBACK_PREFIX))
+        .await
+        .unwrap();
+    assert!(db::get_event(&bot.pool, event_id).await.unwrap().is_none());
+    assert!(db::get_attendees(&bot.pool, event_id)
+        .await
+        .unwrap()
+        .is_empty());
+    assert_eq!(bot.api.calls_to("deleteMessage"), 1);
+    assert_eq!(bot.api.calls_to("editMessageText"), 2);
+}
+
+#[tokio::test]
+async fn admins_prune_old_events_of_their_group() {
+    let mut bot = bot_with_admins().await;
+    let now = Utc::now().naive_utc();
+    for (chat_id, name, days_ago) in [
+        (-100, "Old", 100),
+        (-100, "Recent", 10),
+        (-200, "Old elsewhere", 100),
+    ] {
+        let draft = EventDraft {
+            name: name.to_string(),
+            time: (now - chrono::Duration::days(days_ago))
+                .format(DATETIME_FORMAT)
+                .to_string(),
+            ..Default::default()
+        };
+        db::create_event(&bot.pool, chat_id, 1, &draft)
+            .await
+            .unwrap();
+    }
+
+    bot.handle_prune_old(&ChatType::Supergroup, -100, 3, "")
+        .await;
+    bot.handle_prune_old(&ChatType::Supergroup, -100, 1, "dry 5")
+        .await;
+    bot.handle_prune_old(&ChatType::Supergroup, -100, 1, "soon")
+        .await;
+    bot.handle_prune_old(&ChatType::Supergroup, -100, 1, "")
+        .await;
+    bot.handle_prune_confirmation(-100, 1, "no").await;
+    assert!(db::get_event(&bot.pool, 1).await.unwrap().is_some());
+    bot.handle_prune_old(&ChatType::Supergroup, -100, 1, "")
+        .await;
+    bot.handle_prune_confirmation(-100, 1, "Yes").await;
+
+    assert!(db::get_event(&bot.pool, 1).await.unwrap().is_none());
+    assert!(db::get_event(&bot.pool, 2).await.unwrap().is_some());
+    assert!(db::get_event(&bot.pool, 3).await.unwrap().is_some());
+    let texts: Vec<_> = bot
+        .api
+        .recorded("sendMessage")
+        .into_iter()
+        .map(|params| params["text"].clone())
+        .collect();
+    assert_eq!(
+        texts,
+        [
+            json!("Only group administrators can prune old events."),
+            json!("2 events are older than 5 days and would be deleted."),
+            json!("Usage: /pruneold [dry] [days]"),
+            json!("⚠️ This will delete 1 events. Reply 'yes' to confirm."),
+            json!("Nothing was pruned."),
+            json!("⚠️ This will delete 1 events. Reply 'yes' to confirm."),
+            json!("Pruned 1 events."),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn settings_limit_upcoming_events() {
+    let mut bot = bot_with_admins().await;
+
+    // Nothing is asked if the admin can't be messaged privately.
+    bot.api = bot
+        .api
+        .fail("sendMessage", "Forbidden: bot can't initiate conversation");
+    bot.handle_settings(&ChatType::Supergroup, -100, 1).await;
+    assert!(bot.settings_contexts.is_empty());
+    assert_eq!(
+        bot.api.recorded("sendMessage")[1]["text"],
+        "Could not send you the settings. Start a private chat with me and try again."
+    );
+
+    bot.api = MockClient::new().respond(
+        "sendMessage",
+        json!({ "message_id": 1, "date": 0, "chat": { "id": 1, "type": "private" } }),
+    );
+    bot.handle_settings(&ChatType::Supergroup, -100, 1).await;
+    assert!(bot.settings_contexts.contains_key(&1));
+    for answer in ["1", "skip", "skip", "no", "yes", "Europe/Berlin"] {
+        bot.handle_settings_input(1, answer.to_string()).await;
+    }
+
+    let settings = group_settings::get_settings(&bot.pool, -100).await.unwrap();
+    assert_eq!(settings.max_events, 1);
+    assert!(!settings.require_admin);
+    assert!(!settings.notify_creator);
+    assert!(settings.pinned);
+    assert!(!settings.trusted_creators_only);
+    assert_eq!(settings.timezone, "Europe/Berlin");
+
+    for name in ["First", "Second"] {
+        let mut context = EventContext::new(None);
+        context.draft.name = name.to_string();
+        context.draft.time = "2999-01-01 19:00".to_string();
+        bot.event_contexts.insert(2, context);
+        bot.save_draft(-100, 2).await;
+    }
+
+    assert!(db::get_event(&bot.pool, 1).await.unwrap().is_some());
+    assert!(db::get_event(&bot.pool, 2).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn overlong_fields_are_asked_for_again() {
+    let mut bot = bot_with_admins().await;
+    bot.event_contexts.insert(1, EventContext::new(None));
+
+    for (max_len, state) in [
+        (MAX_TITLE_LEN, UserState::AwaitingName),
+        (MAX_DESCRIPTION_LEN, UserState::AwaitingDescription),
+        (MAX_LOCATION_LEN, UserState::AwaitingLocation),
+    ] {
+        assert_eq!(bot.event_contexts[&1].state, state);
+        bot.handle_event_creation(1, 1, "x".repeat(max_len + 1))
+            .await;
+        assert_eq!(bot.event_contexts[&1].state, state);
+
+        bot.handle_event_creation(1, 1, "x".repeat(max_len)).await;
+    }
+
+    assert_eq!(bot.event_contexts[&1].state, UserState::AwaitingTime);
+    assert_eq!(bot.event_contexts[&1].draft.name.len(), MAX_TITLE_LEN);
+}
+
+#[tokio::test]
+async fn attending_lists_upcoming_accepted_and_maybe() {
+    let bot = bot_with_admins().await;
+    let mut event_ids = Vec::new();
+    for time in [
+        "2025-08-14 19:00",
+        "2025-08-16 19:00",
+        "2025-08-17 19:00",
+        "2025-08-18 19:00",
+    ] {
+        let draft = EventDraft {
+            time: time.to_string(),
+            ..Default::default()
+        };
+        event_ids.push(db::create_event(&bot.pool, -100, 1, &draft).await.unwrap());
+    }
+    for (event_id, status) in event_ids.iter().zip([
+        RsvpStatus::Accepted,
+        RsvpStatus::Maybe,
+        RsvpStatus::Declined,
+        RsvpStatus::Accepted,
+    ]) {
+        db::update_attendance(&bot.pool, *event_id, 3, status)
+            .await
+            .unwrap();
+    }
+    let now = parse_datetime_string("2025-08-15 12:00").unwrap().0;
+
+    assert_eq!(
+        db::get_attending_event_ids(&bot.pool, 3, now, EventSort::ByDate)
+            .await
+            .unwrap(),
+        vec![event_ids[1], event_ids[3]]
+    );
+}
+
+#[tokio::test]
+async fn rsvps_close_at_the_deadline() {
+    let mut bot = bot_with_admins().await;
+    let draft = EventDraft {
+        name: "Dinner".to_string(),
+        time: "2999-01-01 19:00".to_string(),
+        rsvp_deadline: Some("2000-01-01 12:00".to_string()),
+        ..Default::default()
+    };
+    let event_id = db::create_event(&bot.pool, -100, 1, &draft).await.unwrap();
+
+    let query: CallbackQuery = serde_json::from_value(json!({
+        "id": "1",
+        "from": user(3),
+        "chat_instance": "1",
+        "data": format!("accept_{event_id}")
+    }))
+    .unwrap();
+    bot.handle_callback_query(query).await.unwrap();
+
+    assert_eq!(bot.api.calls_to("answerCallbackQuery"), 1);
+    assert!(db::get_attendees(&bot.pool, event_id)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn upcoming_events_stay_in_their_chat_and_window() {
+    let bot = bot_with_admins().await;
+    for (chat_id, name, time) in [
+        (-100, "tomorrow", "2025-08-16 19:00"),
+        (-100, "next month", "2025-09-15 19:00"),
+        (-200, "elsewhere", "2025-08-16 19:00"),
+        (-100, "yesterday", "2025-08-14 19:00"),
+        (-100, "this week", "2025-08-20 19:00"),
+    ] {
+        let draft = EventDraft {
+            name: name.to_string(),
+            time: time.to_string(),
+            ..Default::default()
+        };
+        db::create_event(&bot.pool, chat_id, 1, &draft)
+            .await
+            .unwrap();
+    }
+    let now = parse_datetime_string("2025-08-15 12:00").unwrap().0;
+
+    let names: Vec<String> = db::fetch_upcoming_events(&bot.pool, -100, now, 7)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|event| event.name)
+        .collect();
+    assert_eq!(names, vec!["tomorrow", "this week"]);
+}
+
+#[tokio::test]
+async fn cancelled_events_are_kept_but_hidden() {
+    let mut bot = bot_with_admins().await;
+    let draft = EventDraft {
+        name: "Dinner".to_string(),
+        time: "2999-01-01 19:00".to_string(),
+        ..Default::default()
+    };
+    let event_id = db::create_event(&bot.pool, -100, 1, &draft).await.unwrap();
+    db::update_attendance(&bot.pool, event_id, 3, RsvpStatus::Accepted)
+        .await
+        .unwrap();
+
+    bot.handle_delete(-100, 1, &event_id.to_string(), CancelMode::Soft)
+        .await;
+
+    let event = db::get_event(&bot.pool, event_id).await.unwrap().unwrap();
+    assert!(event.cancelled_at.is_some());
+    assert!(event.format_message(Tz::UTC).contains("🚫 Cancelled"));
+
+    let now = Utc::now().naive_utc();
+    assert!(db::fetch_upcoming_events(&bot.pool, -100, now, 365)
+        .await
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        db::fetch_past_events(&bot.pool, -100, now).await.unwrap()[0].id,
+        event_id
+    );
+    // The accepted attendee and the creator are told.
+    assert_eq!(bot.api.calls_to("sendMessage"), 2);
+}
+
+#[tokio::test]
+async fn commands_are_registered() {
+    let mut bot = bot_with_admins().await;
+
+    bot.register_commands().await;
+    assert_eq!(bot.api.calls_to("setMyCommands"), 1);
+    assert_eq!(bot.api.calls_to("deleteMyCommands"), 0);
+
+    bot.clear_commands = true;
+    bot.register_commands().await;
+    assert_eq!(bot.api.calls_to("setMyCommands"), 2);
+    assert_eq!(bot.api.calls_to("deleteMyCommands"), 1);
+
+    bot.test_mode = true;
+    bot.register_commands().await;
+    assert_eq!(bot.api.calls_to("setMyCommands"), 2);
+    assert_eq!(bot.api.calls_to("deleteMyCommands"), 1);
+}
+
+#[tokio::test]
+async fn transfers_need_a_reachable_member() {
+    let mut bot = bot_with_admins().await;
+    let draft = EventDraft {
+        name: "Dinner".to_string(),
+        ..Default::default()
+    };
+    let event_id = db::create_event(&bot.pool, -100, 1, &draft).await.unwrap();
+    let args = format!("{event_id} 3");
+    bot.api = MockClient::new().respond(
+        "getChatMember",
+        json!({ "status": "member", "user": user(3) }),
+    );
+
+    // The new owner can't be messaged.
+    bot.handle_transfer(-100, 1, &args).await;
+    let event = db::get_event(&bot.pool, event_id).await.unwrap().unwrap();
+    assert_eq!(event.creator, 1);
+
+    bot.api = bot.api.respond(
+        "sendMessage",
+        json!({ "message_id": 1, "date": 0, "chat": { "id": 3, "type": "private" } }),
+    );
+    bot.handle_transfer(-100, 2, &args).await;
+    bot.handle_transfer(-100, 1, &args).await;
+    let event = db::get_event(&bot.pool, event_id).await.unwrap().unwrap();
+    assert_eq!(event.creator, 3);
+}
+
+#[tokio::test]
+async fn inline_queries_find_own_and_attended_events() {
+    let mut bot = bot_with_admins().await;
+    let mut event_ids = Vec::new();
+    for (name, creator) in [
+        ("Board games", 1),
+        ("Dinner", 2),
+        ("Dinner party", 2),
+        ("100% fun", 1),
+    ] {
+        let draft = EventDraft {
+            name: name.to_string(),
+            time: "2999-01-01 19:00".to_string(),
+            ..Default::default()
+        };
+        event_ids.push(
+            db::create_event(&bot.pool, -100, creator, &draft)
+                .await
+                .unwrap(),
+        );
+    }
+    db::update_attendance(&bot.pool, event_ids[1], 1, RsvpStatus::Accepted)
+        .await
+        .unwrap();
+    let now = Utc::now().naive_utc();
+
+    let search = |query: &'static str| db::search_user_events(&bot.pool, 1, query, now, 10);
+    assert_eq!(search("dinner").await.unwrap(), vec![event_ids[1]]);
+    assert_eq!(search("%").await.unwrap(), vec![event_ids[3]]);
+    assert_eq!(search("").await.unwrap().len(), 3);
+
+    let query = serde_json::from_value(json!({
+        "id": "1",
+        "from": user(1),
+        "query": "games",
+        "offset": ""
+    }))
+    .unwrap();
+    bot.handle_inline_query(query).await.unwrap();
+    assert_eq!(bot.api.calls_to("answerInlineQuery"), 1);
+}
+
+#[tokio::test]
+async fn pinned_events_are_unpinned_when_cancelled() {
+    let mut bot = bot_with_admins().await;
+    let mut settings = GroupSettings::new(-100);
+    settings.pinned = true;
+    group_settings::upsert_settings(&bot.pool, &settings)
+        .await
+        .unwrap();
+    let draft = EventDraft {
+        name: "Dinner".to_string(),
+        time: "2999-01-01 19:00".to_string(),
+        ..Default::default()
+    };
+    let event_id = db::create_event(&bot.pool, -100, 1, &draft).await.unwrap();
+    bot.api = MockClient::new().respond(
+        "sendMessage",
+        json!({ "message_id": 42, "date": 0, "chat": { "id": -100, "type": "supergroup" } }),
+    );
+
+    bot.post_event(event_id, None).await.unwrap();
+    assert_eq!(bot.api.calls_to("pinChatMessage"), 1);
+
+    bot.handle_delete(-100, 1, &event_id.to_string(), CancelMode::Soft)
+        .await;
+    assert_eq!(bot.api.calls_to("unpinChatMessage"), 1);
+}
+
+#[tokio::test]
+async fn stats_summarize_a_groups_rsvps() {
+    let mut bot = bot_with_admins().await;
+    let mut event_ids = Vec::new();
+    for (name, time) in [
+        ("Dinner", "2025-08-02 19:00"),
+        ("Picnic", "2025-08-20 12:00"),
+        ("Hike", "2025-09-01 09:00"),
+    ] {
+        let draft = EventDraft {
+            name: name.to_string(),
+            time: time.to_string(),
+            ..Default::default()
+        };
+        event_ids.push(db::create_event(&bot.pool, -100, 1, &draft).await.unwrap());
+    }
+    for (event_id, user_id, status) in [
+        (event_ids[0], 3, RsvpStatus::Accepted),
+        (event_ids[1], 3, RsvpStatus::Accepted),
+        (event_ids[1], 4, RsvpStatus::Accepted),
+        (event_ids[1], 5, RsvpStatus::Declined),
+        (event_ids[2], 5, RsvpStatus::Maybe),
+    ] {
+        db::update_attendance(&bot.pool, event_id, user_id, status)
+            .await
+            .unwrap();
+    }
+    let now = parse_datetime_string("2025-08-15 12:00").unwrap().0;
+
+    assert_eq!(
+        db::stats::get_group_stats(&bot.pool, -100, now)
+            .await
+            .unwrap(),
+        db::stats::GroupStats {
+            total_events: 3,
+            events_this_month: 2,
+            average_accepted: 1.0,
+            top_event: Some(("Picnic".to_string(), 2)),
+            unique_attendees: 2,
+        }
+    );
+
+    bot.handle_stats(&ChatType::Supergroup, -100, 3).await;
+    bot.handle_stats(&ChatType::Supergroup, -100, 1).await;
+    assert_eq!(bot.api.calls_to("sendMessage"), 2);
+}
+
+#[tokio::test]
+async fn past_events_are_read_only() {
+    let mut bot = bot_with_admins().await;
+    let draft = EventDraft {
+        name: "Dinner".to_string(),
+        time: "2000-01-01 19:00".to_string(),
+        ..Default::default()
+    };
+    let event_id = db::create_event(&bot.pool, -100, 1, &draft).await.unwrap();
+
+    for data in [format!("accept_{event_id}"), format!("ended_{event_id}")] {
+        let query: CallbackQuery = serde_json::from_value(json!({
+            "id": "1",
+            "from": user(3),
+            "chat_instance": "1",
+            "data": data
+        }))
+        .unwrap();
+        bot.handle_callback_query(query).await.unwrap();
+    }
+
+    assert_eq!(bot.api.calls_to("answerCallbackQuery"), 2);
+    assert!(db::get_attendees(&bot.pool, event_id)
+        .await
+        .unwrap()
+        .is_empty());
+    let event = db::get_event(&bot.pool, event_id).await.unwrap().unwrap();
+    assert_eq!(
+        event.ended_keyboard().inline_keyboard[0][0].text,
+        "🏁 Ended · 0 went"
+    );
+}
+
+#[tokio::test]
+async fn duplicate_events_are_not_saved() {
+    let mut bot = bot_with_admins().await;
+    let draft = EventDraft {
+        name: "Dinner".to_string(),
+        time: "2999-01-01 19:00".to_string(),
+        ..Default::default()
+    };
+    db::create_event(&bot.pool, -100, 1, &draft).await.unwrap();
+
+    let mut context = EventContext::new(None);
+    context.draft.name = "dinner".to_string();
+    context.draft.time = draft.time.clone();
+    bot.event_contexts.insert(2, context);
+    bot.save_draft(-100, 2).await;
+
+    assert!(bot.event_contexts.is_empty());
+    let now = parse_datetime_string("2025-08-15 12:00").unwrap().0;
+    assert_eq!(
+        db::count_upcoming_events(&bot.pool, -100, now)
+            .await
+            .unwrap(),
+        1
+    );
+    // The same event in another group is fine.
+    assert!(db::create_event(&bot.pool, -200, 1, &draft).await.is_ok());
+}
+
+#[tokio::test]
+async fn groups_are_limited_to_max_events() {
+    let mut bot = bot_with_admins().await;
+    let mut settings = GroupSettings::new(-100);
+    settings.max_events = 1;
+    group_settings::upsert_settings(&bot.pool, &settings)
+        .await
+        .unwrap();
+    let draft = EventDraft {
+        name: "Dinner".to_string(),
+        time: "2999-01-01 19:00".to_string(),
+        ..Default::default()
+    };
+    db::create_event(&bot.pool, -100, 1, &draft).await.unwrap();
+
+    let mut context = EventContext::new(None);
+    context.draft.name = "Lunch".to_string();
+    context.draft.time = draft.time.clone();
+    bot.event_contexts.insert(2, context);
+    bot.save_draft(-100, 2).await;
+
+    assert!(bot.event_contexts.is_empty());
+    assert_eq!(bot.api.calls_to("sendMessage"), 1);
+    let lunch = EventDraft {
+        name: "Lunch".to_string(),
+        ..draft.clone()
+    };
+    assert!(matches!(
+        db::create_event(&bot.pool, -100, 2, &lunch).await,
+        Err(BotError::LimitExceeded(_))
+    ));
+    // Private events have no limit.
+    assert!(db::create_event(&bot.pool, 2, 2, &draft).await.is_ok());
+}
+
+#[tokio::test]
+async fn declines_wait_for_a_reason() {
+    let mut bot = bot_with_admins().await;
+    let draft = EventDraft {
+        name: "Dinner".to_string(),
+        time: "2999-01-01 19:00".to_string(),
+        ..Default::default()
+    };
+    let event_id = db::create_event(&bot.pool, -100, 1, &draft).await.unwrap();
+    bot.api = MockClient::new()
+        .respond(
+            "sendMessage",
+            json!({ "message_id": 1, "date": 0, "chat": { "id": 3, "type": "private" } }),
+        )
+        .respond(
+            "getChatMember",
+            json!({ "status": "member", "user": user(3) }),
+        );
+    let query: CallbackQuery = serde_json::from_value(json!({
+        "id": "1",
+        "from": user(3),
+        "chat_instance": "1",
+        "message": {
+            "message_id": 42,
+            "date": 0,
+            "chat": { "id": -100, "type": "supergroup" }
+        },
+        "data": format!("decline_{event_id}")
+    }))
+    .unwrap();
+
+    bot.handle_callback_query(query).await.unwrap();
+    assert_eq!(bot.pending_declines.get(&3), Some(&(event_id, -100, 42)));
+    assert!(db::get_attendees(&bot.pool, event_id)
+        .await
+        .unwrap()
+        .is_empty());
+
+    bot.handle_decline_reason(3, "Out of town".to_string())
+        .await;
+    assert!(bot.pending_declines.is_empty());
+    let event = bot.fetch_event(event_id).await.unwrap().unwrap();
+    assert_eq!(event.declined, vec![(3, "Test".into())]);
+    assert!(event
+        .format_message(Tz::UTC)
+        .contains("• Test — Out of town"));
+}
+
+#[tokio::test]
+async fn drafts_survive_a_restart() {
+    let mut bot = bot_with_admins().await;
+    let mut context = EventContext::new(Some(Tz::Europe__Berlin));
+    context.state = UserState::AwaitingTime;
+    context.draft.name = "Board games".to_string();
+    context.draft.max_capacity = Some(6);
+    context.clone_chat_id = Some(-100);
+    bot.event_contexts.insert(3, context);
+    bot.store_draft(3).await;
+
+    let restored = Bot::new(MockClient::new(), bot.pool.clone(), &test_config())
+        .await
+        .unwrap();
+
+    let context = &restored.event_contexts[&3];
+    assert_eq!(context.state, UserState::AwaitingTime);
+    assert_eq!(context.draft.name, "Board games");
+    assert_eq!(context.draft.max_capacity, Some(6));
+    assert_eq!(context.timezone, Some(Tz::Europe__Berlin));
+    assert_eq!(context.clone_chat_id, Some(-100));
+
+    bot.forget_draft(3).await;
+    assert!(drafts::get_drafts(&bot.pool).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn new_fails_when_telegram_rejects_the_token() {
+    let api = MockClient::new().respond("getMe", json!(true));
+    let config = test_config();
+    let pool = db::init_db(&config).await.unwrap();
+
+    assert!(matches!(
+        Bot::new(api, pool, &config).await,
+        Err(BotError::Telegram(_))
+    ));
+}
+
+#[tokio::test]
+async fn supergroup_upgrades_move_events_once() {
+    let mut bot = bot_with_admins().await;
+    let draft = EventDraft {
+        name: "Dinner".to_string(),
+        time: "2025-08-15 19:00".to_string(),
+        ..Default::default()
+    };
+    let event_id = db::create_event(&bot.pool, -100, 1, &draft).await.unwrap();
+
+    for message in [
+        json!({
+            "message_id": 1,
+            "date": 0,
+            "chat": { "id": -100, "type": "group" },
+            "migrate_to_chat_id": -1000000000100i64,
+        }),
+        json!({
+            "message_id": 2,
+            "date": 0,
+            "chat": { "id": -1000000000100i64, "type": "supergroup" },
+            "migrate_from_chat_id": -100,
+        }),
+    ] {
+        bot.handle_message(serde_json::from_value(message).unwrap())
+            .await;
+    }
+
+    let event = db::get_event(&bot.pool, event_id).await.unwrap().unwrap();
+    assert_eq!(event.chat_id, -1000000000100);
+    assert_eq!(bot.api.calls_to("sendMessage"), 1);
+}
+
+#[tokio::test]
+async fn search_matches_titles_of_the_chat() {
+    let bot = bot_with_admins().await;
+    for (chat_id, name, time) in [
+        (-100, "Board Games", "2025-08-16 19:00"),
+        (-100, "Dinner", "2025-08-15 19:00"),
+        (-100, "More board games", "2025-08-14 19:00"),
+        (-100, "100% fun", "2025-08-17 19:00"),
+        (-200, "Board games elsewhere", "2025-08-15 19:00"),
+        (-100, "Past board games", "2025-08-01 19:00"),
+        (-100, "Cancelled board games", "2025-08-20 19:00"),
+    ] {
+        let draft = EventDraft {
+            name: name.to_string(),
+            time: time.to_string(),
+            ..Default::default()
+        };
+        db::create_event(&bot.pool, chat_id, 1, &draft)
+            .await
+            .unwrap();
+    }
+    db::delete_event(&bot.pool, 7, CancelMode::Soft)
+        .await
+        .unwrap();
+    let now = parse_datetime_string("2025-08-10 12:00").unwrap().0;
+
+    assert_eq!(
+        db::search_events(&bot.pool, -100, "BOARD", now, 10)
+            .await
+            .unwrap(),
+        vec![3, 1]
+    );
+    assert_eq!(
+        db::search_events(&bot.pool, -100, "0%", now, 10)
+            .await
+            .unwrap(),
+        vec![4]
+    );
+    assert!(db::search_events(&bot.pool, -100, "picnic", now, 10)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn list_filters_by_whole_tags() {
+    let bot = bot_with_admins().await;
+    for (name, tags) in [
+        ("Football", vec!["sports", "outdoor"]),
+        ("Esports night", vec!["esports"]),
+        ("Dinner", vec![]),
+    ] {
+        let draft = EventDraft {
+            name: name.to_string(),
+            time: "2025-08-15 19:00".to_string(),
+            tags: tags.into_iter().map(String::from).collect(),
+            ..Default::default()
+        };
+        db::create_event(&bot.pool, -100, 1, &draft).await.unwrap();
+    }
+    let now = parse_datetime_string("2025-08-15 12:00").unwrap().0;
+
+    let names = |events: Vec<Event>| {
+        events
+            .into_iter()
+            .map(|event| event.name)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        names(
+            db::fetch_events_paged(&bot.pool, -100, now, Some("sports"), 0, 10)
+                .await
+                .unwrap()
+        ),
+        ["Football"]
+    );
+    assert_eq!(
+        db::fetch_events_paged(&bot.pool, -100, now, None, 0, 10)
+            .await
+            .unwrap()
+            .len(),
+        3
+    );
+    assert_eq!(
+        db::get_chat_tags(&bot.pool, -100, now).await.unwrap(),
+        ["esports", "outdoor", "sports"]
+    );
+}
+
+#[tokio::test]
+async fn my_events_buttons_only_work_for_their_user() {
+    let mut bot = bot_with_admins().await;
+    let draft = EventDraft {
+        name: "Dinner".to_string(),
+        time: "2999-08-15 19:00".to_string(),
+        ..Default::default()
+    };
+    db::create_event(&bot.pool, -100, 1, &draft).await.unwrap();
+
+    for from in [2, 1] {
+        let query: CallbackQuery = serde_json::from_value(json!({
+            "id": "1",
+            "from": user(from),
+            "chat_instance": "1",
+            "data": format!("{MY_EVENTS_CREATED_PREFIX}1")
+        }))
+        .unwrap();
+        bot.handle_callback_query(query).await.unwrap();
+    }
+
+    assert_eq!(bot.api.calls_to("answerCallbackQuery"), 2);
+    assert_eq!(bot.api.calls_to("sendMessage"), 1);
+}
+
+#[tokio::test]
+async fn every_list_command_is_paged() {
+    let mut bot = bot_with_admins().await;
+    let now = Utc::now().naive_utc();
+    for day in 1..=7 {
+        for (name, time) in [
+            ("Past", now - chrono::Duration::days(day)),
+            ("Soon", now + chrono::Duration::days(day)),
+        ] {
+            let draft = EventDraft {
+                name: format!("{name} {day}"),
+                time: time.format(DATETIME_FORMAT).to_string(),
+                ..Default::default()
+            };
+            db::create_event(&bot.pool, -100, 1, &draft).await.unwrap();
+        }
+    }
+    let texts = |bot: &Bot<MockClient>| {
+        bot.api
+            .recorded("sendMessage")
+            .into_iter()
+            .map(|message| message["text"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+
+    bot.handle_list(-100, "2").await;
+    let sent = texts(&bot);
+    assert_eq!(sent.len(), 3);
+    assert!(sent[0].starts_with("*Soon 6*"));
+    assert_eq!(sent[2], "Showing 6–7 of 7 events.");
+
+    bot.handle_upcoming(-100, "365000").await;
+    bot.handle_upcoming(-100, "30 2").await;
+    let sent = texts(&bot)[3..].to_vec();
+    assert!(sent[0].starts_with("Usage: /upcoming [days] [page]"));
+    assert_eq!(sent.len(), 4);
+    assert_eq!(sent[3], "Showing 6–7 of 7 events.");
+
+    bot.handle_past_events(-100, "").await;
+    let sent = texts(&bot)[7..].to_vec();
+    assert_eq!(sent.len(), 6);
+    assert!(sent[0].starts_with("*Past 1*"));
+    assert_eq!(
+        sent[5],
+        "Showing 1–5 of 7 events. Use /pastevents 2 to see more."
+    );
+    bot.handle_past_events(-100, "3").await;
+    assert_eq!(texts(&bot).last().unwrap(), "There is no page 3.");
+
+    bot.handle_my_events_callback(
+        &callback_query(1, &format!("{MY_EVENTS_CREATED_PREFIX}1_created_1")),
+        &format!("{MY_EVENTS_CREATED_PREFIX}1_created_1"),
+    )
+    .await;
+    assert_eq!(
+        texts(&bot).last().unwrap(),
+        "Showing 1–5 of 7 events. Use /myevents created 2 to see more."
+    );
+}
+
+/// A button press on a message in the group `-100`.
+fn callback_query(from: u64, data: &str) -> CallbackQuery {
+    serde_json::from_value(json!({
+        "id": "1",
+        "from": user(from),
+        "chat_instance": "1",
+        "message": {
+            "message_id": 42,
+            "date": 0,
+            "chat": { "id": -100, "type": "supergroup" }
+        },
+        "data": data
+    }))
+    .unwrap()
+}
+
+#[tokio::test]
+async fn pressing_the_same_rsvp_again_removes_it() {
+    let mut bot = bot_with_admins().await;
+    let draft = EventDraft {
+        name: "Dinner".to_string(),
+        time: "2999-01-01 19:00".to_string(),
+        ..Default::default()
+    };
+    let event_id = db::create_event(&bot.pool, -100, 1, &draft).await.unwrap();
+    db::update_attendance(&bot.pool, event_id, 3, RsvpStatus::Accepted)
+        .await
+        .unwrap();
+
+    bot.handle_callback_query(callback_query(3, &format!("accept_{event_id}")))
+        .await
+        .unwrap();
+
+    assert!(db::get_attendees(&bot.pool, event_id)
+        .await
+        .unwrap()
+        .is_empty());
+    let answers = bot.api.recorded("answerCallbackQuery");
+    assert_eq!(answers.len(), 1);
+    assert_eq!(answers[0]["text"], "Removed");
+    let edits = bot.api.recorded("editMessageText");
+    assert_eq!(edits.len(), 1);
+    assert_eq!(edits[0]["chat_id"], -100);
+    assert_eq!(edits[0]["message_id"], 42);
+    assert!(!edits[0]["text"].as_str().unwrap().contains("Accepted"));
+}
+
+#[tokio::test]
+async fn accepting_warns_about_overlapping_events() {
+    let mut bot = bot_with_admins().await;
+    let mut event_ids = Vec::new();
+    for (chat_id, name, time) in [
+        (-100, "Dinner", "2999-01-01 19:00"),
+        (-200, "Concert", "2999-01-01 20:00"),
+        (-200, "Breakfast", "2999-01-02 09:00"),
+    ] {
+        let draft = EventDraft {
+            name: name.to_string(),
+            time: time.to_string(),
+            ..Default::default()
+        };
+        event_ids.push(
+            db::create_event(&bot.pool, chat_id, 1, &draft)
+                .await
+                .unwrap(),
+        );
+    }
+    for event_id in &event_ids[1..] {
+        db::update_attendance(&bot.pool, *event_id, 3, RsvpStatus::Accepted)
+            .await
+            .unwrap();
+    }
+
+    for data in ["accept", "accept", "maybe"] {
+        bot.handle_callback_query(callback_query(3, &format!("{data}_{}", event_ids[0])))
+            .await
+            .unwrap();
+    }
+
+    let warnings: Vec<_> = bot
+        .api
+        .recorded("sendMessage")
+        .into_iter()
+        .filter(|params| params["chat_id"] == 3)
+        .map(|params| params["text"].clone())
+        .collect();
+    assert_eq!(
+        warnings,
+        [json!(
+            "⚠️ This event overlaps with 'Concert' you've already accepted."
+        )]
+    );
+}
+
+#[tokio::test]
+async fn presses_for_missing_events_change_nothing() {
+    let mut bot = bot_with_admins().await;
+
+    for data in ["accept_999", "deleted_999", "confirm_delete_999"] {
+        bot.handle_callback_query(callback_query(3, data))
+            .await
+            .unwrap();
+    }
+
+    let answers = bot.api.recorded("answerCallbackQuery");
+    assert_eq!(answers.len(), 3);
+    assert!(answers
+        .iter()
+        .all(|answer| answer["text"] == "This event no longer exists."));
+    assert_eq!(bot.api.calls_to("sendMessage"), 0);
+    assert_eq!(bot.api.calls_to("editMessageText"), 0);
+}
+
+#[tokio::test]
+async fn only_creators_can_delete_from_buttons() {
+    let mut bot = bot_with_admins().await;
+    let draft = EventDraft {
+        name: "Dinner".to_string(),
+        time: "2999-01-01 19:00".to_string(),
+        ..Default::default()
+    };
+    let event_id = db::create_event(&bot.pool, -100, 1, &draft).await.unwrap();
+
+    for prefix in [DELETE_CALLBACK_PREFIX, CONFIRM_DELETE_CALLBACK_PREFIX] {
+        let result = bot
+            .handle_callback_query(callback_query(3, &format!("{prefix}{event_id}")))
+            .await;
+        assert!(matches!(result, Err(BotError::PermissionDenied(_))));
+    }
+
+    let answers = bot.api.recorded("answerCallbackQuery");
+    assert_eq!(answers.len(), 2);
+    assert!(answers
+        .iter()
+        .all(|answer| answer["text"] == "⛔ You are not authorized to do this."));
+    assert_eq!(bot.api.calls_to("editMessageReplyMarkup"), 0);
+    assert_eq!(bot.api.calls_to("deleteMessage"), 0);
+    assert!(db::get_event(&bot.pool, event_id).await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn reminders_reach_accepted_and_maybe_once_per_cooldown() {
+    let mut bot = bot_with_admins().await;
+    let draft = EventDraft {
+        name: "Dinner".to_string(),
+        location: "Pizzeria".to_string(),
+        time: "2999-01-01 19:00".to_string(),
+        ..Default::default()
+    };
+    let event_id = db::create_event(&bot.pool, -100, 1, &draft).await.unwrap();
+    for (user_id, status) in [
+        (3, RsvpStatus::Accepted),
+        (4, RsvpStatus::Maybe),
+        (5, RsvpStatus::Declined),
+    ] {
+        db::update_attendance(&bot.pool, event_id, user_id, status)
+            .await
+            .unwrap();
+    }
+    // A reminder that reaches nobody doesn't start the cooldown.
+    bot.api = MockClient::new().fail("sendMessage", "Forbidden: bot can't initiate conversation");
+    bot.handle_remind(1, 1, &event_id.to_string()).await;
+    assert_eq!(
+        bot.api.recorded("sendMessage")[2]["text"],
+        "No attendees could be reminded. They need to start a private chat with the bot first."
+    );
+
+    bot.api = MockClient::new().respond(
+        "sendMessage",
+        json!({ "message_id": 1, "date": 0, "chat": { "id": 3, "type": "private" } }),
+    );
+
+    bot.handle_remind(2, 2, &event_id.to_string()).await;
+    bot.handle_remind(1, 1, &event_id.to_string()).await;
+    bot.handle_remind(1, 1, &event_id.to_string()).await;
+
+    let texts: Vec<_> = bot
+        .api
+        .recorded("sendMessage")
+        .into_iter()
+        .map(|params| (params["chat_id"].as_i64().unwrap(), params["text"].clone()))
+        .collect();
+    let reminder = "📣 Reminder: «Dinner» is on 2999-01-01 19:00 (UTC) at Pizzeria. See you there!";
+    assert_eq!(
+        texts,
+        [
+            (
+                2,
+                json!("Only the event's creator can send reminders for it.")
+            ),
+            (3, json!(reminder)),
+            (4, json!(reminder)),
+            (1, json!("Reminder sent to 2 attendees.")),
+            (
+                1,
+                json!("A reminder for «Dinner» was already sent in the last 4 hours.")
+            ),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn attendees_check_in_on_the_day() {
+    let mut bot = bot_with_admins().await;
+    let draft = EventDraft {
+        name: "Dinner".to_string(),
+        time: (Utc::now().naive_utc() + chrono::Duration::hours(2))
+            .format(DATETIME_FORMAT)
+            .to_string(),
+        ..Default::default()
+    };
+    let event_id = db::create_event(&bot.pool, -100, 1, &draft).await.unwrap();
+    db::update_attendance(&bot.pool, event_id, 3, RsvpStatus::Accepted)
+        .await
+        .unwrap();
+    db::update_attendance(&bot.pool, event_id, 5, RsvpStatus::Declined)
+        .await
+        .unwrap();
+
+    for user_id in [5, 3] {
+        bot.handle_callback_query(callback_query(user_id, &format!("checkin_{event_id}")))
+            .await
+            .unwrap();
+    }
+
+    let answers = bot.api.recorded("answerCallbackQuery");
+    assert_eq!(
+        answers[0]["text"],
+        "Only people who accepted or might come can check in."
+    );
+    assert_eq!(answers[1]["text"], "✔️ Checked in");
+    assert_eq!(db::get_checked_in(&bot.pool, event_id).await.unwrap(), [3]);
+    let edits = bot.api.recorded("editMessageText");
+    assert_eq!(edits.len(), 1);
+    assert!(edits[0]["text"]
+        .as_str()
+        .unwrap()
+        .ends_with("\n*✔️ Checked in*\n• Unknown user\n"));
+
+    // Declining afterwards undoes the check-in.
+    db::update_attendance(&bot.pool, event_id, 3, RsvpStatus::Declined)
+        .await
+        .unwrap();
+    assert!(db::get_checked_in(&bot.pool, event_id)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn lists_are_paged() {
+    let mut bot = bot_with_admins().await;
+    for day in 1..=7 {
+        let draft = EventDraft {
+            name: format!("Dinner {day}"),
+            time: format!("2999-01-0{day} 19:00"),
+            ..Default::default()
+        };
+        db::create_event(&bot.pool, -100, 1, &draft).await.unwrap();
+    }
+    let buttons = |params: &serde_json::Value| {
+        params["reply_markup"]["inline_keyboard"][0]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|button| button["callback_data"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+
+    bot.handle_list(-100, "").await;
+    let sent = bot.api.recorded("sendMessage");
+    assert_eq!(sent.len(), 6);
+    assert!(sent[4]["text"].as_str().unwrap().starts_with("*Dinner 5*"));
+    assert_eq!(
+        sent[5]["text"],
+        "Showing 1–5 of 7 events. Use /list 2 to see more."
+    );
+    assert_eq!(buttons(&sent[5]), ["list_next_5_-100"]);
+
+    // The buttons only work in the chat they're for.
+    let mut query = callback_query(3, "list_next_5_-200");
+    bot.handle_callback_query(query).await.unwrap();
+    assert_eq!(bot.api.calls_to("sendMessage"), 6);
+
+    query = callback_query(3, "list_next_5_-100");
+    bot.handle_callback_query(query).await.unwrap();
+    let sent = bot.api.recorded("sendMessage");
+    assert_eq!(sent.len(), 9);
+    assert!(sent[6]["text"].as_str().unwrap().starts_with("*Dinner 6*"));
+    assert_eq!(sent[8]["text"], "Showing 6–7 of 7 events.");
+    assert_eq!(buttons(&sent[8]), ["list_next_0_-100"]);
+}
+
+#[tokio::test]
+async fn creators_get_edit_and_delete_buttons_in_my_events() {
+    let mut bot = bot_with_admins().await;
+    let draft = EventDraft {
+        name: "Dinner".to_string(),
+        time: "2999-01-01 19:00".to_string(),
+        ..Default::default()
+    };
+    let event_id = db::create_event(&bot.pool, -100, 1, &draft).await.unwrap();
+
+    bot.handle_callback_query(callback_query(1, "myevents_created_1"))
+        .await
+        .unwrap();
+    let sent = bot.api.recorded("sendMessage");
+    assert_eq!(sent.len(), 1);
+    assert_eq!(
+        sent[0]["reply_markup"]["inline_keyboard"][1],
+        json!([
+            { "text": "✏️ Edit", "callback_data": format!("edit_start_{event_id}") },
+            { "text": "🗑️ Delete", "callback_data": format!("deleted_{event_id}") },
+        ])
+    );
+
+    bot.handle_callback_query(callback_query(3, &format!("edit_start_{event_id}")))
+        .await
+        .unwrap();
+    assert!(bot.event_edit_contexts.is_empty());
+    bot.handle_callback_query(callback_query(1, &format!("edit_start_{event_id}")))
+        .await
+        .unwrap();
+    assert_eq!(bot.event_edit_contexts[&1].event_id, event_id);
+
+    let texts: Vec<_> = bot.api.recorded("sendMessage")[1..]
+        .iter()
+        .map(|params| params["text"].clone())
+        .collect();
+    assert_eq!(
+        texts,
+        [
+            json!("Only the event's creator can edit it."),
+            json!("Editing «Dinner». Which field do you want to change: title, description, location or time?"),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn trusted_creators_only_lets_admins_and_trusted_users_create() {
+    let mut bot = bot_with_admins().await;
+    let mut settings = GroupSettings::new(-100);
+    settings.trusted_creators_only = true;
+    group_settings::upsert_settings(&bot.pool, &settings)
+        .await
+        .unwrap();
+
+    bot.handle_create(&ChatType::Supergroup, -100, 3, 1).await;
+    assert!(bot.event_contexts.is_empty());
+    bot.handle_create(&ChatType::Supergroup, -100, 2, 1).await;
+    assert!(bot.event_contexts.contains_key(&2));
+
+    // Only administrators manage the list.
+    bot.handle_trusted_creator(&ChatType::Supergroup, -100, 3, "3", true)
+        .await;
+    assert!(!group_settings::is_trusted_creator(&bot.pool, -100, 3)
+        .await
+        .unwrap());
+    bot.handle_trusted_creator(&ChatType::Supergroup, -100, 1, "3", true)
+        .await;
+    bot.handle_create(&ChatType::Supergroup, -100, 3, 1).await;
+    assert!(bot.event_contexts.contains_key(&3));
+
+    bot.handle_trusted_creator(&ChatType::Supergroup, -100, 1, "3", false)
+        .await;
+    assert!(!group_settings::is_trusted_creator(&bot.pool, -100, 3)
+        .await
+        .unwrap());
+
+    let texts: Vec<_> = bot
+        .api
+        .recorded("sendMessage")
+        .into_iter()
+        .map(|params| params["text"].clone())
+        .filter(|text| text != "Please enter the Name of the event.")
+        .collect();
+    assert_eq!(
+        texts,
+        [
+            json!("Only group administrators and trusted organizers can create events."),
+            json!("Only group administrators can change who creates events."),
+            json!("User 3 can now create events."),
+            json!("User 3 can no longer create events."),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn admins_export_all_events_privately() {
+    let mut bot = bot_with_admins().await;
+    bot.api = bot.api.respond(
+        "sendDocument",
+        json!({ "message_id": 1, "date": 0, "chat": { "id": 1, "type": "private" } }),
+    );
+    for (name, time) in [
+        ("Dinner", "2000-01-01 19:00"),
+        ("Picnic", "2999-01-01 12:00"),
+    ] {
+        let draft = EventDraft {
+            name: name.to_string(),
+            time: time.to_string(),
+            ..Default::default()
+        };
+        let event_id = db::create_event(&bot.pool, -100, 1, &draft).await.unwrap();
+        db::update_attendance(&bot.pool, event_id, 3, RsvpStatus::Accepted)
+            .await
+            .unwrap();
+    }
+
+    bot.handle_export_all(&ChatType::Private, 1, 1).await;
+    bot.handle_export_all(&ChatType::Supergroup, -100, 3).await;
+    assert_eq!(bot.api.calls_to("sendDocument"), 0);
+
+    bot.handle_export_all(&ChatType::Supergroup, -100, 1).await;
+    let documents = bot.api.recorded("sendDocument");
+    assert_eq!(documents.len(), 1);
+    assert_eq!(documents[0]["chat_id"], 1);
+    let replies = bot.api.recorded("sendMessage");
+    assert_eq!(
+        replies.last().unwrap()["text"],
+        "I've sent you the events in a private chat."
+    );
+}
+
+#[tokio::test]
+async fn removal_from_a_group_cancels_its_events() {
+    let mut bot = bot_with_admins().await;
+    let mut event_ids = Vec::new();
+    for (name, time) in [
+        ("Dinner", "2000-01-01 19:00"),
+        ("Picnic", "2999-01-01 12:00"),
+    ] {
+        let draft = EventDraft {
+            name: name.to_string(),
+            time: time.to_string(),
+            ..Default::default()
+        };
+        event_ids.push(db::create_event(&bot.pool, -100, 1, &draft).await.unwrap());
+    }
+    let settings = GroupSettings {
+        max_events: 3,
+        ..GroupSettings::new(-100)
+    };
+    group_settings::upsert_settings(&bot.pool, &settings)
+        .await
+        .unwrap();
+
+    let membership = |old: serde_json::Value, new: serde_json::Value| -> ChatMemberUpdated {
+        serde_json::from_value(json!({
+            "chat": { "id": -100, "type": "supergroup", "title": "Hikers" },
+            "from": user(1),
+            "date": 0,
+            "old_chat_member": old,
+            "new_chat_member": new,
+        }))
+        .unwrap()
+    };
+    let member = json!({ "status": "member", "user": user(100) });
+    let kicked = json!({ "status": "kicked", "user": user(100), "until_date": 0 });
+
+    bot.handle_my_chat_member(membership(member.clone(), kicked.clone()))
+        .await;
+
+    let past = db::get_event(&bot.pool, event_ids[0])
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(past.cancelled_at, None);
+    let upcoming = db::get_event(&bot.pool, event_ids[1])
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(upcoming.cancelled_at.is_some());
+    assert_eq!(
+        upcoming.cancel_reason.as_deref(),
+        Some("Bot removed from group")
+    );
+    let notices = bot.api.recorded("sendMessage");
+    assert_eq!(notices.len(), 1);
+    assert_eq!(notices[0]["chat_id"], 1);
+    assert_eq!(
+        notices[0]["text"],
+        "🚫 I was removed from Hikers, so «Picnic» has been cancelled."
+    );
+    assert_eq!(
+        group_settings::get_settings(&bot.pool, -100).await.unwrap(),
+        GroupSettings::new(-100)
+    );
+
+    bot.handle_my_chat_member(membership(kicked, member)).await;
+    let stored: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM group_settings WHERE chat_id = -100")
+            .fetch_one(&bot.pool)
+            .await
+            .unwrap();
+    assert_eq!(stored, 1);
+}
+
+#[tokio::test]
+async fn accepting_asks_for_guests() {
+    let mut bot = bot_with_admins().await;
+    let draft = EventDraft {
+        name: "Dinner".to_string(),
+        time: "2999-01-01 19:00".to_string(),
+        guests_per_rsvp: 3,
+        ..Default::default()
+    };
+    let event_id = db::create_event(&bot.pool, -100, 1, &draft).await.unwrap();
+    bot.api = MockClient::new()
+        .respond(
+            "sendMessage",
+            json!({ "message_id": 1, "date": 0, "chat": { "id": 3, "type": "private" } }),
+        )
+        .respond(
+            "getChatMember",
+            json!({ "status": "member", "user": user(3) }),
+        );
+
+    bot.handle_callback_query(callback_query(3, &format!("accept_{event_id}")))
+        .await
+        .unwrap();
+    assert_eq!(bot.pending_guests.get(&3), Some(&(event_id, -100, 42)));
+
+    bot.handle_guests(3, "5".to_string()).await;
+    assert_eq!(bot.pending_guests.get(&3), Some(&(event_id, -100, 42)));
+    bot.handle_guests(3, "2".to_string()).await;
+    assert!(bot.pending_guests.is_empty());
+    assert_eq!(
+        db::get_guests(&bot.pool, event_id).await.unwrap(),
+        vec![(3, 2)]
+    );
+    let replies = bot.api.recorded("sendMessage");
+    assert_eq!(
+        replies.last().unwrap()["text"],
+        "Thanks, you're bringing 2 guests."
+    );
+
+    db::update_attendance(&bot.pool, event_id, 3, RsvpStatus::Maybe)
+        .await
+        .unwrap();
+    assert!(db::get_guests(&bot.pool, event_id)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn feedback_reaches_the_owner_three_times_a_day() {
+    let config = Config {
+        owner_id: Some(99),
+        ..test_config()
+    };
+    let api = MockClient::new().respond(
+        "sendMessage",
+        json!({ "message_id": 1, "date": 0, "chat": { "id": 99, "type": "private" } }),
+    );
+    let pool = db::init_db(&config).await.unwrap();
+    let mut bot = Bot::new(api, pool, &config).await.unwrap();
+    let from: User = serde_json::from_value(json!({
+        "id": 3,
+        "is_bot": false,
+        "first_name": "Alice",
+        "username": "alice"
+    }))
+    .unwrap();
+
+    for _ in 0..4 {
+        bot.handle_feedback(&ChatType::Private, 3, &from, "The buttons are slow")
+            .await;
+    }
+
+    let messages = bot.api.recorded("sendMessage");
+    let forwarded: Vec<_> = messages
+        .iter()
+        .filter(|params| params["chat_id"] == 99)
+        .collect();
+    assert_eq!(forwarded.len(), 3);
+    assert_eq!(
+        forwarded[0]["text"],
+        "Feedback from @alice (3): The buttons are slow"
+    );
+    assert_eq!(messages[1]["text"], "Thanks for your feedback!");
+    assert_eq!(
+        messages.last().unwrap()["text"],
+        "You can send at most 3 feedback messages a day. Please try again tomorrow."
+    );
+}
+
+#[test]
+fn durations_are_shown_coarsely() {
+    assert_eq!(format_duration(Duration::from_secs(42)), "42s");
+    assert_eq!(format_duration(Duration::from_secs(5 * 60 + 3)), "5m");
+    assert_eq!(format_duration(Duration::from_secs(3 * 3600 + 60)), "3h 1m");
+    assert_eq!(
+        format_duration(Duration::from_secs(2 * 86_400 + 4 * 60)),
+        "2d 0h 4m"
+    );
+}
+
+#[tokio::test]
+async fn status_is_only_for_the_owner() {
+    let mut bot = bot_with_admins().await;
+    bot.owner_id = Some(99);
+    let draft = EventDraft {
+        name: "Dinner".to_string(),
+        time: "2999-01-01 19:00".to_string(),
+        ..Default::default()
+    };
+    let event_id = db::create_event(&bot.pool, -100, 1, &draft).await.unwrap();
+    db::update_attendance(&bot.pool, event_id, 3, RsvpStatus::Accepted)
+        .await
+        .unwrap();
+    bot.event_contexts.insert(4, EventContext::new(None));
+
+    bot.handle_status(-100, 3).await;
+    bot.handle_status(-100, 99).await;
+
+    let messages = bot.api.recorded("sendMessage");
+    assert_eq!(
+        messages[0]["text"],
+        "Only the bot's owner can see its status."
+    );
+    assert_eq!(messages[1]["chat_id"], 99);
+    let status = messages[1]["text"].as_str().unwrap();
+    assert!(status.contains("\nEvents: 1\nRSVPs: 1\nDrafts in progress: 1\n"));
+
+    bot.pool.close().await;
+    bot.handle_status(-100, 99).await;
+    let status = bot.api.recorded("sendMessage")[2]["text"].clone();
+    assert!(status
+        .as_str()
+        .unwrap()
+        .contains("\nEvents: DB error\nRSVPs: DB error\n"));
+}
+
+#[tokio::test]
+async fn list_all_summarizes_every_chat() {
+    let mut bot = bot_with_admins().await;
+    bot.owner_id = Some(99);
+    for (chat_id, name, time) in [
+        (-200, "Chess", "2000-01-01 19:00"),
+        (-100, "Dinner", "2000-01-01 19:00"),
+        (-100, "Picnic", "2999-01-02 12:00"),
+        (-100, "BBQ", "2999-01-01 18:00"),
+    ] {
+        let draft = EventDraft {
+            name: name.to_string(),
+            time: time.to_string(),
+            ..Default::default()
+        };
+        db::create_event(&bot.pool, chat_id, 1, &draft)
+            .await
+            .unwrap();
+    }
+    db::stats::upsert_chat_name(&bot.pool, -100, "Hikers")
+        .await
+        .unwrap();
+
+    bot.handle_list_all(&ChatType::Private, 3, 3).await;
+    bot.handle_list_all(&ChatType::Private, 99, 99).await;
+
+    let messages = bot.api.recorded("sendMessage");
+    assert_eq!(
+        messages[0]["text"],
+        "Only the bot's owner can list all events."
+    );
+    assert_eq!(
+        messages[1]["text"],
+        "Chat -200: 1 event, none upcoming\nHikers: 3 events, next: BBQ on 2999-01-01"
+    );
+}
+
+#[tokio::test]
+async fn blocked_users_cannot_rsvp() {
+    let mut bot = bot_with_admins().await;
+    let draft = EventDraft {
+        name: "Dinner".to_string(),
+        time: "2999-01-01 19:00".to_string(),
+        max_capacity: Some(1),
+        ..Default::default()
+    };
+    let event_id = db::create_event(&bot.pool, -100, 1, &draft).await.unwrap();
+    for user_id in [3, 4] {
+        db::update_attendance(&bot.pool, event_id, user_id, RsvpStatus::Accepted)
+            .await
+            .unwrap();
+    }
+
+    bot.handle_block(&ChatType::Private, 1, 1, "3", true).await;
+    assert_eq!(
+        db::get_attendees(&bot.pool, event_id).await.unwrap(),
+        vec![(4, "accepted".to_string())]
+    );
+    let replies = bot.api.recorded("sendMessage");
+    assert_eq!(replies[0]["chat_id"], 4);
+    assert_eq!(
+        replies.last().unwrap()["text"],
+        "User 3 can no longer RSVP to your events. Their RSVP to 1 of your upcoming events was removed."
+    );
+
+    bot.handle_callback_query(callback_query(3, &format!("maybe_{event_id}")))
+        .await
+        .unwrap();
+    assert_eq!(
+        bot.api.recorded("answerCallbackQuery").last().unwrap()["text"],
+        "You are not permitted to RSVP for this event."
+    );
+    assert_eq!(
+        db::get_attendees(&bot.pool, event_id).await.unwrap().len(),
+        1
+    );
+
+    bot.handle_block(&ChatType::Private, 1, 1, "3", false).await;
+    bot.handle_callback_query(callback_query(3, &format!("maybe_{event_id}")))
+        .await
+        .unwrap();
+    assert_eq!(
+        db::get_attendees(&bot.pool, event_id).await.unwrap().len(),
+        2
+    );
+}
+
+#[tokio::test]
+async fn edits_only_take_private_messages() {
+    let mut bot = bot_with_admins().await;
+    let draft = EventDraft {
+        name: "Dinner".to_string(),
+        time: "2999-01-01 19:00".to_string(),
+        ..Default::default()
+    };
+    let event_id = db::create_event(&bot.pool, -100, 1, &draft).await.unwrap();
+    bot.event_edit_contexts
+        .insert(1, EventEditContext::new(event_id));
+
+    for (chat, text) in [
+        (json!({ "id": -100, "type": "supergroup" }), "title"),
+        (json!({ "id": 1, "type": "private" }), "title"),
+        (
+            json!({ "id": -100, "type": "supergroup" }),
+            "Lunch in the group",
+        ),
+        (json!({ "id": 1, "type": "private" }), "Lunch"),
+    ] {
+        let message = json!({
+            "message_id": 1,
+            "date": 0,
+            "chat": chat,
+            "from": user(1),
+            "text": text,
+        });
+        bot.handle_message(serde_json::from_value(message).unwrap())
+            .await;
+    }
+
+    let event = db::get_event(&bot.pool, event_id).await.unwrap().unwrap();
+    assert_eq!(event.name, "Lunch");
+    assert!(bot.event_edit_contexts.is_empty());
+}
+
+#[tokio::test]
+async fn my_events_can_be_sorted_by_creation() {
+    let mut bot = bot_with_admins().await;
+    for (name, time) in [
+        ("Later", "2999-09-01 19:00"),
+        ("Sooner", "2999-08-01 19:00"),
+    ] {
+        let draft = EventDraft {
+            name: name.to_string(),
+            time: time.to_string(),
+            ..Default::default()
+        };
+        db::create_event(&bot.pool, -100, 1, &draft).await.unwrap();
+    }
+
+    let mut orders = Vec::new();
+    for args in ["", "created"] {
+        bot.handle_my_events(&ChatType::Private, 1, 1, args).await;
+        let menu = bot.api.recorded("sendMessage").pop().unwrap();
+        let data = menu["reply_markup"]["inline_keyboard"][0][0]["callback_data"]
+            .as_str()
+            .unwrap()
+            .to_string();
+
+        let sent = bot.api.calls_to("sendMessage");
+        bot.handle_callback_query(
+            serde_json::from_value(json!({
+                "id": "1",
+                "from": user(1),
+                "chat_instance": "1",
+                "data": data
+            }))
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+        let is_later: Vec<_> = bot.api.recorded("sendMessage")[sent..]
+            .iter()
+            .map(|message| message["text"].as_str().unwrap().contains("Later"))
+            .collect();
+        orders.push(is_later);
+    }
+
+    assert_eq!(orders, [[false, true], [true, false]]);
+
+    bot.handle_my_events(&ChatType::Private, 1, 1, "soonest")
+        .await;
+    assert_eq!(
+        bot.api.recorded("sendMessage").pop().unwrap()["text"],
+        "Usage: /myevents [date|created] [page]"
+    );
+}
+
+#[tokio::test]
+async fn editing_the_time_checks_it_and_resets_reminders() {
+    let mut bot = bot_with_admins().await;
+    let draft = EventDraft {
+        name: "Dinner".to_string(),
+        time: "2999-01-01 19:00".to_string(),
+        rsvp_deadline: Some("2999-01-01 12:00".to_string()),
+        ..Default::default()
+    };
+    let event_id = db::create_event(&bot.pool, -100, 1, &draft).await.unwrap();
+    let other = EventDraft {
+        name: "DINNER".to_string(),
+        time: "2999-02-01 19:00".to_string(),
+        ..Default::default()
+    };
+    db::create_event(&bot.pool, -100, 1, &other).await.unwrap();
+    db::mark_reminders_sent(&bot.pool, event_id).await.unwrap();
+    db::mark_summary_sent(&bot.pool, event_id).await.unwrap();
+
+    let mut context = EventEditContext::new(event_id);
+    context.state = EventEditState::Editing(EventField::Time);
+    bot.event_edit_contexts.insert(1, context);
+    for time in ["2998-12-31 19:00", "2999-02-01 19:00", "2999-03-01 19:00"] {
+        bot.handle_event_edit(1, time.to_string()).await;
+    }
+
+    let replies: Vec<_> = bot
+        .api
+        .recorded("sendMessage")
+        .into_iter()
+        .map(|message| message["text"].as_str().unwrap().to_string())
+        .collect();
+    assert!(replies[0].starts_with("RSVPs close on"));
+    assert!(replies[1].starts_with("An event with this title and time already exists"));
+    assert_eq!(replies[2], "The Event has been updated.");
+
+    let flags: (bool, bool) =
+        sqlx::query_as("SELECT reminders_sent, summary_sent FROM events WHERE id = ?")
+            .bind(event_id)
+            .fetch_one(&bot.pool)
+            .await
+            .unwrap();
+    assert_eq!(flags, (false, false));
+    let event = db::get_event(&bot.pool, event_id).await.unwrap().unwrap();
+    assert_eq!(event.time, "2999-03-01 19:00");
+}
+
+#[tokio::test]
+async fn deleted_event_messages_are_posted_again() {
+    let mut bot = bot_with_admins().await;
+    let draft = EventDraft {
+        name: "Dinner".to_string(),
+        time: "2999-01-01 19:00".to_string(),
+        ..Default::default()
+    };
+    let event_id = db::create_event(&bot.pool, -100, 1, &draft).await.unwrap();
+    db::set_event_message_id(&bot.pool, event_id, Some(42))
+        .await
+        .unwrap();
+
+    // An edit that fails for another reason leaves the message alone.
+    bot.api = bot
+        .api
+        .fail("editMessageText", "Bad Request: message is not modified")
+        .fail(
+            "editMessageReplyMarkup",
+            "Bad Request: message is not modified",
+        );
+    assert!(bot.reload_event_message(event_id).await.is_err());
+    let event = db::get_event(&bot.pool, event_id).await.unwrap().unwrap();
+    assert_eq!(event.message_id, Some(42));
+
+    bot.api = bot
+        .api
+        .fail("editMessageText", "Bad Request: message to edit not found")
+        .fail(
+            "editMessageReplyMarkup",
+            "Bad Request: message to edit not found",
+        )
+        .respond(
+            "sendMessage",
+            json!({ "message_id": 77, "date": 0, "chat": { "id": -100, "type": "supergroup" } }),
+        );
+    bot.reload_event_message(event_id).await.unwrap();
+
+    let event = db::get_event(&bot.pool, event_id).await.unwrap().unwrap();
+    assert_eq!(event.message_id, Some(77));
+    assert_eq!(bot.api.calls_to("editMessageReplyMarkup"), 2);
+    assert_eq!(bot.api.recorded("sendMessage")[0]["chat_id"], -100);
+}
+
+#[tokio::test]
+async fn unchanged_event_messages_are_not_edited() {
+    let mut bot = bot_with_admins().await;
+    let draft = EventDraft {
+        name: "Dinner".to_string(),
+        time: "2999-01-01 19:00".to_string(),
+        ..Default::default()
+    };
+    let event_id = db::create_event(&bot.pool, -100, 1, &draft).await.unwrap();
+    db::set_event_message_id(&bot.pool, event_id, Some(42))
+        .await
+        .unwrap();
+
+    bot.reload_event_message(event_id).await.unwrap();
+    bot.reload_event_message(event_id).await.unwrap();
+    assert_eq!(bot.api.calls_to("editMessageText"), 1);
+
+    db::update_attendance(&bot.pool, event_id, 3, RsvpStatus::Accepted)
+        .await
+        .unwrap();
+    bot.reload_event_message(event_id).await.unwrap();
+    assert_eq!(bot.api.calls_to("editMessageText"), 2);
+
+    // Edits other than RSVPs always go through.
+    db::update_event_field(&bot.pool, event_id, EventField::Title, "Lunch")
+        .await
+        .unwrap();
+    bot.reload_event_message(event_id).await.unwrap();
+    assert_eq!(bot.api.calls_to("editMessageText"), 3);
+}
+
+#[tokio::test]
+async fn events_can_reply_to_the_message_creating_them() {
+    let mut bot = bot_with_admins().await;
+    bot.reply_to_create = true;
+
+    for (chat_type, chat_id, name) in [
+        (ChatType::Supergroup, -100, "Dinner"),
+        (ChatType::Private, 1, "Lunch"),
+    ] {
+        bot.handle_create(&chat_type, chat_id, 1, 7).await;
+        let draft = &mut bot.event_contexts.get_mut(&1).unwrap().draft;
+        draft.name = name.to_string();
+        draft.time = "2999-01-01 19:00".to_string();
+        bot.save_draft(chat_id, 1).await;
+    }
+    // A draft begun in the group but finished elsewhere isn't a reply.
+    bot.handle_create(&ChatType::Supergroup, -100, 1, 8).await;
+    let context = bot.event_contexts.get_mut(&1).unwrap();
+    context.draft.name = "Breakfast".to_string();
+    context.draft.time = "2999-01-01 08:00".to_string();
+    bot.save_draft(1, 1).await;
+
+    let posts: Vec<_> = bot
+        .api
+        .recorded("sendMessage")
+        .into_iter()
+        .filter(|message| message["reply_markup"]["inline_keyboard"].is_array())
+        .map(|message| message["reply_parameters"]["message_id"].clone())
+        .collect();
+    assert_eq!(posts, [json!(7), json!(7), json!(null)]);
+}
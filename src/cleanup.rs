@@ -0,0 +1,126 @@
+use crate::db;
+use chrono::{NaiveDateTime, Utc};
+use sqlx::SqlitePool;
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::{error, info};
+
+/// How often past events are cleaned up.
+pub const CLEANUP_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Periodically deletes events, with their RSVPs, that took place more than
+/// a retention period ago.
+pub struct Cleanup {
+    pool: SqlitePool,
+    retention_days: u32,
+    dry_run: bool,
+    pool_updates: Option<watch::Receiver<SqlitePool>>,
+}
+
+impl Cleanup {
+    pub fn new(pool: SqlitePool, retention_days: u32) -> Self {
+        Self {
+            pool,
+            retention_days,
+            dry_run: false,
+            pool_updates: None,
+        }
+    }
+
+    /// Only logs the events that would be deleted, if `dry_run` is set.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Switches to the pools received on `pools` when the database is reopened.
+    pub fn with_pool_updates(mut self, pools: watch::Receiver<SqlitePool>) -> Self {
+        self.pool_updates = Some(pools);
+        self
+    }
+
+    /// Cleans up every `CLEANUP_INTERVAL`, starting right away, forever.
+    /// Failures are logged and retried on the next tick.
+    pub async fn run(mut self) {
+        let mut interval = tokio::time::interval(CLEANUP_INTERVAL);
+
+        loop {
+            interval.tick().await;
+            if let Some(pools) = &mut self.pool_updates {
+                if pools.has_changed().unwrap_or(false) {
+                    self.pool = pools.borrow_and_update().clone();
+                }
+            }
+
+            if let Err(err) = self.clean_up(Utc::now().naive_utc()).await {
+                error!(%err, "failed to clean up past events");
+            }
+        }
+    }
+
+    /// Deletes the events that started more than `retention_days` before
+    /// `now` (in UTC), or in a dry run just lists them. Returns how many
+    /// there were.
+    pub async fn clean_up(&self, now: NaiveDateTime) -> Result<u64, sqlx::Error> {
+        let cutoff = now - chrono::Duration::days(self.retention_days.into());
+        let days = self.retention_days;
+
+        if self.dry_run {
+            let event_ids = db::get_event_ids_before(&self.pool, cutoff, None).await?;
+            info!(
+                ?event_ids,
+                "Dry run: would clean up {} events older than {days} days.",
+                event_ids.len()
+            );
+            return Ok(event_ids.len() as u64);
+        }
+
+        let deleted = db::delete_events_before(&self.pool, cutoff, None).await?;
+        info!("Cleaned up {deleted} events older than {days} days.");
+        Ok(deleted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::event::{EventDraft, RsvpStatus};
+    use crate::util::parse_datetime_string;
+
+    #[tokio::test]
+    async fn removes_events_past_the_retention_period() {
+        let config = Config {
+            database_url: db::TEST_DB_URL.to_string(),
+            ..Default::default()
+        };
+        let pool = db::init_db(&config).await.unwrap();
+        let mut event_ids = Vec::new();
+        for (name, time) in [("Old", "2025-01-01 19:00"), ("Recent", "2025-03-20 19:00")] {
+            let draft = EventDraft {
+                name: name.to_string(),
+                time: time.to_string(),
+                ..Default::default()
+            };
+            let event_id = db::create_event(&pool, 1, 1, &draft).await.unwrap();
+            db::update_attendance(&pool, event_id, 2, RsvpStatus::Accepted)
+                .await
+                .unwrap();
+            event_ids.push(event_id);
+        }
+        let now = parse_datetime_string("2025-04-15 12:00").unwrap().0;
+
+        let dry_run = Cleanup::new(pool.clone(), 90).with_dry_run(true);
+        assert_eq!(dry_run.clean_up(now).await.unwrap(), 1);
+        assert!(db::get_event(&pool, event_ids[0]).await.unwrap().is_some());
+
+        let cleanup = Cleanup::new(pool.clone(), 90);
+        assert_eq!(cleanup.clean_up(now).await.unwrap(), 1);
+        assert!(db::get_event(&pool, event_ids[0]).await.unwrap().is_none());
+        assert!(db::get_attendees(&pool, event_ids[0])
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(db::get_event(&pool, event_ids[1]).await.unwrap().is_some());
+    }
+}
@@ -0,0 +1,220 @@
+use crate::bot::{parse_creation_roles, CreationRole};
+use crate::db;
+use crate::error::BotError;
+use crate::event::ParseModeStrategy;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// The request timeout of frankenstein's HTTP client, which long polls have
+/// to stay below.
+const MAX_POLL_TIMEOUT_SECS: u64 = 500;
+
+/// The bot's settings, read from the environment once at startup.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// `TELEGRAM_BOT_TOKEN`, the only required variable.
+    pub token: String,
+    /// `DATABASE_URL`, `sqlite://events_bot.db` by default. With
+    /// `TELEVENT_TEST_MODE=1` a throwaway in-memory database is used instead.
+    pub database_url: String,
+    /// `DATABASE_MAX_CONNECTIONS`, 5 by default.
+    pub database_max_connections: u32,
+    /// `DATABASE_CONNECT_TIMEOUT_SECS`, 30 seconds by default.
+    pub database_connect_timeout: Duration,
+    /// `RUST_LOG`, `televent=info` by default.
+    pub log_level: String,
+    /// `SQLX_LOG_LEVEL`, see `init_logging`.
+    pub sqlx_log_level: Option<String>,
+    /// `CREATION_ALLOWED_ROLES`, or `admin` if `REQUIRE_ADMIN_CREATE=true`,
+    /// and `all` otherwise.
+    pub creation_roles: Vec<CreationRole>,
+    /// `REMINDER_INTERVAL_MINS`, 15 minutes by default.
+    pub reminder_interval: Duration,
+    /// `DRAFT_TIMEOUT_SECS`, 30 minutes by default.
+    pub draft_timeout: Duration,
+    /// `EVENT_RETENTION_DAYS`, 90 by default. Events are deleted this long
+    /// after they took place.
+    pub event_retention_days: u32,
+    /// `DRY_RUN=true` only logs the events that would be cleaned up.
+    pub cleanup_dry_run: bool,
+    /// `HEALTH_CHECK_INTERVAL_SECS`, 60 seconds by default.
+    pub health_check_interval: Duration,
+    /// `POLL_TIMEOUT_SECS`, 30 seconds by default. How long each poll for
+    /// updates waits for one to arrive. Longer polls mean fewer requests to
+    /// Telegram, but the bot also only stops, expires drafts and handles
+    /// injected test updates between polls. 0 polls without waiting.
+    pub poll_timeout: Duration,
+    /// `POLL_INTERVAL_MS`, 0 by default. A pause between polls, which
+    /// lowers the load on Telegram and the host when polls return right
+    /// away, at the cost of updates taking that much longer to be handled.
+    pub poll_interval: Duration,
+    /// `TELEGRAM_WEBHOOK_URL`. Set to receive updates through a webhook
+    /// instead of polling.
+    pub webhook_url: Option<String>,
+    /// `WEBHOOK_PORT`, 8443 by default.
+    pub webhook_port: u16,
+    /// `TELEGRAM_PARSE_MODE`, one of `markdownv2` (the default), `html` or
+    /// `plain`, for how event messages are marked up.
+    pub parse_mode: ParseModeStrategy,
+    /// `CLEAR_COMMANDS=true` removes the registered command menu before
+    /// registering it again.
+    pub clear_commands: bool,
+    /// `REPLY_TO_CREATE=1` posts events as replies to the `/start` message
+    /// they were created with, when that's in the chat they're posted to.
+    pub reply_to_create: bool,
+    /// `TELEVENT_TEST_MODE=1`, for trying the bot out locally. The command
+    /// menu isn't registered, so the one of the production bot is kept.
+    pub test_mode: bool,
+    /// `TELEVENT_TEST_UPDATES`, in test mode a JSON file of updates to inject.
+    pub test_updates: Option<PathBuf>,
+    /// `BOT_OWNER_ID`, the Telegram user ID `/feedback` is sent to and the
+    /// only one that can use `/status` and `/listall`. Without it, all three
+    /// are turned off.
+    pub owner_id: Option<i64>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            token: String::new(),
+            database_url: db::DB_URL.to_string(),
+            database_max_connections: 5,
+            database_connect_timeout: Duration::from_secs(30),
+            log_level: "televent=info".to_string(),
+            sqlx_log_level: None,
+            creation_roles: vec![CreationRole::All],
+            reminder_interval: Duration::from_secs(15 * 60),
+            draft_timeout: Duration::from_secs(30 * 60),
+            event_retention_days: 90,
+            cleanup_dry_run: false,
+            health_check_interval: Duration::from_secs(60),
+            poll_timeout: Duration::from_secs(30),
+            poll_interval: Duration::ZERO,
+            webhook_url: None,
+            webhook_port: 8443,
+            parse_mode: ParseModeStrategy::MarkdownV2,
+            clear_commands: false,
+            reply_to_create: false,
+            test_mode: false,
+            test_updates: None,
+            owner_id: None,
+        }
+    }
+}
+
+impl Config {
+    /// Reads the configuration from the environment. Every missing or
+    /// invalid variable is listed in the returned error, not just the first.
+    pub fn from_env() -> Result<Self, BotError> {
+        let mut config = Config::default();
+        let mut errors = Vec::new();
+
+        match std::env::var("TELEGRAM_BOT_TOKEN") {
+            Ok(token) => config.token = token,
+            Err(_) => errors.push("TELEGRAM_BOT_TOKEN is not set".to_string()),
+        }
+
+        config.test_mode = std::env::var("TELEVENT_TEST_MODE").is_ok_and(|value| value == "1");
+        if config.test_mode {
+            config.database_url = db::TEST_DB_URL.to_string();
+            config.test_updates = std::env::var("TELEVENT_TEST_UPDATES")
+                .ok()
+                .map(PathBuf::from);
+        } else if let Ok(url) = std::env::var("DATABASE_URL") {
+            config.database_url = url;
+        }
+
+        if let Some(max_connections) = parse_var("DATABASE_MAX_CONNECTIONS", &mut errors) {
+            config.database_max_connections = max_connections;
+        }
+        if let Some(secs) = parse_var("DATABASE_CONNECT_TIMEOUT_SECS", &mut errors) {
+            config.database_connect_timeout = Duration::from_secs(secs);
+        }
+
+        if let Ok(log_level) = std::env::var("RUST_LOG") {
+            config.log_level = log_level;
+        }
+        config.sqlx_log_level = std::env::var("SQLX_LOG_LEVEL").ok();
+
+        // REQUIRE_ADMIN_CREATE=true is a shorthand for CREATION_ALLOWED_ROLES=admin.
+        let require_admin_create = parse_var("REQUIRE_ADMIN_CREATE", &mut errors).unwrap_or(false);
+        let default_roles = if require_admin_create { "admin" } else { "all" };
+        match parse_creation_roles(
+            &std::env::var("CREATION_ALLOWED_ROLES").unwrap_or_else(|_| default_roles.to_string()),
+        ) {
+            Ok(roles) => config.creation_roles = roles,
+            Err(err) => errors.push(format!("CREATION_ALLOWED_ROLES: {err}")),
+        }
+
+        match parse_var::<u64>("REMINDER_INTERVAL_MINS", &mut errors) {
+            Some(0) => errors.push("REMINDER_INTERVAL_MINS must be at least 1".to_string()),
+            Some(mins) => config.reminder_interval = Duration::from_secs(mins * 60),
+            None => {}
+        }
+        if let Some(secs) = parse_var("DRAFT_TIMEOUT_SECS", &mut errors) {
+            config.draft_timeout = Duration::from_secs(secs);
+        }
+        match parse_var::<u32>("EVENT_RETENTION_DAYS", &mut errors) {
+            Some(0) => errors.push("EVENT_RETENTION_DAYS must be at least 1".to_string()),
+            Some(days) => config.event_retention_days = days,
+            None => {}
+        }
+        if let Some(dry_run) = parse_var("DRY_RUN", &mut errors) {
+            config.cleanup_dry_run = dry_run;
+        }
+        match parse_var::<u64>("HEALTH_CHECK_INTERVAL_SECS", &mut errors) {
+            Some(0) => errors.push("HEALTH_CHECK_INTERVAL_SECS must be at least 1".to_string()),
+            Some(secs) => config.health_check_interval = Duration::from_secs(secs),
+            None => {}
+        }
+
+        // Polls have to finish before the HTTP client gives up on them.
+        match parse_var::<u64>("POLL_TIMEOUT_SECS", &mut errors) {
+            Some(secs) if secs >= MAX_POLL_TIMEOUT_SECS => errors.push(format!(
+                "POLL_TIMEOUT_SECS must be below {MAX_POLL_TIMEOUT_SECS}"
+            )),
+            Some(secs) => config.poll_timeout = Duration::from_secs(secs),
+            None => {}
+        }
+        if let Some(millis) = parse_var("POLL_INTERVAL_MS", &mut errors) {
+            config.poll_interval = Duration::from_millis(millis);
+        }
+
+        config.webhook_url = std::env::var("TELEGRAM_WEBHOOK_URL").ok();
+        if let Some(port) = parse_var("WEBHOOK_PORT", &mut errors) {
+            config.webhook_port = port;
+        }
+
+        if let Some(parse_mode) = parse_var("TELEGRAM_PARSE_MODE", &mut errors) {
+            config.parse_mode = parse_mode;
+        }
+        if let Some(clear_commands) = parse_var("CLEAR_COMMANDS", &mut errors) {
+            config.clear_commands = clear_commands;
+        }
+        config.reply_to_create = std::env::var("REPLY_TO_CREATE").is_ok_and(|value| value == "1");
+        config.owner_id = parse_var("BOT_OWNER_ID", &mut errors);
+
+        if errors.is_empty() {
+            Ok(config)
+        } else {
+            Err(BotError::Config(errors.join("; ")))
+        }
+    }
+}
+
+/// Reads and parses the variable `name`. Returns `None` if it isn't set, or
+/// if it is invalid, in which case the problem is added to `errors`.
+fn parse_var<T: FromStr>(name: &str, errors: &mut Vec<String>) -> Option<T>
+where
+    T::Err: std::fmt::Display,
+{
+    let value = std::env::var(name).ok()?;
+    match value.parse() {
+        Ok(parsed) => Some(parsed),
+        Err(err) => {
+            errors.push(format!("{name}={value:?} is invalid: {err}"));
+            None
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use crate::db;
+use crate::error::BotError;
+use crate::event::{Event, EventSummary};
+use crate::util::DATETIME_FORMAT;
+use chrono::NaiveDateTime;
+use sqlx::SqlitePool;
+
+/// How long before and after it starts an event is taken to keep its
+/// attendees busy, unless told otherwise.
+pub const DEFAULT_CONFLICT_WINDOW: chrono::Duration = chrono::Duration::hours(2);
+
+/// Finds the events, in any chat, that a user accepted and that take place
+/// around the same time as another one. Each event is taken to last from
+/// `window` before its start until `window` after it.
+#[derive(Debug, Clone, Copy)]
+pub struct EventConflictChecker {
+    window: chrono::Duration,
+}
+
+impl Default for EventConflictChecker {
+    fn default() -> Self {
+        Self::new(DEFAULT_CONFLICT_WINDOW)
+    }
+}
+
+impl EventConflictChecker {
+    pub fn new(window: chrono::Duration) -> Self {
+        Self { window }
+    }
+
+    /// Returns the other events `user_id` accepted that overlap
+    /// `new_event`, soonest first. Events without a valid time overlap
+    /// nothing.
+    pub async fn check(
+        &self,
+        user_id: i64,
+        new_event: &Event,
+        pool: &SqlitePool,
+    ) -> Result<Vec<EventSummary>, BotError> {
+        let Ok(time) = NaiveDateTime::parse_from_str(&new_event.time, DATETIME_FORMAT) else {
+            return Ok(Vec::new());
+        };
+
+        Ok(db::get_accepted_event_summaries(pool, user_id)
+            .await?
+            .into_iter()
+            .filter(|other| other.id != new_event.id && self.overlaps(time, &other.time))
+            .collect())
+    }
+
+    /// Whether the windows around `time` and `other` overlap. Windows that
+    /// only touch don't.
+    fn overlaps(&self, time: NaiveDateTime, other: &str) -> bool {
+        NaiveDateTime::parse_from_str(other, DATETIME_FORMAT)
+            .is_ok_and(|other| (other - time).abs() < self.window * 2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::db::CancelMode;
+    use crate::event::{EventDraft, RsvpStatus};
+
+    #[tokio::test]
+    async fn finds_accepted_events_of_every_chat_around_the_same_time() {
+        let config = Config {
+            database_url: db::TEST_DB_URL.to_string(),
+            ..Default::default()
+        };
+        let pool = db::init_db(&config).await.unwrap();
+        let mut event_ids = Vec::new();
+        for (chat_id, name, time, status) in [
+            (-100, "Dinner", "2999-01-01 19:00", RsvpStatus::Accepted),
+            (-200, "Late show", "2999-01-01 22:30", RsvpStatus::Accepted),
+            (
+                -300,
+                "Midnight run",
+                "2999-01-01 23:00",
+                RsvpStatus::Accepted,
+            ),
+            (-200, "Drinks", "2999-01-01 18:00", RsvpStatus::Maybe),
+            (
+                -300,
+                "Cancelled brunch",
+                "2999-01-01 17:00",
+                RsvpStatus::Accepted,
+            ),
+            (-100, "Board games", "", RsvpStatus::Accepted),
+        ] {
+            let draft = EventDraft {
+                name: name.to_string(),
+                time: time.to_string(),
+                ..Default::default()
+            };
+            let event_id = db::create_event(&pool, chat_id, 1, &draft).await.unwrap();
+            db::update_attendance(&pool, event_id, 3, status)
+                .await
+                .unwrap();
+            event_ids.push(event_id);
+        }
+        db::delete_event(&pool, event_ids[4], CancelMode::Soft)
+            .await
+            .unwrap();
+
+        let checker = EventConflictChecker::default();
+        let dinner = db::get_event(&pool, event_ids[0]).await.unwrap().unwrap();
+        let conflicts = checker.check(3, &dinner, &pool).await.unwrap();
+        assert_eq!(
+            conflicts,
+            [EventSummary {
+                id: event_ids[1],
+                name: "Late show".to_string(),
+                time: "2999-01-01 22:30".to_string(),
+            }]
+        );
+        assert!(checker.check(4, &dinner, &pool).await.unwrap().is_empty());
+
+        let board_games = db::get_event(&pool, event_ids[5]).await.unwrap().unwrap();
+        assert!(checker
+            .check(3, &board_games, &pool)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
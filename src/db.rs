@@ -0,0 +1,1226 @@
+pub mod drafts;
+pub mod group_settings;
+pub mod stats;
+
+use crate::config::Config;
+use crate::error::BotError;
+use crate::event::{parse_tags, Event, EventDraft, EventField, EventSummary, RsvpStatus};
+use crate::util::DATETIME_FORMAT;
+use chrono::{NaiveDateTime, Utc};
+use group_settings::GroupSettings;
+use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::{SqliteConnection, SqlitePool};
+use std::collections::BTreeSet;
+use std::str::FromStr;
+use std::time::Duration;
+use tracing::{instrument, warn};
+
+/// Used unless `DATABASE_URL` is set.
+pub const DB_URL: &str = "sqlite://events_bot.db";
+
+/// Used instead of `DB_URL` when running with `TELEVENT_TEST_MODE=1`.
+pub const TEST_DB_URL: &str = "sqlite::memory:";
+
+/// How many times `init_db` tries to connect before giving up.
+const MAX_DB_RETRIES: u32 = 5;
+
+/// How long `init_db` waits after its first failed attempt; the wait doubles
+/// after every further one.
+const INITIAL_DB_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Opens and migrates the database configured in `config`.
+pub async fn init_db(config: &Config) -> Result<SqlitePool, BotError> {
+    let url = config.database_url.as_str();
+    // Foreign keys are a per-connection setting, so every connection gets it.
+    let options = sqlx::sqlite::SqliteConnectOptions::from_str(url)?
+        .create_if_missing(true)
+        .foreign_keys(true);
+    let pool_options = if url == TEST_DB_URL {
+        // Every connection to an in-memory database gets its own empty copy,
+        // so the pool must hold on to exactly one connection.
+        SqlitePoolOptions::new()
+            .max_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+    } else {
+        SqlitePoolOptions::new()
+            .max_connections(config.database_max_connections)
+            .acquire_timeout(config.database_connect_timeout)
+    };
+
+    let mut delay = INITIAL_DB_RETRY_DELAY;
+    let mut attempt = 1;
+    let pool = loop {
+        match pool_options.clone().connect_with(options.clone()).await {
+            Ok(pool) => break pool,
+            Err(err) if attempt < MAX_DB_RETRIES => {
+                warn!(attempt, %err, "failed to connect to the database, retrying in {delay:?}");
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+                attempt += 1;
+            }
+            Err(err) => return Err(err.into()),
+        }
+    };
+
+    // WAL lets readers carry on while an event is being written. The mode is
+    // stored in the database file, so setting it once is enough.
+    sqlx::query("PRAGMA journal_mode=WAL")
+        .execute(&pool)
+        .await?;
+    sqlx::query("PRAGMA foreign_keys=ON").execute(&pool).await?;
+
+    create_schema(&pool).await?;
+
+    Ok(pool)
+}
+
+/// Brings the schema on `pool` up to date by running any pending migrations
+/// from `migrations/`.
+pub async fn create_schema(pool: &SqlitePool) -> Result<(), sqlx::migrate::MigrateError> {
+    sqlx::migrate!().run(pool).await
+}
+
+// This is a supremely ugly function. Need to look into sqlx macros for this.
+/// Inserts an event and returns its ID. Fails with `BotError::DuplicateEvent`
+/// if the chat already has an event, not cancelled, with the same title
+/// (ignoring case) and time, and with `BotError::LimitExceeded` if a group
+/// already has its `max_events` upcoming events.
+#[instrument(skip(pool))]
+pub async fn create_event(
+    pool: &SqlitePool,
+    chat_id: i64,
+    user_id: i64,
+    draft: &EventDraft,
+) -> Result<i64, BotError> {
+    let mut tx = pool.begin().await?;
+
+    if has_duplicate(&mut tx, chat_id, &draft.name, &draft.time, None).await? {
+        return Err(BotError::DuplicateEvent);
+    }
+
+    // Private chats have the user's ID; only groups have event limits.
+    if chat_id != user_id {
+        let max_events: i64 =
+            sqlx::query_scalar("SELECT max_events FROM group_settings WHERE chat_id = ?")
+                .bind(chat_id)
+                .fetch_optional(&mut *tx)
+                .await?
+                .unwrap_or_else(|| GroupSettings::new(chat_id).max_events);
+        let upcoming: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM events WHERE chat_id = ? AND time > ? AND cancelled_at IS NULL",
+        )
+        .bind(chat_id)
+        .bind(Utc::now().naive_utc().format(DATETIME_FORMAT).to_string())
+        .fetch_one(&mut *tx)
+        .await?;
+        if upcoming >= max_events {
+            return Err(BotError::LimitExceeded(format!(
+                "This group already has {max_events} active events. Delete older ones before creating new ones."
+            )));
+        }
+    }
+
+    let result = sqlx::query(
+        "INSERT INTO events (chat_id, user_id, name, description, location, time, max_capacity, rsvp_deadline, url, anonymous, tags, guests_per_rsvp) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(chat_id)
+    .bind(user_id)
+    .bind(&draft.name)
+    .bind(&draft.description)
+    .bind(&draft.location)
+    .bind(&draft.time)
+    .bind(draft.max_capacity)
+    .bind(&draft.rsvp_deadline)
+    .bind(&draft.url)
+    .bind(draft.anonymous)
+    .bind(join_tags(&draft.tags))
+    .bind(draft.guests_per_rsvp)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    Ok(result.last_insert_rowid())
+}
+
+/// Whether the chat has an event other than `except` whose name matches
+/// `name`, ignoring case, at `time`, not counting cancelled ones.
+async fn has_duplicate(
+    conn: &mut SqliteConnection,
+    chat_id: i64,
+    name: &str,
+    time: &str,
+    except: Option<i64>,
+) -> Result<bool, sqlx::Error> {
+    let duplicate: Option<i64> = sqlx::query_scalar(
+        "SELECT id FROM events
+WHERE chat_id = ? AND name = ? COLLATE NOCASE AND time = ? AND cancelled_at IS NULL AND id IS NOT ?",
+    )
+    .bind(chat_id)
+    .bind(name)
+    .bind(time)
+    .bind(except)
+    .fetch_optional(conn)
+    .await?;
+
+    Ok(duplicate.is_some())
+}
+
+/// Loads an event without its attendees.
+#[instrument(skip(pool))]
+pub async fn get_event(pool: &SqlitePool, event_id: i64) -> Result<Option<Event>, sqlx::Error> {
+    sqlx::query("SELECT * FROM events WHERE id = ?")
+        .bind(event_id)
+        .fetch_optional(pool)
+        .await?
+        .map(|row| Event::from_row(&row))
+        .transpose()
+}
+
+/// Remembers which message in the event's chat shows the event, so it can be
+/// updated after an edit, or forgets it with `None`.
+#[instrument(skip(pool))]
+pub async fn set_event_message_id(
+    pool: &SqlitePool,
+    event_id: i64,
+    message_id: Option<i32>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE events SET message_id = ?, message_hash = NULL WHERE id = ?")
+        .bind(message_id)
+        .bind(event_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Remembers the `Event::attendee_list_hash` the event's message shows.
+#[instrument(skip(pool))]
+pub async fn set_message_hash(
+    pool: &SqlitePool,
+    event_id: i64,
+    hash: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE events SET message_hash = ? WHERE id = ?")
+        .bind(hash)
+        .bind(event_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Hands `event_id` over to `user_id`, who can then edit or delete it.
+#[instrument(skip(pool))]
+pub async fn set_event_creator(
+    pool: &SqlitePool,
+    event_id: i64,
+    user_id: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE events SET user_id = ?, message_hash = NULL WHERE id = ?")
+        .bind(user_id)
+        .bind(event_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Overwrites a single field of an event.
+#[instrument(skip(pool))]
+pub async fn update_event_field(
+    pool: &SqlitePool,
+    event_id: i64,
+    field: EventField,
+    value: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(&format!(
+        "UPDATE events SET {} = ?, message_hash = NULL WHERE id = ?",
+        field.column()
+    ))
+    .bind(value)
+    .bind(event_id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Moves an event to `time`, and resets its reminder and summary so that
+/// they are sent again before the new time. Fails with
+/// `BotError::DuplicateEvent` if the chat already has an event with the
+/// same name at `time`.
+#[instrument(skip(pool))]
+pub async fn update_event_time(
+    pool: &SqlitePool,
+    event_id: i64,
+    time: &str,
+) -> Result<(), BotError> {
+    let mut tx = pool.begin().await?;
+
+    let event: Option<(i64, String)> =
+        sqlx::query_as("SELECT chat_id, name FROM events WHERE id = ?")
+            .bind(event_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+    let Some((chat_id, name)) = event else {
+        return Ok(());
+    };
+    if has_duplicate(&mut tx, chat_id, &name, time, Some(event_id)).await? {
+        return Err(BotError::DuplicateEvent);
+    }
+
+    sqlx::query(
+        "UPDATE events SET time = ?, reminders_sent = 0, summary_sent = 0, message_hash = NULL WHERE id = ?",
+    )
+        .bind(time)
+        .bind(event_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Returns the `(user_id, status)` RSVPs of an event, in the order they were made.
+#[instrument(skip(pool))]
+pub async fn get_attendees(
+    pool: &SqlitePool,
+    event_id: i64,
+) -> Result<Vec<(i64, String)>, sqlx::Error> {
+    sqlx::query_as("SELECT user_id, status FROM attendees WHERE event_id = ? ORDER BY rowid")
+        .bind(event_id)
+        .fetch_all(pool)
+        .await
+}
+
+/// Returns the decline reasons given for an event, by user.
+#[instrument(skip(pool))]
+pub async fn get_decline_reasons(
+    pool: &SqlitePool,
+    event_id: i64,
+) -> Result<Vec<(i64, String)>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT user_id, decline_reason FROM attendees
+WHERE event_id = ? AND status = 'declined' AND decline_reason IS NOT NULL",
+    )
+    .bind(event_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Returns the events, in any chat, that `user_id` accepted and that
+/// weren't cancelled, in the order they take place.
+#[instrument(skip(pool))]
+pub async fn get_accepted_event_summaries(
+    pool: &SqlitePool,
+    user_id: i64,
+) -> Result<Vec<EventSummary>, sqlx::Error> {
+    let rows: Vec<(i64, String, String)> = sqlx::query_as(
+        "SELECT events.id, events.name, events.time FROM events
+JOIN attendees ON attendees.event_id = events.id
+WHERE attendees.user_id = ? AND attendees.status = 'accepted' AND events.cancelled_at IS NULL
+ORDER BY events.time, events.id",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(id, name, time)| EventSummary { id, name, time })
+        .collect())
+}
+
+/// Stores why a user declined an event.
+#[instrument(skip(pool))]
+pub async fn set_decline_reason(
+    pool: &SqlitePool,
+    event_id: i64,
+    user_id: i64,
+    reason: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE attendees SET decline_reason = ? WHERE event_id = ? AND user_id = ? AND status = 'declined'",
+    )
+    .bind(reason)
+    .bind(event_id)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Returns the users who checked in to an event.
+#[instrument(skip(pool))]
+pub async fn get_checked_in(pool: &SqlitePool, event_id: i64) -> Result<Vec<i64>, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT user_id FROM attendees WHERE event_id = ? AND checked_in = 1 ORDER BY rowid",
+    )
+    .bind(event_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Checks a user in to an event. Returns `false`, without checking them in,
+/// unless they accepted or answered maybe.
+#[instrument(skip(pool))]
+pub async fn check_in(pool: &SqlitePool, event_id: i64, user_id: i64) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE attendees SET checked_in = 1
+WHERE event_id = ? AND user_id = ? AND status IN ('accepted', 'maybe')",
+    )
+    .bind(event_id)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Returns how many guests the accepted users of an event are bringing, for
+/// those bringing any.
+#[instrument(skip(pool))]
+pub async fn get_guests(pool: &SqlitePool, event_id: i64) -> Result<Vec<(i64, i64)>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT user_id, guests FROM attendees
+WHERE event_id = ? AND status = 'accepted' AND guests > 0
+ORDER BY rowid",
+    )
+    .bind(event_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Records how many guests a user is bringing to an event. Returns `false`,
+/// without recording it, unless they accepted.
+#[instrument(skip(pool))]
+pub async fn set_guests(
+    pool: &SqlitePool,
+    event_id: i64,
+    user_id: i64,
+    guests: i64,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE attendees SET guests = ?
+WHERE event_id = ? AND user_id = ? AND status = 'accepted'",
+    )
+    .bind(guests)
+    .bind(event_id)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// How `delete_event` gets rid of an event.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CancelMode {
+    /// Marks the event as cancelled, keeping it and its RSVPs for the record.
+    Soft,
+    /// Deletes the event along with its RSVPs.
+    Hard,
+}
+
+#[instrument(skip(pool))]
+pub async fn delete_event(
+    pool: &SqlitePool,
+    event_id: i64,
+    mode: CancelMode,
+) -> Result<(), sqlx::Error> {
+    if mode == CancelMode::Soft {
+        sqlx::query(
+            "UPDATE events SET cancelled_at = CURRENT_TIMESTAMP, message_hash = NULL WHERE id = ?",
+        )
+        .bind(event_id)
+        .execute(pool)
+        .await?;
+        return Ok(());
+    }
+
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM attendees WHERE event_id = ?")
+        .bind(event_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("DELETE FROM events WHERE id = ?")
+        .bind(event_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await
+}
+
+/// Cancels every upcoming event of a chat, noting `reason`, and returns the
+/// events it cancelled.
+#[instrument(skip(pool))]
+pub async fn cancel_chat_events(
+    pool: &SqlitePool,
+    chat_id: i64,
+    now: NaiveDateTime,
+    reason: &str,
+) -> Result<Vec<Event>, sqlx::Error> {
+    sqlx::query(
+        "UPDATE events SET cancelled_at = CURRENT_TIMESTAMP, cancel_reason = ?, message_hash = NULL
+WHERE chat_id = ? AND time > ? AND cancelled_at IS NULL
+RETURNING *",
+    )
+    .bind(reason)
+    .bind(chat_id)
+    .bind(now.format(DATETIME_FORMAT).to_string())
+    .fetch_all(pool)
+    .await?
+    .iter()
+    .map(Event::from_row)
+    .collect()
+}
+
+/// Returns the IDs of the events that started before `cutoff` (in UTC), in
+/// `chat_id` only if given.
+#[instrument(skip(pool))]
+pub async fn get_event_ids_before(
+    pool: &SqlitePool,
+    cutoff: NaiveDateTime,
+    chat_id: Option<i64>,
+) -> Result<Vec<i64>, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT id FROM events WHERE time < ? AND (? IS NULL OR chat_id = ?) ORDER BY time, id",
+    )
+    .bind(cutoff.format(DATETIME_FORMAT).to_string())
+    .bind(chat_id)
+    .bind(chat_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Deletes the events that started before `cutoff` (in UTC), in `chat_id`
+/// only if given, along with their RSVPs. Returns how many events were
+/// deleted.
+#[instrument(skip(pool))]
+pub async fn delete_events_before(
+    pool: &SqlitePool,
+    cutoff: NaiveDateTime,
+    chat_id: Option<i64>,
+) -> Result<u64, sqlx::Error> {
+    let cutoff = cutoff.format(DATETIME_FORMAT).to_string();
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        "DELETE FROM attendees WHERE event_id IN
+(SELECT id FROM events WHERE time < ? AND (? IS NULL OR chat_id = ?))",
+    )
+    .bind(&cutoff)
+    .bind(chat_id)
+    .bind(chat_id)
+    .execute(&mut *tx)
+    .await?;
+    let deleted = sqlx::query("DELETE FROM events WHERE time < ? AND (? IS NULL OR chat_id = ?)")
+        .bind(&cutoff)
+        .bind(chat_id)
+        .bind(chat_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+    tx.commit().await?;
+    Ok(deleted)
+}
+
+/// Returns a user's RSVP status for an event, if they have one.
+#[instrument(skip(pool))]
+pub async fn get_attendee_status(
+    pool: &SqlitePool,
+    event_id: i64,
+    user_id: i64,
+) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar("SELECT status FROM attendees WHERE event_id = ? AND user_id = ?")
+        .bind(event_id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Records a user's RSVP. Choosing the status the user already has removes
+/// their RSVP instead.
+///
+/// Accepting an event that is at its `max_capacity` puts the user on the
+/// waitlist. When an accepted user leaves, the longest-waiting user is
+/// promoted, and their ID is returned.
+#[instrument(skip(pool))]
+pub async fn update_attendance(
+    pool: &SqlitePool,
+    event_id: i64,
+    user_id: i64,
+    status: RsvpStatus,
+) -> Result<Option<i64>, sqlx::Error> {
+    let current = get_attendee_status(pool, event_id, user_id).await?;
+    let current = current.as_deref();
+
+    // Pressing Accept again while waitlisted leaves the waitlist.
+    let toggled_off = current == Some(status.as_str())
+        || (current == Some("waitlist") && status == RsvpStatus::Accepted);
+
+    if toggled_off {
+        sqlx::query("DELETE FROM attendees WHERE event_id = ? AND user_id = ?")
+            .bind(event_id)
+            .bind(user_id)
+            .execute(pool)
+            .await?;
+    } else {
+        let mut new_status = status.as_str();
+        if status == RsvpStatus::Accepted && is_full(pool, event_id).await? {
+            new_status = "waitlist";
+        }
+
+        sqlx::query(
+            "INSERT INTO attendees (event_id, user_id, status) VALUES (?, ?, ?)
+ON CONFLICT (event_id, user_id) DO UPDATE SET
+  status = excluded.status,
+  decline_reason = NULL,
+  checked_in = checked_in AND excluded.status IN ('accepted', 'maybe'),
+  guests = CASE WHEN excluded.status = 'accepted' THEN guests ELSE 0 END",
+        )
+        .bind(event_id)
+        .bind(user_id)
+        .bind(new_status)
+        .execute(pool)
+        .await?;
+    }
+
+    if current == Some("accepted") {
+        return promote_from_waitlist(pool, event_id).await;
+    }
+
+    Ok(None)
+}
+
+/// Whether the accepted list of an event has reached its `max_capacity`.
+async fn is_full(pool: &SqlitePool, event_id: i64) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT COUNT(attendees.user_id) >= events.max_capacity FROM events
+LEFT JOIN attendees ON attendees.event_id = events.id AND attendees.status = 'accepted'
+WHERE events.id = ? AND events.max_capacity IS NOT NULL
+GROUP BY events.id",
+    )
+    .bind(event_id)
+    .fetch_optional(pool)
+    .await
+    .map(|full| full.unwrap_or(false))
+}
+
+/// Removes a user's RSVP to an event, moving the first waitlisted user up if
+/// that frees a spot. Returns the promoted user's ID.
+#[instrument(skip(pool))]
+pub async fn remove_attendee(
+    pool: &SqlitePool,
+    event_id: i64,
+    user_id: i64,
+) -> Result<Option<i64>, sqlx::Error> {
+    let status: Option<String> = sqlx::query_scalar(
+        "DELETE FROM attendees WHERE event_id = ? AND user_id = ? RETURNING status",
+    )
+    .bind(event_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    if status.as_deref() == Some("accepted") {
+        return promote_from_waitlist(pool, event_id).await;
+    }
+
+    Ok(None)
+}
+
+/// Moves the first waitlisted user of an event to the accepted list if
+/// there is room, returning their ID.
+async fn promote_from_waitlist(
+    pool: &SqlitePool,
+    event_id: i64,
+) -> Result<Option<i64>, sqlx::Error> {
+    if is_full(pool, event_id).await? {
+        return Ok(None);
+    }
+
+    sqlx::query_scalar(
+        "UPDATE attendees SET status = 'accepted'
+WHERE rowid = (
+  SELECT rowid FROM attendees WHERE event_id = ? AND status = 'waitlist' ORDER BY rowid LIMIT 1
+)
+RETURNING user_id",
+    )
+    .bind(event_id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Returns the IDs of events starting after `from` and no later than `until`
+/// (both in UTC) whose reminders haven't been sent, soonest first.
+#[instrument(skip(pool))]
+pub async fn get_events_needing_reminder(
+    pool: &SqlitePool,
+    from: NaiveDateTime,
+    until: NaiveDateTime,
+) -> Result<Vec<i64>, sqlx::Error> {
+    // Times are stored as DATETIME_FORMAT strings, which sort chronologically.
+    sqlx::query_scalar(
+        "SELECT id FROM events WHERE time > ? AND time <= ? AND reminders_sent = 0 AND cancelled_at IS NULL ORDER BY time",
+    )
+    .bind(from.format(DATETIME_FORMAT).to_string())
+    .bind(until.format(DATETIME_FORMAT).to_string())
+    .fetch_all(pool)
+    .await
+}
+
+#[instrument(skip(pool))]
+pub async fn mark_reminders_sent(pool: &SqlitePool, event_id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE events SET reminders_sent = 1 WHERE id = ?")
+        .bind(event_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Returns the IDs of events starting between `from` and `until` (both in
+/// UTC) whose group hasn't been sent an attendance summary, soonest first.
+#[instrument(skip(pool))]
+pub async fn get_events_needing_summary(
+    pool: &SqlitePool,
+    from: NaiveDateTime,
+    until: NaiveDateTime,
+) -> Result<Vec<i64>, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT id FROM events WHERE time BETWEEN ? AND ? AND summary_sent = 0 AND cancelled_at IS NULL ORDER BY time",
+    )
+    .bind(from.format(DATETIME_FORMAT).to_string())
+    .bind(until.format(DATETIME_FORMAT).to_string())
+    .fetch_all(pool)
+    .await
+}
+
+#[instrument(skip(pool))]
+pub async fn mark_summary_sent(pool: &SqlitePool, event_id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE events SET summary_sent = 1 WHERE id = ?")
+        .bind(event_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Records that the creator of an event reminded its attendees at `now` (in
+/// UTC), unless they already did after `since`. Returns whether it was
+/// recorded, so that reminders sent at the same time can't both go out.
+#[instrument(skip(pool))]
+pub async fn claim_manual_reminder(
+    pool: &SqlitePool,
+    event_id: i64,
+    now: NaiveDateTime,
+    since: NaiveDateTime,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE events SET last_reminded_at = ?
+WHERE id = ? AND (last_reminded_at IS NULL OR last_reminded_at <= ?)",
+    )
+    .bind(now.format(DATETIME_FORMAT).to_string())
+    .bind(event_id)
+    .bind(since.format(DATETIME_FORMAT).to_string())
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Undoes [`claim_manual_reminder`] for the claim made at `claimed_at`, when
+/// the reminder reached nobody, so the creator can try again.
+#[instrument(skip(pool))]
+pub async fn release_manual_reminder(
+    pool: &SqlitePool,
+    event_id: i64,
+    claimed_at: NaiveDateTime,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE events SET last_reminded_at = NULL WHERE id = ? AND last_reminded_at = ?")
+        .bind(event_id)
+        .bind(claimed_at.format(DATETIME_FORMAT).to_string())
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Records that a user sent feedback at `now` (in UTC), unless they already
+/// sent `limit` since `since`. Returns whether it was recorded.
+#[instrument(skip(pool))]
+pub async fn claim_feedback(
+    pool: &SqlitePool,
+    user_id: i64,
+    now: NaiveDateTime,
+    since: NaiveDateTime,
+    limit: i64,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT INTO feedback_log (user_id, sent_at)
+SELECT ?, ? WHERE (SELECT COUNT(*) FROM feedback_log WHERE user_id = ? AND sent_at > ?) < ?",
+    )
+    .bind(user_id)
+    .bind(now.format(DATETIME_FORMAT).to_string())
+    .bind(user_id)
+    .bind(since.format(DATETIME_FORMAT).to_string())
+    .bind(limit)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Stops `user_id` from RSVPing to the events of `creator_id`. Returns
+/// whether they weren't blocked already.
+#[instrument(skip(pool))]
+pub async fn block_attendee(
+    pool: &SqlitePool,
+    creator_id: i64,
+    user_id: i64,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT OR IGNORE INTO blocked_attendees (creator_id, blocked_user_id) VALUES (?, ?)",
+    )
+    .bind(creator_id)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Lets a blocked user RSVP to the events of `creator_id` again. Returns
+/// whether they were blocked.
+#[instrument(skip(pool))]
+pub async fn unblock_attendee(
+    pool: &SqlitePool,
+    creator_id: i64,
+    user_id: i64,
+) -> Result<bool, sqlx::Error> {
+    let result =
+        sqlx::query("DELETE FROM blocked_attendees WHERE creator_id = ? AND blocked_user_id = ?")
+            .bind(creator_id)
+            .bind(user_id)
+            .execute(pool)
+            .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+#[instrument(skip(pool))]
+pub async fn is_blocked(
+    pool: &SqlitePool,
+    creator_id: i64,
+    user_id: i64,
+) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM blocked_attendees WHERE creator_id = ? AND blocked_user_id = ?)",
+    )
+    .bind(creator_id)
+    .bind(user_id)
+    .fetch_one(pool)
+    .await
+}
+
+/// Returns the IDs of events starting after `now` (in UTC) that `creator_id`
+/// created and `user_id` RSVPed to, soonest first.
+#[instrument(skip(pool))]
+pub async fn get_created_event_ids_with_attendee(
+    pool: &SqlitePool,
+    creator_id: i64,
+    user_id: i64,
+    now: NaiveDateTime,
+) -> Result<Vec<i64>, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT events.id FROM events
+JOIN attendees ON attendees.event_id = events.id
+WHERE events.user_id = ? AND attendees.user_id = ? AND events.time > ?
+ORDER BY events.time",
+    )
+    .bind(creator_id)
+    .bind(user_id)
+    .bind(now.format(DATETIME_FORMAT).to_string())
+    .fetch_all(pool)
+    .await
+}
+
+/// The order in which `get_created_event_ids` and `get_attending_event_ids`
+/// return events.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum EventSort {
+    /// Soonest first.
+    #[default]
+    ByDate,
+    /// Oldest first, in the order they were created.
+    ByCreation,
+}
+
+impl EventSort {
+    /// The column to `ORDER BY`.
+    fn column(self) -> &'static str {
+        match self {
+            EventSort::ByDate => "events.time",
+            EventSort::ByCreation => "events.id",
+        }
+    }
+}
+
+/// Returns the IDs of events starting after `now` (in UTC) that a user
+/// created and didn't cancel, in `sort` order.
+#[instrument(skip(pool))]
+pub async fn get_created_event_ids(
+    pool: &SqlitePool,
+    user_id: i64,
+    now: NaiveDateTime,
+    sort: EventSort,
+) -> Result<Vec<i64>, sqlx::Error> {
+    let query = format!(
+        "SELECT id FROM events WHERE user_id = ? AND time > ? AND cancelled_at IS NULL ORDER BY {}",
+        sort.column()
+    );
+    sqlx::query_scalar(&query)
+        .bind(user_id)
+        .bind(now.format(DATETIME_FORMAT).to_string())
+        .fetch_all(pool)
+        .await
+}
+
+/// Returns the IDs of events starting after `now` (in UTC) that a user
+/// accepted or might attend, in `sort` order.
+#[instrument(skip(pool))]
+pub async fn get_attending_event_ids(
+    pool: &SqlitePool,
+    user_id: i64,
+    now: NaiveDateTime,
+    sort: EventSort,
+) -> Result<Vec<i64>, sqlx::Error> {
+    let query = format!(
+        "SELECT attendees.event_id FROM attendees
+JOIN events ON events.id = attendees.event_id
+WHERE attendees.user_id = ? AND attendees.status IN ('accepted', 'maybe') AND events.time > ?
+  AND events.cancelled_at IS NULL
+ORDER BY {}",
+        sort.column()
+    );
+    sqlx::query_scalar(&query)
+        .bind(user_id)
+        .bind(now.format(DATETIME_FORMAT).to_string())
+        .fetch_all(pool)
+        .await
+}
+
+/// A `LIKE` pattern, with `\` as the escape character, matching text that
+/// contains `text`.
+fn like_pattern(text: &str) -> String {
+    format!("%{}%", escape_like(text))
+}
+
+/// Escapes the `LIKE` wildcards in `text`, with `\` as the escape character.
+fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// Stores tags as `sports,social`, or `NULL` if there are none.
+pub(crate) fn join_tags(tags: &[String]) -> Option<String> {
+    (!tags.is_empty()).then(|| tags.join(","))
+}
+
+/// Returns the IDs of up to `limit` events starting after `now` (in UTC)
+/// whose name contains `query`, ignoring case, and that a user created,
+/// accepted or might attend. Soonest first.
+#[instrument(skip(pool))]
+pub async fn search_user_events(
+    pool: &SqlitePool,
+    user_id: i64,
+    query: &str,
+    now: NaiveDateTime,
+    limit: u32,
+) -> Result<Vec<i64>, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT id FROM events
+WHERE name LIKE ? ESCAPE '\\' AND time > ? AND cancelled_at IS NULL
+  AND (user_id = ? OR id IN (
+    SELECT event_id FROM attendees WHERE user_id = ? AND status IN ('accepted', 'maybe')
+  ))
+ORDER BY time
+LIMIT ?",
+    )
+    .bind(like_pattern(query))
+    .bind(now.format(DATETIME_FORMAT).to_string())
+    .bind(user_id)
+    .bind(user_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+/// Returns the IDs of up to `limit` events of a chat starting after `now`
+/// (in UTC) and not cancelled, whose name contains `keyword`, ignoring case,
+/// in the order they take place.
+#[instrument(skip(pool))]
+pub async fn search_events(
+    pool: &SqlitePool,
+    chat_id: i64,
+    keyword: &str,
+    now: NaiveDateTime,
+    limit: u32,
+) -> Result<Vec<i64>, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT id FROM events
+WHERE chat_id = ? AND LOWER(name) LIKE LOWER(?) ESCAPE '\\' AND time > ? AND cancelled_at IS NULL
+ORDER BY time LIMIT ?",
+    )
+    .bind(chat_id)
+    .bind(like_pattern(keyword))
+    .bind(now.format(DATETIME_FORMAT).to_string())
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+/// Loads the events of a chat starting after `now` (in UTC) and within
+/// `days` days of it, soonest first, without their attendees.
+#[instrument(skip(pool))]
+pub async fn fetch_upcoming_events(
+    pool: &SqlitePool,
+    chat_id: i64,
+    now: NaiveDateTime,
+    days: u32,
+) -> Result<Vec<Event>, sqlx::Error> {
+    let until = now + chrono::Duration::days(days.into());
+
+    sqlx::query(
+        "SELECT * FROM events WHERE chat_id = ? AND time > ? AND time <= ? AND cancelled_at IS NULL ORDER BY time",
+    )
+        .bind(chat_id)
+        .bind(now.format(DATETIME_FORMAT).to_string())
+        .bind(until.format(DATETIME_FORMAT).to_string())
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(Event::from_row)
+        .collect()
+}
+
+/// Loads every event of a chat, past and cancelled ones included, oldest
+/// first and without their attendees.
+#[instrument(skip(pool))]
+pub async fn fetch_all_events(pool: &SqlitePool, chat_id: i64) -> Result<Vec<Event>, sqlx::Error> {
+    sqlx::query("SELECT * FROM events WHERE chat_id = ? ORDER BY time, id")
+        .bind(chat_id)
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(Event::from_row)
+        .collect()
+}
+
+/// Loads up to `limit` events of a chat starting after `now` (in UTC),
+/// soonest first and skipping the first `offset`, without their attendees.
+/// With a `tag`, only those tagged with it.
+#[instrument(skip(pool))]
+pub async fn fetch_events_paged(
+    pool: &SqlitePool,
+    chat_id: i64,
+    now: NaiveDateTime,
+    tag: Option<&str>,
+    offset: i64,
+    limit: i64,
+) -> Result<Vec<Event>, sqlx::Error> {
+    // Wrapping the stored `a,b` in commas makes every tag match `%,tag,%`.
+    let pattern = tag.map(|tag| format!("%,{},%", escape_like(tag)));
+
+    sqlx::query(
+        "SELECT * FROM events
+WHERE chat_id = ? AND time > ? AND cancelled_at IS NULL
+  AND (? IS NULL OR ',' || tags || ',' LIKE ? ESCAPE '\\')
+ORDER BY time, id
+LIMIT ? OFFSET ?",
+    )
+    .bind(chat_id)
+    .bind(now.format(DATETIME_FORMAT).to_string())
+    .bind(&pattern)
+    .bind(&pattern)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await?
+    .iter()
+    .map(Event::from_row)
+    .collect()
+}
+
+/// Counts the events `fetch_events_paged` pages through.
+#[instrument(skip(pool))]
+pub async fn count_listed_events(
+    pool: &SqlitePool,
+    chat_id: i64,
+    now: NaiveDateTime,
+    tag: Option<&str>,
+) -> Result<i64, sqlx::Error> {
+    let pattern = tag.map(|tag| format!("%,{},%", escape_like(tag)));
+
+    sqlx::query_scalar(
+        "SELECT COUNT(*) FROM events
+WHERE chat_id = ? AND time > ? AND cancelled_at IS NULL
+  AND (? IS NULL OR ',' || tags || ',' LIKE ? ESCAPE '\\')",
+    )
+    .bind(chat_id)
+    .bind(now.format(DATETIME_FORMAT).to_string())
+    .bind(&pattern)
+    .bind(&pattern)
+    .fetch_one(pool)
+    .await
+}
+
+/// Returns the tags of a chat's events starting after `now` (in UTC), in
+/// alphabetical order.
+#[instrument(skip(pool))]
+pub async fn get_chat_tags(
+    pool: &SqlitePool,
+    chat_id: i64,
+    now: NaiveDateTime,
+) -> Result<Vec<String>, sqlx::Error> {
+    let tags: Vec<String> = sqlx::query_scalar(
+        "SELECT tags FROM events WHERE chat_id = ? AND time > ? AND cancelled_at IS NULL AND tags IS NOT NULL",
+    )
+    .bind(chat_id)
+    .bind(now.format(DATETIME_FORMAT).to_string())
+    .fetch_all(pool)
+    .await?;
+
+    let tags: BTreeSet<String> = tags.iter().flat_map(|tags| parse_tags(tags)).collect();
+    Ok(tags.into_iter().collect())
+}
+
+/// Loads the events of a chat that started before `now` (in UTC) or were
+/// cancelled, latest first, without their attendees.
+#[instrument(skip(pool))]
+pub async fn fetch_past_events(
+    pool: &SqlitePool,
+    chat_id: i64,
+    now: NaiveDateTime,
+) -> Result<Vec<Event>, sqlx::Error> {
+    sqlx::query(
+        "SELECT * FROM events WHERE chat_id = ? AND (time <= ? OR cancelled_at IS NOT NULL)
+ORDER BY time DESC",
+    )
+    .bind(chat_id)
+    .bind(now.format(DATETIME_FORMAT).to_string())
+    .fetch_all(pool)
+    .await?
+    .iter()
+    .map(Event::from_row)
+    .collect()
+}
+
+/// Counts the events of a chat that haven't started by `now` (in UTC).
+#[instrument(skip(pool))]
+pub async fn count_upcoming_events(
+    pool: &SqlitePool,
+    chat_id: i64,
+    now: NaiveDateTime,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT COUNT(*) FROM events WHERE chat_id = ? AND time > ? AND cancelled_at IS NULL",
+    )
+    .bind(chat_id)
+    .bind(now.format(DATETIME_FORMAT).to_string())
+    .fetch_one(pool)
+    .await
+}
+
+/// Moves the events, settings, trusted creators and drafts of `old_chat_id`
+/// to `new_chat_id`, for a group that was upgraded to a supergroup. Returns
+/// whether anything was moved, which nothing is once the migration has been
+/// handled.
+#[instrument(skip(pool))]
+pub async fn migrate_chat(
+    pool: &SqlitePool,
+    old_chat_id: i64,
+    new_chat_id: i64,
+) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let events = sqlx::query("UPDATE events SET chat_id = ? WHERE chat_id = ?")
+        .bind(new_chat_id)
+        .bind(old_chat_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    // Settings already made in the supergroup win over the old ones.
+    let settings = sqlx::query("UPDATE OR IGNORE group_settings SET chat_id = ? WHERE chat_id = ?")
+        .bind(new_chat_id)
+        .bind(old_chat_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    sqlx::query("DELETE FROM group_settings WHERE chat_id = ?")
+        .bind(old_chat_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("UPDATE OR IGNORE trusted_creators SET chat_id = ? WHERE chat_id = ?")
+        .bind(new_chat_id)
+        .bind(old_chat_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("DELETE FROM trusted_creators WHERE chat_id = ?")
+        .bind(old_chat_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("UPDATE OR IGNORE chat_names SET chat_id = ? WHERE chat_id = ?")
+        .bind(new_chat_id)
+        .bind(old_chat_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("DELETE FROM chat_names WHERE chat_id = ?")
+        .bind(old_chat_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("UPDATE drafts SET origin_chat_id = ? WHERE origin_chat_id = ?")
+        .bind(new_chat_id)
+        .bind(old_chat_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(events + settings > 0)
+}
+
+/// Returns the timezone configured for a group, if one was set.
+#[instrument(skip(pool))]
+pub async fn get_group_timezone(
+    pool: &SqlitePool,
+    chat_id: i64,
+) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar("SELECT timezone FROM group_settings WHERE chat_id = ?")
+        .bind(chat_id)
+        .fetch_optional(pool)
+        .await
+}
+
+#[instrument(skip(pool))]
+pub async fn set_group_timezone(
+    pool: &SqlitePool,
+    chat_id: i64,
+    timezone: &str,
+) -> Result<(), sqlx::Error> {
+    let _ = sqlx::query(
+        "INSERT INTO group_settings (chat_id, timezone) VALUES (?, ?)
+ON CONFLICT (chat_id) DO UPDATE SET timezone = excluded.timezone",
+    )
+    .bind(chat_id)
+    .bind(timezone)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,117 @@
+use crate::db::join_tags;
+use crate::event::{parse_tags, EventDraft, UserState};
+use crate::util::DATETIME_FORMAT;
+use chrono::NaiveDateTime;
+use sqlx::{Row, SqlitePool};
+use tracing::{instrument, warn};
+
+/// An event creation in progress, saved so that it survives restarts.
+#[derive(Debug)]
+pub struct StoredDraft {
+    pub user_id: i64,
+    pub state: UserState,
+    /// For a `/clone`, the chat the copy is posted to.
+    pub origin_chat_id: Option<i64>,
+    pub draft: EventDraft,
+    /// The IANA name of the timezone times are entered in.
+    pub timezone: Option<String>,
+    /// When the draft last changed, in UTC.
+    pub updated_at: NaiveDateTime,
+}
+
+/// Saves `draft`, replacing the previous save of its user's draft.
+#[instrument(skip_all, fields(user_id = draft.user_id))]
+pub async fn upsert_draft(pool: &SqlitePool, draft: &StoredDraft) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO drafts (user_id, state, origin_chat_id, title, description, location, datetime, max_capacity, rsvp_deadline, url, anonymous, tags, guests_per_rsvp, timezone, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+ON CONFLICT (user_id) DO UPDATE SET
+  state = excluded.state,
+  origin_chat_id = excluded.origin_chat_id,
+  title = excluded.title,
+  description = excluded.description,
+  location = excluded.location,
+  datetime = excluded.datetime,
+  max_capacity = excluded.max_capacity,
+  rsvp_deadline = excluded.rsvp_deadline,
+  url = excluded.url,
+  anonymous = excluded.anonymous,
+  tags = excluded.tags,
+  guests_per_rsvp = excluded.guests_per_rsvp,
+  timezone = excluded.timezone,
+  updated_at = excluded.updated_at",
+    )
+    .bind(draft.user_id)
+    .bind(draft.state.as_str())
+    .bind(draft.origin_chat_id)
+    .bind(&draft.draft.name)
+    .bind(&draft.draft.description)
+    .bind(&draft.draft.location)
+    .bind(&draft.draft.time)
+    .bind(draft.draft.max_capacity)
+    .bind(&draft.draft.rsvp_deadline)
+    .bind(&draft.draft.url)
+    .bind(draft.draft.anonymous)
+    .bind(join_tags(&draft.draft.tags))
+    .bind(draft.draft.guests_per_rsvp)
+    .bind(&draft.timezone)
+    .bind(draft.updated_at.format(DATETIME_FORMAT).to_string())
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Returns every saved draft. Drafts in a state this version doesn't know
+/// are skipped.
+#[instrument(skip(pool))]
+pub async fn get_drafts(pool: &SqlitePool) -> Result<Vec<StoredDraft>, sqlx::Error> {
+    let rows = sqlx::query("SELECT * FROM drafts").fetch_all(pool).await?;
+
+    let mut drafts = Vec::with_capacity(rows.len());
+    for row in rows {
+        let user_id: i64 = row.try_get("user_id")?;
+        let state: String = row.try_get("state")?;
+        let Some(state) = UserState::parse(&state) else {
+            warn!(user_id, state, "skipping draft in unknown state");
+            continue;
+        };
+        let updated_at: String = row.try_get("updated_at")?;
+
+        drafts.push(StoredDraft {
+            user_id,
+            state,
+            origin_chat_id: row.try_get("origin_chat_id")?,
+            draft: EventDraft {
+                name: row.try_get("title")?,
+                description: row.try_get("description")?,
+                location: row.try_get("location")?,
+                time: row.try_get("datetime")?,
+                max_capacity: row.try_get("max_capacity")?,
+                rsvp_deadline: row.try_get("rsvp_deadline")?,
+                url: row.try_get("url")?,
+                anonymous: row.try_get("anonymous")?,
+                tags: row
+                    .try_get::<Option<String>, _>("tags")?
+                    .map(|tags| parse_tags(&tags))
+                    .unwrap_or_default(),
+                guests_per_rsvp: row.try_get("guests_per_rsvp")?,
+            },
+            timezone: row.try_get("timezone")?,
+            updated_at: NaiveDateTime::parse_from_str(&updated_at, DATETIME_FORMAT)
+                .map_err(|err| sqlx::Error::Decode(Box::new(err)))?,
+        });
+    }
+
+    Ok(drafts)
+}
+
+/// Removes the saved draft of `user_id`, if there is one.
+#[instrument(skip(pool))]
+pub async fn delete_draft(pool: &SqlitePool, user_id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM drafts WHERE user_id = ?")
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,151 @@
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Row, SqlitePool};
+use tracing::instrument;
+
+/// A group's configuration, as changed with `/settings`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupSettings {
+    pub chat_id: i64,
+    /// The most upcoming events the group can have at once.
+    pub max_events: i64,
+    /// Whether only administrators can create events, on top of
+    /// `CREATION_ALLOWED_ROLES`.
+    pub require_admin: bool,
+    /// Whether event creators get a private message for every RSVP.
+    pub notify_creator: bool,
+    /// Whether new events are pinned in the group.
+    pub pinned: bool,
+    /// Whether only administrators and the users added with `/addcreator`
+    /// can create events.
+    pub trusted_creators_only: bool,
+    /// The IANA name of the timezone event times are entered and shown in.
+    pub timezone: String,
+}
+
+impl GroupSettings {
+    /// The settings of a group that never changed them.
+    pub fn new(chat_id: i64) -> Self {
+        Self {
+            chat_id,
+            max_events: 10,
+            require_admin: false,
+            notify_creator: true,
+            pinned: false,
+            trusted_creators_only: false,
+            timezone: "UTC".to_string(),
+        }
+    }
+
+    fn from_row(row: &SqliteRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            chat_id: row.try_get("chat_id")?,
+            max_events: row.try_get("max_events")?,
+            require_admin: row.try_get("require_admin")?,
+            notify_creator: row.try_get("notify_creator")?,
+            pinned: row.try_get("pinned")?,
+            trusted_creators_only: row.try_get("trusted_creators_only")?,
+            timezone: row.try_get("timezone")?,
+        })
+    }
+}
+
+/// Returns a group's settings, or the defaults if it has none stored.
+#[instrument(skip(pool))]
+pub async fn get_settings(pool: &SqlitePool, chat_id: i64) -> Result<GroupSettings, sqlx::Error> {
+    let settings = sqlx::query("SELECT * FROM group_settings WHERE chat_id = ?")
+        .bind(chat_id)
+        .fetch_optional(pool)
+        .await?
+        .map(|row| GroupSettings::from_row(&row))
+        .transpose()?;
+
+    Ok(settings.unwrap_or_else(|| GroupSettings::new(chat_id)))
+}
+
+#[instrument(skip(pool))]
+pub async fn upsert_settings(
+    pool: &SqlitePool,
+    settings: &GroupSettings,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO group_settings (chat_id, max_events, require_admin, notify_creator, pinned, trusted_creators_only, timezone) VALUES (?, ?, ?, ?, ?, ?, ?)
+ON CONFLICT (chat_id) DO UPDATE SET
+  max_events = excluded.max_events,
+  require_admin = excluded.require_admin,
+  notify_creator = excluded.notify_creator,
+  pinned = excluded.pinned,
+  trusted_creators_only = excluded.trusted_creators_only,
+  timezone = excluded.timezone",
+    )
+    .bind(settings.chat_id)
+    .bind(settings.max_events)
+    .bind(settings.require_admin)
+    .bind(settings.notify_creator)
+    .bind(settings.pinned)
+    .bind(settings.trusted_creators_only)
+    .bind(&settings.timezone)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Forgets a group's settings, so it starts over from the defaults.
+#[instrument(skip(pool))]
+pub async fn delete_settings(pool: &SqlitePool, chat_id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM group_settings WHERE chat_id = ?")
+        .bind(chat_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Lets a user create events in a group with `trusted_creators_only` set.
+/// Returns whether they weren't trusted already.
+#[instrument(skip(pool))]
+pub async fn add_trusted_creator(
+    pool: &SqlitePool,
+    chat_id: i64,
+    user_id: i64,
+) -> Result<bool, sqlx::Error> {
+    let result =
+        sqlx::query("INSERT OR IGNORE INTO trusted_creators (chat_id, user_id) VALUES (?, ?)")
+            .bind(chat_id)
+            .bind(user_id)
+            .execute(pool)
+            .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Undoes `add_trusted_creator`. Returns whether the user was trusted.
+#[instrument(skip(pool))]
+pub async fn remove_trusted_creator(
+    pool: &SqlitePool,
+    chat_id: i64,
+    user_id: i64,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM trusted_creators WHERE chat_id = ? AND user_id = ?")
+        .bind(chat_id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+#[instrument(skip(pool))]
+pub async fn is_trusted_creator(
+    pool: &SqlitePool,
+    chat_id: i64,
+    user_id: i64,
+) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM trusted_creators WHERE chat_id = ? AND user_id = ?)",
+    )
+    .bind(chat_id)
+    .bind(user_id)
+    .fetch_one(pool)
+    .await
+}
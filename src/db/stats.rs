@@ -0,0 +1,158 @@
+use crate::util::DATETIME_FORMAT;
+use chrono::NaiveDateTime;
+use sqlx::{Row, SqlitePool};
+use tracing::instrument;
+
+/// RSVP figures for a group's events, as shown by `/stats`. Cancelled events
+/// aren't counted.
+#[derive(Debug, Default, PartialEq)]
+pub struct GroupStats {
+    pub total_events: i64,
+    /// Events taking place in the calendar month (in UTC) of `now`.
+    pub events_this_month: i64,
+    pub average_accepted: f64,
+    /// The name and accepted count of the event most people accepted.
+    pub top_event: Option<(String, i64)>,
+    /// How many different people accepted any event.
+    pub unique_attendees: i64,
+}
+
+#[instrument(skip(pool))]
+pub async fn get_group_stats(
+    pool: &SqlitePool,
+    chat_id: i64,
+    now: NaiveDateTime,
+) -> Result<GroupStats, sqlx::Error> {
+    // Times are DATETIME_FORMAT strings, so the month is their first 7 characters.
+    let month = now.format(DATETIME_FORMAT).to_string()[..7].to_string();
+    let events = sqlx::query(
+        "SELECT COUNT(*) AS total, COALESCE(SUM(substr(time, 1, 7) = ?), 0) AS this_month
+FROM events WHERE chat_id = ? AND cancelled_at IS NULL",
+    )
+    .bind(month)
+    .bind(chat_id)
+    .fetch_one(pool)
+    .await?;
+
+    let attendees = sqlx::query(
+        "SELECT COUNT(*) AS accepted, COUNT(DISTINCT attendees.user_id) AS unique_attendees
+FROM attendees JOIN events ON events.id = attendees.event_id
+WHERE events.chat_id = ? AND events.cancelled_at IS NULL AND attendees.status = 'accepted'",
+    )
+    .bind(chat_id)
+    .fetch_one(pool)
+    .await?;
+
+    let top_event = sqlx::query(
+        "SELECT events.name, COUNT(*) AS accepted
+FROM attendees JOIN events ON events.id = attendees.event_id
+WHERE events.chat_id = ? AND events.cancelled_at IS NULL AND attendees.status = 'accepted'
+GROUP BY events.id
+ORDER BY accepted DESC, events.time DESC
+LIMIT 1",
+    )
+    .bind(chat_id)
+    .fetch_optional(pool)
+    .await?
+    .map(|row| Ok::<_, sqlx::Error>((row.try_get("name")?, row.try_get("accepted")?)))
+    .transpose()?;
+
+    let total_events: i64 = events.try_get("total")?;
+    let accepted: i64 = attendees.try_get("accepted")?;
+
+    Ok(GroupStats {
+        total_events,
+        events_this_month: events.try_get("this_month")?,
+        average_accepted: if total_events > 0 {
+            accepted as f64 / total_events as f64
+        } else {
+            0.0
+        },
+        top_event,
+        unique_attendees: attendees.try_get("unique_attendees")?,
+    })
+}
+
+/// How many events the database holds, cancelled and past ones included.
+#[instrument(skip(pool))]
+pub async fn count_events(pool: &SqlitePool) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar("SELECT COUNT(*) FROM events")
+        .fetch_one(pool)
+        .await
+}
+
+/// How many RSVPs the database holds, across all events.
+#[instrument(skip(pool))]
+pub async fn count_attendees(pool: &SqlitePool) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar("SELECT COUNT(*) FROM attendees")
+        .fetch_one(pool)
+        .await
+}
+
+/// A chat's events, as listed by `/listall`.
+#[derive(Debug, PartialEq)]
+pub struct ChatSummary {
+    pub chat_id: i64,
+    /// The chat's title, if the bot was added to it since titles are stored.
+    pub title: Option<String>,
+    /// Every event of the chat, cancelled and past ones included.
+    pub events: i64,
+    /// The name and time (in UTC) of the chat's next event, if it has one.
+    pub next_event: Option<(String, String)>,
+}
+
+/// Summarizes the events of every chat that has any, by chat ID.
+#[instrument(skip(pool))]
+pub async fn get_chat_summaries(
+    pool: &SqlitePool,
+    now: NaiveDateTime,
+) -> Result<Vec<ChatSummary>, sqlx::Error> {
+    let now = now.format(DATETIME_FORMAT).to_string();
+    let rows = sqlx::query(
+        "SELECT events.chat_id, chat_names.title, COUNT(*) AS events, next.name AS next_name, next.time AS next_time
+FROM events
+LEFT JOIN chat_names ON chat_names.chat_id = events.chat_id
+LEFT JOIN events AS next ON next.id = (
+  SELECT id FROM events AS upcoming
+  WHERE upcoming.chat_id = events.chat_id AND upcoming.time > ? AND upcoming.cancelled_at IS NULL
+  ORDER BY upcoming.time, upcoming.id LIMIT 1
+)
+GROUP BY events.chat_id
+ORDER BY events.chat_id",
+    )
+    .bind(now)
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter()
+        .map(|row| {
+            let next_name: Option<String> = row.try_get("next_name")?;
+            let next_time: Option<String> = row.try_get("next_time")?;
+            Ok(ChatSummary {
+                chat_id: row.try_get("chat_id")?,
+                title: row.try_get("title")?,
+                events: row.try_get("events")?,
+                next_event: next_name.zip(next_time),
+            })
+        })
+        .collect()
+}
+
+/// Remembers the title of a chat the bot is in, for `/listall`.
+#[instrument(skip(pool))]
+pub async fn upsert_chat_name(
+    pool: &SqlitePool,
+    chat_id: i64,
+    title: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO chat_names (chat_id, title) VALUES (?, ?)
+ON CONFLICT (chat_id) DO UPDATE SET title = excluded.title",
+    )
+    .bind(chat_id)
+    .bind(title)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use std::fmt;
+
+#[derive(Debug)]
+pub enum BotError {
+    Telegram(frankenstein::Error),
+    Database(sqlx::Error),
+    Io(std::io::Error),
+    DateTime(chrono::ParseError),
+    Migrate(sqlx::migrate::MigrateError),
+    /// A missing or invalid environment variable.
+    Config(String),
+    /// The chat already has an event with the same title and time.
+    DuplicateEvent,
+    /// The chat can't have more events, with the message explaining why.
+    LimitExceeded(String),
+    /// Someone tried an action reserved for others, such as deleting an event
+    /// they didn't create.
+    PermissionDenied(String),
+}
+
+impl fmt::Display for BotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BotError::Telegram(err) => write!(f, "Telegram API error: {err}"),
+            BotError::Database(err) => write!(f, "Database error: {err}"),
+            BotError::Io(err) => write!(f, "I/O error: {err}"),
+            BotError::DateTime(err) => write!(f, "Invalid date/time: {err}"),
+            BotError::Migrate(err) => write!(f, "Database migration error: {err}"),
+            BotError::Config(message) => write!(f, "Configuration error: {message}"),
+            BotError::DuplicateEvent => {
+                write!(f, "An event with this title and time already exists")
+            }
+            BotError::LimitExceeded(message) => write!(f, "{message}"),
+            BotError::PermissionDenied(message) => write!(f, "Permission denied: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for BotError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BotError::Telegram(err) => Some(err),
+            BotError::Database(err) => Some(err),
+            BotError::Io(err) => Some(err),
+            BotError::DateTime(err) => Some(err),
+            BotError::Migrate(err) => Some(err),
+            BotError::Config(_)
+            | BotError::DuplicateEvent
+            | BotError::LimitExceeded(_)
+            | BotError::PermissionDenied(_) => None,
+        }
+    }
+}
+
+impl From<frankenstein::Error> for BotError {
+    fn from(err: frankenstein::Error) -> Self {
+        BotError::Telegram(err)
+    }
+}
+
+impl From<sqlx::Error> for BotError {
+    fn from(err: sqlx::Error) -> Self {
+        BotError::Database(err)
+    }
+}
+
+impl From<std::io::Error> for BotError {
+    fn from(err: std::io::Error) -> Self {
+        BotError::Io(err)
+    }
+}
+
+impl From<chrono::ParseError> for BotError {
+    fn from(err: chrono::ParseError) -> Self {
+        BotError::DateTime(err)
+    }
+}
+
+impl From<sqlx::migrate::MigrateError> for BotError {
+    fn from(err: sqlx::migrate::MigrateError) -> Self {
+        BotError::Migrate(err)
+    }
+}
@@ -0,0 +1,1252 @@
+pub mod csv;
+pub mod ical;
+pub mod json;
+
+use crate::util::{parse_datetime_string, utc_to_local, DATETIME_FORMAT};
+use chrono::{NaiveDateTime, Utc};
+use chrono_tz::Tz;
+use frankenstein::{InlineKeyboardButton, InlineKeyboardMarkup, ParseMode};
+use serde::Serialize;
+use sqlx::sqlite::SqliteRow;
+use sqlx::Row;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+use std::time::Instant;
+
+/// Characters that have to be escaped in MarkdownV2 text.
+const MARKDOWN_SPECIAL_CHARS: &[char] = &[
+    '\\', '_', '*', '[', ']', '(', ')', '~', '`', '>', '#', '+', '-', '=', '|', '{', '}', '.', '!',
+];
+
+/// The longest title, description and location, in characters, an event can
+/// have. They keep event messages well below Telegram's 4096 character limit.
+pub const MAX_TITLE_LEN: usize = 100;
+pub const MAX_DESCRIPTION_LEN: usize = 500;
+pub const MAX_LOCATION_LEN: usize = 200;
+/// The longest reason, in characters, a user can give for declining.
+pub const MAX_DECLINE_REASON_LEN: usize = 100;
+/// The most people a single RSVP can be for, the attendee included.
+pub const MAX_GUESTS_PER_RSVP: i64 = 10;
+
+/// How messages are marked up, as set with `TELEGRAM_PARSE_MODE`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ParseModeStrategy {
+    #[default]
+    MarkdownV2,
+    Html,
+    /// No markup at all, for when user content keeps breaking the others.
+    Plain,
+}
+
+impl ParseModeStrategy {
+    /// The parse mode to send messages with, if any.
+    pub fn parse_mode(self) -> Option<ParseMode> {
+        match self {
+            ParseModeStrategy::MarkdownV2 => Some(ParseMode::MarkdownV2),
+            ParseModeStrategy::Html => Some(ParseMode::Html),
+            ParseModeStrategy::Plain => None,
+        }
+    }
+
+    /// Escapes `text` so that it's shown as is.
+    pub fn escape(self, text: &str) -> String {
+        match self {
+            ParseModeStrategy::MarkdownV2 => Event::escape_markdown(text),
+            ParseModeStrategy::Html => escape_html(text),
+            ParseModeStrategy::Plain => text.to_string(),
+        }
+    }
+
+    /// Escapes `text` and shows it in bold.
+    pub fn bold(self, text: &str) -> String {
+        match self {
+            ParseModeStrategy::MarkdownV2 => format!("*{}*", Event::escape_markdown(text)),
+            ParseModeStrategy::Html => format!("<b>{}</b>", escape_html(text)),
+            ParseModeStrategy::Plain => text.to_string(),
+        }
+    }
+
+    /// A link to `url` reading `label`. Plain text shows the URL instead.
+    pub fn link(self, label: &str, url: &str) -> String {
+        match self {
+            ParseModeStrategy::MarkdownV2 => format!(
+                "[{}]({})",
+                Event::escape_markdown(label),
+                Event::escape_markdown_url(url)
+            ),
+            ParseModeStrategy::Html => {
+                format!(
+                    "<a href=\"{}\">{}</a>",
+                    escape_html(url),
+                    escape_html(label)
+                )
+            }
+            ParseModeStrategy::Plain => url.to_string(),
+        }
+    }
+}
+
+impl FromStr for ParseModeStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "markdownv2" | "markdown" => Ok(ParseModeStrategy::MarkdownV2),
+            "html" => Ok(ParseModeStrategy::Html),
+            "plain" | "none" => Ok(ParseModeStrategy::Plain),
+            other => Err(format!("unknown parse mode {other:?}")),
+        }
+    }
+}
+
+/// Escapes the characters that are special in Telegram's HTML.
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Events closer than this get a countdown in minutes rather than hours.
+const COUNTDOWN_MINUTES_THRESHOLD: chrono::Duration = chrono::Duration::hours(1);
+
+/// Events closer than this (and not today) get a countdown in hours rather
+/// than days.
+const COUNTDOWN_HOURS_THRESHOLD: chrono::Duration = chrono::Duration::hours(24);
+
+/// How long before an event starts its attendees can check in.
+const CHECK_IN_WINDOW: chrono::Duration = chrono::Duration::hours(24);
+
+/// The callback data prefix of the check-in button, followed by the event ID.
+pub const CHECK_IN_CALLBACK_PREFIX: &str = "checkin_";
+
+/// The callback data prefix of an event's Edit button, followed by the event ID.
+pub const EDIT_START_CALLBACK_PREFIX: &str = "edit_start_";
+
+/// The callback data prefix of the button shown under past events.
+pub const ENDED_CALLBACK_PREFIX: &str = "ended_";
+
+/// The callback data prefixes of an event's Delete button and of the
+/// buttons confirming or aborting the deletion, followed by the event ID.
+pub const DELETE_CALLBACK_PREFIX: &str = "deleted_";
+pub const CONFIRM_DELETE_CALLBACK_PREFIX: &str = "confirm_delete_";
+pub const CANCEL_DELETE_CALLBACK_PREFIX: &str = "cancel_delete_";
+
+/// An RSVP answer, as stored in `attendees.status`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RsvpStatus {
+    Accepted,
+    Declined,
+    Maybe,
+}
+
+impl RsvpStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RsvpStatus::Accepted => "accepted",
+            RsvpStatus::Declined => "declined",
+            RsvpStatus::Maybe => "maybe",
+        }
+    }
+
+    /// The prefix of the callback data sent by this status' keyboard button.
+    pub fn callback_prefix(&self) -> &'static str {
+        match self {
+            RsvpStatus::Accepted => "accept_",
+            RsvpStatus::Declined => "decline_",
+            RsvpStatus::Maybe => "maybe_",
+        }
+    }
+
+    /// Splits callback data such as `accept_12` into a status and event ID.
+    pub fn from_callback_data(data: &str) -> Option<(Self, i64)> {
+        [
+            RsvpStatus::Accepted,
+            RsvpStatus::Declined,
+            RsvpStatus::Maybe,
+        ]
+        .into_iter()
+        .find_map(|status| {
+            let event_id = data.strip_prefix(status.callback_prefix())?.parse().ok()?;
+            Some((status, event_id))
+        })
+    }
+}
+
+/// How a user is shown in attendee lists.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UserDisplay {
+    /// The first name, followed by the last name if there is one.
+    pub name: String,
+    /// The Telegram username, without the `@`.
+    pub username: Option<String>,
+}
+
+impl From<&str> for UserDisplay {
+    fn from(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            username: None,
+        }
+    }
+}
+
+/// Renders as `Alice Smith (@alice)`, or just the name without a username.
+impl fmt::Display for UserDisplay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.username {
+            Some(username) => write!(f, "{} (@{username})", self.name),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+/// An event as stored in the database, along with its RSVPs.
+/// The attendee lists are left out of the serialized form, see `json`.
+#[derive(Debug, Default, Serialize)]
+pub struct Event {
+    pub id: i64,
+    /// The chat the event was created in.
+    pub chat_id: i64,
+    pub creator: i64,
+    pub name: String,
+    pub description: String,
+    pub location: String,
+    /// The start time in UTC, formatted with `DATETIME_FORMAT`.
+    pub time: String,
+    pub max_capacity: Option<i64>,
+    /// When RSVPs close, in UTC and formatted with `DATETIME_FORMAT`.
+    pub rsvp_deadline: Option<String>,
+    /// The ID of the message the event was posted as in `chat_id`.
+    pub message_id: Option<i32>,
+    /// The `attendee_list_hash` that message shows, in hex, unless the event
+    /// was edited since.
+    #[serde(skip)]
+    pub message_hash: Option<String>,
+    /// When the event was cancelled, if it was.
+    pub cancelled_at: Option<String>,
+    /// Why the event was cancelled, when it wasn't by its creator.
+    pub cancel_reason: Option<String>,
+    /// A link to a signup form, map or video call.
+    pub url: Option<String>,
+    /// Whether only the creator gets to see who RSVPed; others see counts.
+    pub anonymous: bool,
+    /// Lowercase categories such as `sports`, as returned by `parse_tags`.
+    pub tags: Vec<String>,
+    /// How many people each RSVP can be for, the attendee included. 1 means
+    /// no guests.
+    pub guests_per_rsvp: i64,
+    #[serde(skip)]
+    pub accepted: Vec<(i64, UserDisplay)>,
+    #[serde(skip)]
+    pub declined: Vec<(i64, UserDisplay)>,
+    #[serde(skip)]
+    pub maybe: Vec<(i64, UserDisplay)>,
+    #[serde(skip)]
+    pub waitlist: Vec<(i64, UserDisplay)>,
+    /// Why declined users can't come, for those who said.
+    #[serde(skip)]
+    pub decline_reasons: HashMap<i64, String>,
+    /// The accepted and maybe users who checked in.
+    #[serde(skip)]
+    pub checked_in: Vec<i64>,
+    /// How many guests accepted users are bringing, for those bringing any.
+    #[serde(skip)]
+    pub guests: HashMap<i64, i64>,
+}
+
+impl Event {
+    /// Reads an `events` row. The attendee lists are left empty.
+    pub fn from_row(row: &SqliteRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            chat_id: row.try_get("chat_id")?,
+            creator: row.try_get("user_id")?,
+            name: row.try_get("name")?,
+            description: row
+                .try_get::<Option<String>, _>("description")?
+                .unwrap_or_default(),
+            location: row
+                .try_get::<Option<String>, _>("location")?
+                .unwrap_or_default(),
+            time: row
+                .try_get::<Option<String>, _>("time")?
+                .unwrap_or_default(),
+            max_capacity: row.try_get("max_capacity")?,
+            rsvp_deadline: row.try_get("rsvp_deadline")?,
+            message_id: row.try_get("message_id")?,
+            message_hash: row.try_get("message_hash")?,
+            cancelled_at: row.try_get("cancelled_at")?,
+            cancel_reason: row.try_get("cancel_reason")?,
+            url: row.try_get("url")?,
+            anonymous: row.try_get("anonymous")?,
+            tags: row
+                .try_get::<Option<String>, _>("tags")?
+                .map(|tags| parse_tags(&tags))
+                .unwrap_or_default(),
+            guests_per_rsvp: row.try_get("guests_per_rsvp")?,
+            ..Default::default()
+        })
+    }
+
+    /// Hashes who RSVPed how, along with their guests and check-ins, to tell
+    /// whether the event's message needs updating. The order of RSVPs doesn't
+    /// matter.
+    pub fn attendee_list_hash(&self) -> u64 {
+        let sorted_ids = |attendees: &[(i64, UserDisplay)]| {
+            let mut ids: Vec<_> = attendees.iter().map(|(user_id, _)| *user_id).collect();
+            ids.sort_unstable();
+            ids
+        };
+        let mut guests: Vec<_> = self.guests.iter().collect();
+        guests.sort_unstable();
+        let mut checked_in = self.checked_in.clone();
+        checked_in.sort_unstable();
+
+        let mut hasher = DefaultHasher::new();
+        sorted_ids(&self.accepted).hash(&mut hasher);
+        sorted_ids(&self.declined).hash(&mut hasher);
+        sorted_ids(&self.maybe).hash(&mut hasher);
+        sorted_ids(&self.waitlist).hash(&mut hasher);
+        guests.hash(&mut hasher);
+        checked_in.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// The most guests each accepted user can bring.
+    pub fn max_guests(&self) -> i64 {
+        (self.guests_per_rsvp - 1).max(0)
+    }
+
+    /// Shows a stored UTC time in `timezone`.
+    pub(crate) fn format_time(time: &str, timezone: Tz) -> String {
+        match parse_datetime_string(time) {
+            Ok((time, _)) => format!(
+                "{} ({timezone})",
+                utc_to_local(time, timezone).format(DATETIME_FORMAT)
+            ),
+            Err(_) => time.to_string(),
+        }
+    }
+
+    /// Whether RSVPs are closed at `now` (in UTC), because their deadline or
+    /// the event itself has passed.
+    pub fn rsvp_closed(&self, now: NaiveDateTime) -> bool {
+        // Times are stored as DATETIME_FORMAT strings, which sort chronologically.
+        self.rsvp_deadline
+            .as_ref()
+            .is_some_and(|deadline| *deadline <= now.format(DATETIME_FORMAT).to_string())
+            || self.has_started(now)
+    }
+
+    /// Whether attendees can check in at `now` (in UTC), which they can in
+    /// the `CHECK_IN_WINDOW` before a (not cancelled) event starts.
+    pub fn check_in_open(&self, now: NaiveDateTime) -> bool {
+        self.cancelled_at.is_none()
+            && parse_datetime_string(&self.time)
+                .is_ok_and(|(time, _)| time - CHECK_IN_WINDOW <= now && now < time)
+    }
+
+    /// Whether the event has started at `now` (in UTC).
+    pub fn has_started(&self, now: NaiveDateTime) -> bool {
+        parse_datetime_string(&self.time).is_ok_and(|(time, _)| time <= now)
+    }
+
+    /// Renders the event as a MarkdownV2 message, with its time shown in `timezone`.
+    pub fn format_message(&self, timezone: Tz) -> String {
+        self.format_message_for(timezone, None)
+    }
+
+    /// Like `format_message`, for a message only `viewer` sees. Anonymous
+    /// events list attendees by name only for their creator.
+    pub fn format_message_for(&self, timezone: Tz, viewer: Option<i64>) -> String {
+        self.format_message_with(timezone, viewer, ParseModeStrategy::MarkdownV2)
+    }
+
+    /// Renders the event as an HTML message, with its time shown in `timezone`.
+    pub fn format_message_html(&self, timezone: Tz) -> String {
+        self.format_message_with(timezone, None, ParseModeStrategy::Html)
+    }
+
+    /// Like `format_message_for`, marked up for `strategy`.
+    pub fn format_message_with(
+        &self,
+        timezone: Tz,
+        viewer: Option<i64>,
+        strategy: ParseModeStrategy,
+    ) -> String {
+        let mut message = format!("{}\n", strategy.bold(&self.name));
+        match (&self.cancelled_at, &self.cancel_reason) {
+            (Some(_), Some(reason)) => {
+                message.push_str(&format!("🚫 Cancelled: {}\n", strategy.escape(reason)))
+            }
+            (Some(_), None) => message.push_str("🚫 Cancelled\n"),
+            _ => {}
+        }
+
+        if !self.description.is_empty() {
+            message.push_str(&format!("{}\n", strategy.escape(&self.description)));
+        }
+        message.push('\n');
+
+        if !self.location.is_empty() {
+            message.push_str(&format!("📍 {}\n", strategy.escape(&self.location)));
+        }
+
+        if let Some(url) = &self.url {
+            message.push_str(&format!("🔗 {}\n", strategy.link("Link", url)));
+        }
+
+        if !self.tags.is_empty() {
+            message.push_str(&format!("🏷 {}\n", strategy.escape(&self.tags.join(", "))));
+        }
+
+        let time = Self::format_time(&self.time, timezone);
+        message.push_str(&format!("🕒 {}\n", strategy.escape(&time)));
+
+        if let (Ok((time, _)), None) = (parse_datetime_string(&self.time), &self.cancelled_at) {
+            let countdown = countdown(time, Utc::now().naive_utc(), timezone);
+            message.push_str(&format!("🕐 {}\n", strategy.escape(&countdown)));
+        }
+
+        if let Some(deadline) = &self.rsvp_deadline {
+            let deadline = Self::format_time(deadline, timezone);
+            message.push_str(&format!("⏰ RSVP by {}\n", strategy.escape(&deadline)));
+        }
+
+        let mut accepted_count = match self.max_capacity {
+            Some(max_capacity) => format!("{}/{max_capacity}", self.accepted.len()),
+            None => self.accepted.len().to_string(),
+        };
+        let mut accepted_heading = match self.max_capacity {
+            Some(_) => format!("✅ Accepted ({accepted_count})"),
+            None => "✅ Accepted".to_string(),
+        };
+        let guests = self
+            .accepted
+            .iter()
+            .filter_map(|(user_id, _)| self.guests.get(user_id))
+            .sum::<i64>();
+        if guests > 0 {
+            let headcount = format!(" · {} people", self.accepted.len() as i64 + guests);
+            accepted_count.push_str(&headcount);
+            accepted_heading.push_str(&headcount);
+        }
+
+        // Each list with its heading and, for anonymous events, its count.
+        let lists = [
+            (
+                accepted_heading,
+                format!("✅ Accepted: {accepted_count}"),
+                &self.accepted,
+            ),
+            (
+                "⏳ Waitlist".to_string(),
+                format!("⏳ Waitlist: {}", self.waitlist.len()),
+                &self.waitlist,
+            ),
+            (
+                "❌ Declined".to_string(),
+                format!("❌ Declined: {}", self.declined.len()),
+                &self.declined,
+            ),
+            (
+                "❓ Maybe".to_string(),
+                format!("❓ Maybe: {}", self.maybe.len()),
+                &self.maybe,
+            ),
+        ];
+
+        // Once the event has started, check-ins are only counted, at the end.
+        let started = self.has_started(Utc::now().naive_utc());
+        let attended = match self.checked_in.len() {
+            _ if !started => String::new(),
+            0 => String::new(),
+            1 => format!("\n{}\n", strategy.escape("👥 1 person attended")),
+            n => format!(
+                "\n{}\n",
+                strategy.escape(&format!("👥 {n} people attended"))
+            ),
+        };
+
+        if self.anonymous && viewer != Some(self.creator) {
+            let mut counts = lists
+                .iter()
+                .filter(|(_, _, attendees)| !attendees.is_empty())
+                .map(|(_, count, _)| count.clone())
+                .collect::<Vec<_>>();
+            if !started && !self.checked_in.is_empty() {
+                counts.push(format!("✔️ Checked in: {}", self.checked_in.len()));
+            }
+            if !counts.is_empty() {
+                message.push('\n');
+            }
+            for count in counts {
+                message.push_str(&format!("{}\n", strategy.escape(&count)));
+            }
+            message.push_str(&attended);
+            return message;
+        }
+
+        let checked_in = self
+            .accepted
+            .iter()
+            .chain(&self.maybe)
+            .filter(|(user_id, _)| !started && self.checked_in.contains(user_id))
+            .cloned()
+            .collect::<Vec<_>>();
+        let lists = lists
+            .into_iter()
+            .map(|(heading, _, attendees)| (heading, attendees))
+            .chain([("✔️ Checked in".to_string(), &checked_in)]);
+
+        for (heading, attendees) in lists {
+            if attendees.is_empty() {
+                continue;
+            }
+
+            message.push_str(&format!("\n{}\n", strategy.bold(&heading)));
+            for (user_id, name) in attendees {
+                let mut line = name.to_string();
+                if let Some(reason) = self.decline_reasons.get(user_id) {
+                    line.push_str(&format!(" — {reason}"));
+                }
+                if let Some(guests) = self.guests.get(user_id) {
+                    line.push_str(&format!(" (+{guests})"));
+                }
+                message.push_str(&format!("• {}\n", strategy.escape(&line)));
+            }
+        }
+        message.push_str(&attended);
+
+        message
+    }
+
+    /// The RSVP buttons shown under the event message, and on the day of
+    /// the event a check-in button. The `owner_view`, for the creator's
+    /// private chat, adds Edit and Delete buttons.
+    pub fn create_keyboard(&self, owner_view: bool) -> InlineKeyboardMarkup {
+        let button = |text: &str, status: RsvpStatus| {
+            InlineKeyboardButton::builder()
+                .text(text)
+                .callback_data(format!("{}{}", status.callback_prefix(), self.id))
+                .build()
+        };
+
+        let mut rows = vec![vec![
+            button("✅ Accept", RsvpStatus::Accepted),
+            button("❌ Decline", RsvpStatus::Declined),
+            button("❓ Maybe", RsvpStatus::Maybe),
+        ]];
+        if self.check_in_open(Utc::now().naive_utc()) {
+            rows.push(vec![InlineKeyboardButton::builder()
+                .text("✔️ Check in")
+                .callback_data(format!("{CHECK_IN_CALLBACK_PREFIX}{}", self.id))
+                .build()]);
+        }
+        if owner_view {
+            rows.push(vec![
+                InlineKeyboardButton::builder()
+                    .text("✏️ Edit")
+                    .callback_data(format!("{EDIT_START_CALLBACK_PREFIX}{}", self.id))
+                    .build(),
+                InlineKeyboardButton::builder()
+                    .text("🗑️ Delete")
+                    .callback_data(format!("{DELETE_CALLBACK_PREFIX}{}", self.id))
+                    .build(),
+            ]);
+        }
+
+        InlineKeyboardMarkup::builder()
+            .inline_keyboard(rows)
+            .build()
+    }
+
+    /// The single, informational button shown under an event that has taken
+    /// place, in place of the RSVP buttons.
+    pub fn ended_keyboard(&self) -> InlineKeyboardMarkup {
+        let button = InlineKeyboardButton::builder()
+            .text(format!("🏁 Ended · {} went", self.accepted.len()))
+            .callback_data(format!("{ENDED_CALLBACK_PREFIX}{}", self.id))
+            .build();
+
+        InlineKeyboardMarkup::builder()
+            .inline_keyboard(vec![vec![button]])
+            .build()
+    }
+
+    /// The buttons asking the creator to confirm deleting the event.
+    pub fn delete_confirmation_keyboard(&self) -> InlineKeyboardMarkup {
+        let button = |text: &str, prefix: &str| {
+            InlineKeyboardButton::builder()
+                .text(text)
+                .callback_data(format!("{prefix}{}", self.id))
+                .build()
+        };
+
+        InlineKeyboardMarkup::builder()
+            .inline_keyboard(vec![vec![
+                button("✅ Yes, delete", CONFIRM_DELETE_CALLBACK_PREFIX),
+                button("🚫 No, keep it", CANCEL_DELETE_CALLBACK_PREFIX),
+            ]])
+            .build()
+    }
+
+    /// Escapes user-supplied text for use in a MarkdownV2 message.
+    pub fn escape_markdown(text: &str) -> String {
+        let mut escaped = String::with_capacity(text.len());
+        for c in text.chars() {
+            if MARKDOWN_SPECIAL_CHARS.contains(&c) {
+                escaped.push('\\');
+            }
+            escaped.push(c);
+        }
+        escaped
+    }
+
+    /// Escapes the URL of a MarkdownV2 inline link, where only `)` and `\`
+    /// are special.
+    pub fn escape_markdown_url(url: &str) -> String {
+        url.replace('\\', "\\\\").replace(')', "\\)")
+    }
+}
+
+/// Whether `text` looks like a link we can show as one.
+pub fn is_valid_url(text: &str) -> bool {
+    (text.starts_with("http://") || text.starts_with("https://"))
+        && !text.contains(char::is_whitespace)
+}
+
+/// Splits comma-separated tags, such as `Sports, social`, into trimmed and
+/// lowercased tags without empty ones or duplicates.
+pub fn parse_tags(text: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in text.split(',') {
+        let tag = sanitize_text(tag).to_lowercase();
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+/// Describes how far `now` is from an event starting at `time` (both in
+/// UTC), e.g. "In 3 days" or "Today at 19:00" in `timezone`.
+pub fn countdown(time: NaiveDateTime, now: NaiveDateTime, timezone: Tz) -> String {
+    let delta = time - now;
+    let plural = |count: i64, unit: &str| {
+        if count == 1 {
+            format!("In 1 {unit}")
+        } else {
+            format!("In {count} {unit}s")
+        }
+    };
+
+    if delta < chrono::Duration::zero() {
+        "(event has passed)".to_string()
+    } else if delta < COUNTDOWN_MINUTES_THRESHOLD {
+        plural(delta.num_minutes().max(1), "minute")
+    } else if utc_to_local(time, timezone).date() == utc_to_local(now, timezone).date() {
+        format!("Today at {}", utc_to_local(time, timezone).format("%H:%M"))
+    } else if delta < COUNTDOWN_HOURS_THRESHOLD {
+        plural(delta.num_hours(), "hour")
+    } else {
+        plural(delta.num_days(), "day")
+    }
+}
+
+/// The few columns of an event needed to compare it with others, without
+/// its RSVPs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventSummary {
+    pub id: i64,
+    pub name: String,
+    /// The start time in UTC, formatted with `DATETIME_FORMAT`.
+    pub time: String,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct EventDraft {
+    pub name: String,
+    pub description: String,
+    pub location: String,
+    pub time: String,
+    /// The most attendees that can accept; `None` means unlimited.
+    pub max_capacity: Option<i64>,
+    /// When RSVPs close, in UTC; `None` means they stay open.
+    pub rsvp_deadline: Option<String>,
+    pub url: Option<String>,
+    pub anonymous: bool,
+    pub tags: Vec<String>,
+    /// How many people each RSVP can be for, the attendee included.
+    pub guests_per_rsvp: i64,
+}
+
+impl EventDraft {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Cleans up pasted input before the draft is stored: trims every field,
+    /// normalises line endings to `\n`, strips control characters other than
+    /// newlines and tabs, and collapses runs of blank lines to a single one.
+    pub fn sanitize(&mut self) {
+        for field in [
+            &mut self.name,
+            &mut self.description,
+            &mut self.location,
+            &mut self.time,
+        ] {
+            *field = sanitize_text(field);
+        }
+    }
+}
+
+/// The cleanup `EventDraft::sanitize` applies to each field. Trailing
+/// whitespace is dropped from every line, so lines of only spaces count as
+/// blank too.
+pub fn sanitize_text(text: &str) -> String {
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.split('\n') {
+        let line = line
+            .chars()
+            .filter(|&c| !c.is_control() || c == '\t')
+            .collect::<String>();
+        let line = line.trim_end();
+        if line.is_empty() && lines.last().is_some_and(|last| last.is_empty()) {
+            continue;
+        }
+        lines.push(line.to_string());
+    }
+
+    lines.join("\n").trim().to_string()
+}
+
+/// Returns how many characters `text` is over `max_len`, once sanitized.
+pub fn excess_length(text: &str, max_len: usize) -> Option<usize> {
+    let len = sanitize_text(text).chars().count();
+    (len > max_len).then(|| len - max_len)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[allow(clippy::enum_variant_names)]
+pub enum UserState {
+    AwaitingName,
+    AwaitingDescription,
+    AwaitingLocation,
+    AwaitingTime,
+    AwaitingUrl,
+    AwaitingTags,
+    AwaitingCapacity,
+    AwaitingGuestPolicy,
+    AwaitingDeadline,
+    AwaitingAnonymous,
+}
+
+impl UserState {
+    const ALL: [UserState; 10] = [
+        UserState::AwaitingName,
+        UserState::AwaitingDescription,
+        UserState::AwaitingLocation,
+        UserState::AwaitingTime,
+        UserState::AwaitingUrl,
+        UserState::AwaitingTags,
+        UserState::AwaitingCapacity,
+        UserState::AwaitingGuestPolicy,
+        UserState::AwaitingDeadline,
+        UserState::AwaitingAnonymous,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UserState::AwaitingName => "name",
+            UserState::AwaitingDescription => "description",
+            UserState::AwaitingLocation => "location",
+            UserState::AwaitingTime => "time",
+            UserState::AwaitingUrl => "url",
+            UserState::AwaitingTags => "tags",
+            UserState::AwaitingCapacity => "capacity",
+            UserState::AwaitingGuestPolicy => "guest_policy",
+            UserState::AwaitingDeadline => "deadline",
+            UserState::AwaitingAnonymous => "anonymous",
+        }
+    }
+
+    /// The inverse of `as_str`.
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|state| state.as_str() == s)
+    }
+}
+
+/// A user's event creation in progress.
+#[derive(Debug)]
+pub struct EventContext {
+    pub state: UserState,
+    pub draft: EventDraft,
+    /// The timezone times are entered in, from the group's settings. `None`
+    /// means UTC.
+    pub timezone: Option<Tz>,
+    pub created_at: Instant,
+    /// For a `/clone`, the chat of the original event, which the copy is
+    /// posted to. Clones only ask for a new time.
+    pub clone_chat_id: Option<i64>,
+    /// The `/start` message the draft was begun with, and the chat it's in,
+    /// for the event to be posted as a reply to. Not kept across restarts.
+    pub origin_message_id: Option<i32>,
+    pub origin_message_chat_id: Option<i64>,
+}
+
+impl EventContext {
+    pub fn new(timezone: Option<Tz>) -> Self {
+        Self {
+            state: UserState::AwaitingName,
+            draft: EventDraft::new(),
+            timezone,
+            created_at: Instant::now(),
+            clone_chat_id: None,
+            origin_message_id: None,
+            origin_message_chat_id: None,
+        }
+    }
+
+    /// Starts a copy of `event` for its chat, with everything but the time
+    /// filled in.
+    pub fn clone_of(event: &Event, timezone: Option<Tz>) -> Self {
+        Self {
+            state: UserState::AwaitingTime,
+            draft: EventDraft {
+                name: event.name.clone(),
+                description: event.description.clone(),
+                location: event.location.clone(),
+                time: String::new(),
+                max_capacity: event.max_capacity,
+                rsvp_deadline: None,
+                url: event.url.clone(),
+                anonymous: event.anonymous,
+                tags: event.tags.clone(),
+                guests_per_rsvp: event.guests_per_rsvp,
+            },
+            timezone,
+            created_at: Instant::now(),
+            clone_chat_id: Some(event.chat_id),
+            origin_message_id: None,
+            origin_message_chat_id: None,
+        }
+    }
+}
+
+/// A field of an existing event that can be changed with `/edit`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EventField {
+    Title,
+    Description,
+    Location,
+    Time,
+}
+
+impl EventField {
+    /// Parses the field name a user replies with, e.g. `title`.
+    pub fn parse(text: &str) -> Option<Self> {
+        match text.trim().to_lowercase().as_str() {
+            "title" | "name" => Some(EventField::Title),
+            "description" => Some(EventField::Description),
+            "location" => Some(EventField::Location),
+            "time" => Some(EventField::Time),
+            _ => None,
+        }
+    }
+
+    /// The longest value the field can have, in characters.
+    pub fn max_len(&self) -> Option<usize> {
+        match self {
+            EventField::Title => Some(MAX_TITLE_LEN),
+            EventField::Description => Some(MAX_DESCRIPTION_LEN),
+            EventField::Location => Some(MAX_LOCATION_LEN),
+            EventField::Time => None,
+        }
+    }
+
+    /// The `events` column holding this field.
+    pub fn column(&self) -> &'static str {
+        match self {
+            EventField::Title => "name",
+            EventField::Description => "description",
+            EventField::Location => "location",
+            EventField::Time => "time",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            EventField::Title => "title",
+            EventField::Description => "description",
+            EventField::Location => "location",
+            EventField::Time => "time",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum EventEditState {
+    SelectField,
+    Editing(EventField),
+}
+
+/// A user's edit of an existing event in progress.
+#[derive(Debug)]
+pub struct EventEditContext {
+    pub event_id: i64,
+    pub state: EventEditState,
+}
+
+impl EventEditContext {
+    pub fn new(event_id: i64) -> Self {
+        Self {
+            event_id,
+            state: EventEditState::SelectField,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sanitize_trims_fields() {
+        let mut draft = EventDraft {
+            name: "  Board games \t".to_string(),
+            description: "\n\nBring snacks\n".to_string(),
+            location: " Library ".to_string(),
+            time: "Friday 19:00\r\n".to_string(),
+            max_capacity: None,
+            rsvp_deadline: None,
+            url: None,
+            anonymous: false,
+            tags: Vec::new(),
+            guests_per_rsvp: 1,
+        };
+        draft.sanitize();
+
+        assert_eq!(draft.name, "Board games");
+        assert_eq!(draft.description, "Bring snacks");
+        assert_eq!(draft.location, "Library");
+        assert_eq!(draft.time, "Friday 19:00");
+    }
+
+    #[test]
+    fn links_are_validated_and_escaped() {
+        assert!(is_valid_url("https://example.com/signup"));
+        assert!(!is_valid_url("example.com"));
+        assert!(!is_valid_url("https://example.com/a b"));
+
+        let event = Event {
+            url: Some("https://en.wikipedia.org/wiki/Go_(game)".to_string()),
+            ..Default::default()
+        };
+        assert!(event
+            .format_message(Tz::UTC)
+            .contains("🔗 [Link](https://en.wikipedia.org/wiki/Go_(game\\))\n"));
+    }
+
+    #[test]
+    fn escape_markdown_escapes_every_special_character() {
+        for c in MARKDOWN_SPECIAL_CHARS {
+            assert_eq!(Event::escape_markdown(&c.to_string()), format!("\\{c}"));
+        }
+        assert_eq!(Event::escape_markdown("Hello, world"), "Hello, world");
+    }
+
+    #[test]
+    fn escape_markdown_keeps_line_breaks() {
+        assert_eq!(
+            Event::escape_markdown("Bring:\n- snacks\n\nThanks!"),
+            "Bring:\n\\- snacks\n\nThanks\\!"
+        );
+    }
+
+    #[test]
+    fn escape_markdown_shows_escaped_looking_text_as_typed() {
+        // A backslash the user typed is escaped too, rather than taken as
+        // escaping what follows it.
+        assert_eq!(Event::escape_markdown(r"1\.5"), r"1\\\.5");
+        assert_eq!(Event::escape_markdown(r"\\"), r"\\\\");
+    }
+
+    #[test]
+    fn format_message_lists_attendees() {
+        let event = Event {
+            name: "Board games".to_string(),
+            description: "Bring snacks!".to_string(),
+            location: "Room 1.2".to_string(),
+            time: "TBA".to_string(),
+            accepted: vec![(2, "Alice".into()), (3, "Bob".into())],
+            declined: vec![(4, "Carol".into())],
+            decline_reasons: HashMap::from([(4, "Busy".to_string())]),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            event.format_message(Tz::UTC),
+            "*Board games*\nBring snacks\\!\n\n📍 Room 1\\.2\n🕒 TBA\n\n*✅ Accepted*\n• Alice\n• Bob\n\n*❌ Declined*\n• Carol — Busy\n"
+        );
+    }
+
+    #[test]
+    fn format_message_without_attendees() {
+        let event = Event {
+            name: "Board games".to_string(),
+            time: "TBA".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(event.format_message(Tz::UTC), "*Board games*\n\n🕒 TBA\n");
+    }
+
+    #[test]
+    fn keyboard_only_has_rsvp_buttons() {
+        let event = Event {
+            id: 7,
+            creator: 1,
+            ..Default::default()
+        };
+
+        let keyboard = event.create_keyboard(false);
+        let callbacks: Vec<_> = keyboard.inline_keyboard[0]
+            .iter()
+            .map(|button| button.callback_data.as_deref().unwrap())
+            .collect();
+        assert_eq!(callbacks, ["accept_7", "decline_7", "maybe_7"]);
+    }
+
+    #[test]
+    fn owner_keyboard_adds_edit_and_delete() {
+        let event = Event {
+            id: 7,
+            creator: 1,
+            ..Default::default()
+        };
+
+        let keyboard = event.create_keyboard(true);
+        assert_eq!(keyboard.inline_keyboard.len(), 2);
+        let callbacks: Vec<_> = keyboard.inline_keyboard[1]
+            .iter()
+            .map(|button| button.callback_data.clone().unwrap())
+            .collect();
+        assert_eq!(
+            callbacks,
+            [
+                format!("{EDIT_START_CALLBACK_PREFIX}7"),
+                format!("{DELETE_CALLBACK_PREFIX}7")
+            ]
+        );
+    }
+
+    #[test]
+    fn check_ins_open_on_the_day_and_are_counted_afterwards() {
+        let now = Utc::now().naive_utc();
+        let at = |offset: chrono::Duration| (now + offset).format(DATETIME_FORMAT).to_string();
+        let mut event = Event {
+            id: 7,
+            time: at(chrono::Duration::hours(3)),
+            accepted: vec![(2, "Alice".into()), (3, "Bob".into())],
+            checked_in: vec![2],
+            ..Default::default()
+        };
+
+        assert!(event.check_in_open(now));
+        assert_eq!(
+            event.create_keyboard(false).inline_keyboard[1][0]
+                .callback_data
+                .as_deref(),
+            Some("checkin_7")
+        );
+        assert!(event
+            .format_message(Tz::UTC)
+            .ends_with("\n*✔️ Checked in*\n• Alice\n"));
+
+        event.time = at(chrono::Duration::days(3));
+        assert!(!event.check_in_open(now));
+        assert_eq!(event.create_keyboard(false).inline_keyboard.len(), 1);
+
+        event.time = at(-chrono::Duration::hours(3));
+        event.checked_in.push(3);
+        assert!(!event.check_in_open(now));
+        let message = event.format_message(Tz::UTC);
+        assert!(message.ends_with("• Bob\n\n👥 2 people attended\n"));
+        assert!(!message.contains("Checked in"));
+    }
+
+    #[test]
+    fn html_messages_escape_user_content() {
+        let event = Event {
+            name: "Fish & <chips>".to_string(),
+            url: Some("https://example.com/?a=1&b=2".to_string()),
+            accepted: vec![(2, "Alice".into())],
+            ..Default::default()
+        };
+
+        let html = event.format_message_html(Tz::UTC);
+        assert!(html.starts_with("<b>Fish &amp; &lt;chips&gt;</b>\n"));
+        assert!(html.contains("🔗 <a href=\"https://example.com/?a=1&amp;b=2\">Link</a>\n"));
+        assert!(html.contains("\n<b>✅ Accepted</b>\n• Alice\n"));
+
+        let plain = event.format_message_with(Tz::UTC, None, ParseModeStrategy::Plain);
+        assert!(plain.starts_with("Fish & <chips>\n"));
+        assert!(plain.contains("🔗 https://example.com/?a=1&b=2\n"));
+    }
+
+    #[test]
+    fn anonymous_events_show_names_only_to_their_creator() {
+        let event = Event {
+            creator: 1,
+            anonymous: true,
+            max_capacity: Some(10),
+            accepted: vec![(2, "Alice".into()), (3, "Bob".into())],
+            maybe: vec![(4, "Carol".into())],
+            ..Default::default()
+        };
+
+        let public = event.format_message(Tz::UTC);
+        assert!(public.contains("✅ Accepted: 2/10\n❓ Maybe: 1\n"));
+        assert!(!public.contains("Alice"));
+        assert!(!event.format_message_for(Tz::UTC, Some(2)).contains("Alice"));
+        assert!(event.format_message_for(Tz::UTC, Some(1)).contains("Alice"));
+    }
+
+    #[test]
+    fn guests_count_towards_the_headcount() {
+        let mut event = Event {
+            guests_per_rsvp: 3,
+            max_capacity: Some(10),
+            accepted: vec![(2, "Alice".into()), (3, "Bob".into())],
+            ..Default::default()
+        };
+        event.guests.insert(2, 2);
+
+        let message = event.format_message_with(Tz::UTC, None, ParseModeStrategy::Plain);
+        assert!(message.contains("✅ Accepted (2/10) · 4 people\n• Alice (+2)\n• Bob\n"));
+        event.anonymous = true;
+        let message = event.format_message_with(Tz::UTC, None, ParseModeStrategy::Plain);
+        assert!(message.contains("✅ Accepted: 2/10 · 4 people\n"));
+    }
+
+    #[test]
+    fn tags_are_normalised() {
+        assert_eq!(
+            parse_tags(" Sports, social,,sports , Board games"),
+            ["sports", "social", "board games"]
+        );
+        assert!(parse_tags(" , ").is_empty());
+    }
+
+    #[test]
+    fn sanitize_normalises_line_endings() {
+        assert_eq!(sanitize_text("one\r\ntwo\rthree"), "one\ntwo\nthree");
+    }
+
+    #[test]
+    fn sanitize_collapses_blank_lines() {
+        assert_eq!(sanitize_text("one\n\n\n\n\ntwo"), "one\n\ntwo");
+        assert_eq!(sanitize_text("one\r\n\r\n\r\ntwo"), "one\n\ntwo");
+        assert_eq!(sanitize_text("one\n\ntwo"), "one\n\ntwo");
+        assert_eq!(sanitize_text("one  \n \n\t\n  two"), "one\n\n  two");
+    }
+
+    #[test]
+    fn sanitize_keeps_single_line_breaks() {
+        assert_eq!(
+            sanitize_text("\n  Bring:\r\n- snacks\n- games\n\n"),
+            "Bring:\n- snacks\n- games"
+        );
+    }
+
+    #[test]
+    fn sanitize_strips_control_characters() {
+        assert_eq!(sanitize_text("Pi\u{0}c\u{7}nic\u{1b}"), "Picnic");
+        assert_eq!(sanitize_text("a\tb\u{7f}"), "a\tb");
+        assert_eq!(sanitize_text("one\n\u{0}\n\u{0}\ntwo"), "one\n\ntwo");
+    }
+
+    #[test]
+    fn callback_data_round_trips() {
+        for status in [
+            RsvpStatus::Accepted,
+            RsvpStatus::Declined,
+            RsvpStatus::Maybe,
+        ] {
+            let data = format!("{}42", status.callback_prefix());
+            assert_eq!(RsvpStatus::from_callback_data(&data), Some((status, 42)));
+        }
+
+        assert_eq!(RsvpStatus::from_callback_data("accept_"), None);
+        assert_eq!(RsvpStatus::from_callback_data("attend_42"), None);
+    }
+
+    #[test]
+    fn countdown_picks_a_readable_unit() {
+        let at = |text| parse_datetime_string(text).unwrap().0;
+        let now = at("2025-08-15 12:00");
+        let toronto: Tz = "America/Toronto".parse().unwrap();
+
+        assert_eq!(
+            countdown(at("2025-08-15 11:59"), now, Tz::UTC),
+            "(event has passed)"
+        );
+        assert_eq!(
+            countdown(at("2025-08-15 12:00"), now, Tz::UTC),
+            "In 1 minute"
+        );
+        assert_eq!(
+            countdown(at("2025-08-15 12:45"), now, Tz::UTC),
+            "In 45 minutes"
+        );
+        assert_eq!(
+            countdown(at("2025-08-15 19:00"), now, Tz::UTC),
+            "Today at 19:00"
+        );
+        assert_eq!(
+            countdown(at("2025-08-15 19:00"), now, toronto),
+            "Today at 15:00"
+        );
+        assert_eq!(
+            countdown(at("2025-08-16 04:00"), now, Tz::UTC),
+            "In 16 hours"
+        );
+        assert_eq!(
+            countdown(at("2025-08-16 03:00"), now, toronto),
+            "Today at 23:00"
+        );
+        assert_eq!(countdown(at("2025-08-18 19:00"), now, Tz::UTC), "In 3 days");
+    }
+
+    #[test]
+    fn attendee_list_hash_ignores_rsvp_order() {
+        let event = |accepted: Vec<(i64, UserDisplay)>| Event {
+            accepted,
+            ..Default::default()
+        };
+        let ada = || (1, UserDisplay::from("Ada"));
+        let grace = || (2, UserDisplay::from("Grace"));
+
+        assert_eq!(
+            event(vec![ada(), grace()]).attendee_list_hash(),
+            event(vec![grace(), ada()]).attendee_list_hash()
+        );
+        assert_ne!(
+            event(vec![ada()]).attendee_list_hash(),
+            event(vec![ada(), grace()]).attendee_list_hash()
+        );
+        let mut declined = event(vec![ada()]);
+        declined.declined = declined.accepted.split_off(0);
+        assert_ne!(
+            declined.attendee_list_hash(),
+            event(vec![ada()]).attendee_list_hash()
+        );
+    }
+}
@@ -0,0 +1,72 @@
+//! Serializes an event's attendees as an RFC 4180 CSV file, for organizers'
+//! own records.
+
+use super::Event;
+
+/// Renders the attendees of `event` as `name,status,reason` rows, accepted
+/// first, then waitlisted, declined and maybe.
+pub fn to_csv(event: &Event) -> String {
+    let mut csv = "name,status,reason\r\n".to_string();
+
+    for (status, attendees) in [
+        ("accepted", &event.accepted),
+        ("waitlist", &event.waitlist),
+        ("declined", &event.declined),
+        ("maybe", &event.maybe),
+    ] {
+        for (user_id, name) in attendees {
+            let reason = event
+                .decline_reasons
+                .get(user_id)
+                .map(String::as_str)
+                .unwrap_or_default();
+            csv.push_str(&format!(
+                "{},{status},{}\r\n",
+                escape_field(&name.name),
+                escape_field(reason)
+            ));
+        }
+    }
+
+    csv
+}
+
+/// Quotes a field if needed. Fields that spreadsheets would run as a formula
+/// get a leading `'`.
+fn escape_field(field: &str) -> String {
+    let field = if field.starts_with(['=', '+', '-', '@']) {
+        format!("'{field}")
+    } else {
+        field.to_string()
+    };
+
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exports_attendees_with_reasons() {
+        let mut event = Event {
+            accepted: vec![(1, "Ada".into())],
+            declined: vec![(2, "Grace \"Amazing\" Hopper".into())],
+            maybe: vec![(3, "=cmd".into())],
+            ..Default::default()
+        };
+        event.decline_reasons.insert(2, "Busy, sorry".to_string());
+
+        assert_eq!(
+            to_csv(&event),
+            "name,status,reason\r\n\
+             Ada,accepted,\r\n\
+             \"Grace \"\"Amazing\"\" Hopper\",declined,\"Busy, sorry\"\r\n\
+             '=cmd,maybe,\r\n"
+        );
+    }
+}
@@ -0,0 +1,116 @@
+//! Serializes events as RFC 5545 iCalendar files, for importing into
+//! calendar apps.
+
+use super::Event;
+use crate::util::DATETIME_FORMAT;
+use chrono::NaiveDateTime;
+
+/// The format of UTC date-times in iCalendar, e.g. `20250815T190000Z`.
+const ICAL_DATETIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// How long exported events last, as the bot doesn't store an end time.
+const EVENT_DURATION: chrono::Duration = chrono::Duration::hours(1);
+
+/// Renders `event` as a calendar with a single VEVENT. `now` (in UTC) is
+/// recorded as the time the file was created.
+pub fn to_ical(event: &Event, now: NaiveDateTime) -> Result<String, chrono::ParseError> {
+    let start = NaiveDateTime::parse_from_str(&event.time, DATETIME_FORMAT)?;
+    let end = start + EVENT_DURATION;
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//televent//EN".to_string(),
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:event-{}@televent", event.id),
+        format!("DTSTAMP:{}", now.format(ICAL_DATETIME_FORMAT)),
+        format!("DTSTART:{}", start.format(ICAL_DATETIME_FORMAT)),
+        format!("DTEND:{}", end.format(ICAL_DATETIME_FORMAT)),
+        format!("SUMMARY:{}", escape_text(&event.name)),
+    ];
+    if !event.description.is_empty() {
+        lines.push(format!("DESCRIPTION:{}", escape_text(&event.description)));
+    }
+    if !event.location.is_empty() {
+        lines.push(format!("LOCATION:{}", escape_text(&event.location)));
+    }
+    lines.push("END:VEVENT".to_string());
+    lines.push("END:VCALENDAR".to_string());
+
+    Ok(lines.iter().map(|line| fold_line(line) + "\r\n").collect())
+}
+
+/// Escapes the characters that are special in iCalendar text values.
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Splits a content line into lines of at most 75 bytes, continuing each
+/// with a leading space, without breaking up UTF-8 characters.
+fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exports_a_one_hour_event() {
+        let event = Event {
+            id: 7,
+            name: "Board games, snacks".to_string(),
+            location: "Library; room 2".to_string(),
+            time: "2025-08-15 19:00".to_string(),
+            ..Default::default()
+        };
+        let now = NaiveDateTime::parse_from_str("2025-08-01 12:00", DATETIME_FORMAT).unwrap();
+
+        assert_eq!(
+            to_ical(&event, now).unwrap(),
+            "BEGIN:VCALENDAR\r\n\
+             VERSION:2.0\r\n\
+             PRODID:-//televent//EN\r\n\
+             BEGIN:VEVENT\r\n\
+             UID:event-7@televent\r\n\
+             DTSTAMP:20250801T120000Z\r\n\
+             DTSTART:20250815T190000Z\r\n\
+             DTEND:20250815T200000Z\r\n\
+             SUMMARY:Board games\\, snacks\r\n\
+             LOCATION:Library\\; room 2\r\n\
+             END:VEVENT\r\n\
+             END:VCALENDAR\r\n"
+        );
+    }
+
+    #[test]
+    fn long_lines_are_folded() {
+        let line = format!("DESCRIPTION:{}", "é".repeat(40));
+        let folded = fold_line(&line);
+
+        assert!(folded.split("\r\n").all(|part| part.len() <= 75));
+        assert_eq!(folded.replace("\r\n ", ""), line);
+    }
+}
@@ -0,0 +1,116 @@
+//! Serializes a group's events and their attendees as a JSON document, for
+//! backups and moving to another chat.
+
+use serde::Serialize;
+
+use super::Event;
+
+/// One RSVP to an event.
+#[derive(Debug, Serialize)]
+pub struct AttendeeRecord {
+    pub user_id: i64,
+    pub name: String,
+    pub username: Option<String>,
+    /// `accepted`, `waitlist`, `declined` or `maybe`.
+    pub status: &'static str,
+    /// Why the user can't come, if they declined and said.
+    pub reason: Option<String>,
+    pub checked_in: bool,
+    /// How many guests the user is bringing along.
+    pub guests: i64,
+}
+
+#[derive(Serialize)]
+struct EventRecord<'a> {
+    #[serde(flatten)]
+    event: &'a Event,
+    attendees: Vec<AttendeeRecord>,
+}
+
+/// Lists the RSVPs to `event`, accepted first, then waitlisted, declined and
+/// maybe.
+fn attendee_records(event: &Event) -> Vec<AttendeeRecord> {
+    let mut records = Vec::new();
+    for (status, attendees) in [
+        ("accepted", &event.accepted),
+        ("waitlist", &event.waitlist),
+        ("declined", &event.declined),
+        ("maybe", &event.maybe),
+    ] {
+        for (user_id, name) in attendees {
+            records.push(AttendeeRecord {
+                user_id: *user_id,
+                name: name.name.clone(),
+                username: name.username.clone(),
+                status,
+                reason: event.decline_reasons.get(user_id).cloned(),
+                checked_in: event.checked_in.contains(user_id),
+                guests: event.guests.get(user_id).copied().unwrap_or_default(),
+            });
+        }
+    }
+
+    records
+}
+
+/// Renders `events`, each with an `attendees` array, as pretty-printed JSON.
+pub fn to_json(events: &[Event]) -> serde_json::Result<String> {
+    let records: Vec<_> = events
+        .iter()
+        .map(|event| EventRecord {
+            event,
+            attendees: attendee_records(event),
+        })
+        .collect();
+
+    serde_json::to_string_pretty(&records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exports_events_with_attendees() {
+        let mut event = Event {
+            id: 7,
+            name: "Picnic".to_string(),
+            tags: vec!["outdoor".to_string()],
+            accepted: vec![(1, "Ada".into())],
+            declined: vec![(2, "Grace".into())],
+            checked_in: vec![1],
+            ..Default::default()
+        };
+        event.decline_reasons.insert(2, "Busy".to_string());
+
+        let json: serde_json::Value = serde_json::from_str(&to_json(&[event]).unwrap()).unwrap();
+
+        assert_eq!(json[0]["id"], 7);
+        assert_eq!(json[0]["name"], "Picnic");
+        assert_eq!(json[0]["tags"], serde_json::json!(["outdoor"]));
+        assert!(json[0].get("accepted").is_none());
+        assert_eq!(
+            json[0]["attendees"],
+            serde_json::json!([
+                {
+                    "user_id": 1,
+                    "name": "Ada",
+                    "username": null,
+                    "status": "accepted",
+                    "reason": null,
+                    "checked_in": true,
+                    "guests": 0,
+                },
+                {
+                    "user_id": 2,
+                    "name": "Grace",
+                    "username": null,
+                    "status": "declined",
+                    "reason": "Busy",
+                    "checked_in": false,
+                    "guests": 0,
+                },
+            ])
+        );
+    }
+}
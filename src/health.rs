@@ -0,0 +1,118 @@
+use crate::config::Config;
+use crate::db;
+use sqlx::SqlitePool;
+use std::sync::Arc;
+use tokio::sync::{watch, Notify};
+use tracing::{error, info};
+
+/// How many health checks in a row may fail before the bot shuts down.
+pub const MAX_HEALTH_CHECK_FAILURES: u32 = 3;
+
+/// Periodically pings the database, and reopens it when a ping fails.
+///
+/// Reopened pools are published to everyone who subscribed, so that stale
+/// connections are dropped everywhere at once.
+pub struct HealthCheck {
+    config: Config,
+    pool: SqlitePool,
+    pools: watch::Sender<SqlitePool>,
+    failures: u32,
+}
+
+impl HealthCheck {
+    pub fn new(config: Config, pool: SqlitePool) -> Self {
+        let (pools, _) = watch::channel(pool.clone());
+        Self {
+            config,
+            pool,
+            pools,
+            failures: 0,
+        }
+    }
+
+    /// Returns a receiver of the pools opened to replace a failed one.
+    pub fn subscribe(&self) -> watch::Receiver<SqlitePool> {
+        self.pools.subscribe()
+    }
+
+    /// Checks the database every `HEALTH_CHECK_INTERVAL_SECS`. After
+    /// `MAX_HEALTH_CHECK_FAILURES` failed checks in a row, notifies `stop`
+    /// and returns.
+    pub async fn run(mut self, stop: Arc<Notify>) {
+        let mut interval = tokio::time::interval(self.config.health_check_interval);
+        // The first tick completes immediately, right after startup checked
+        // the database already.
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+
+            if !self.check().await {
+                error!(
+                    failures = self.failures,
+                    "database is unavailable, shutting down"
+                );
+                stop.notify_one();
+                return;
+            }
+        }
+    }
+
+    /// Pings the database, reopening it if that fails. Returns `false` once
+    /// `MAX_HEALTH_CHECK_FAILURES` pings in a row have failed.
+    async fn check(&mut self) -> bool {
+        let err = match sqlx::query("SELECT 1").execute(&self.pool).await {
+            Ok(_) => {
+                self.failures = 0;
+                return true;
+            }
+            Err(err) => err,
+        };
+
+        self.failures += 1;
+        error!(%err, failures = self.failures, "database health check failed");
+        if self.failures >= MAX_HEALTH_CHECK_FAILURES {
+            return false;
+        }
+
+        self.pool.close().await;
+        match db::init_db(&self.config).await {
+            Ok(pool) => {
+                info!("reopened the database");
+                self.pool = pool.clone();
+                self.pools.send_replace(pool);
+            }
+            Err(err) => error!(%err, "failed to reopen the database"),
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn failed_checks_reopen_the_database() {
+        let config = Config {
+            database_url: db::TEST_DB_URL.to_string(),
+            ..Default::default()
+        };
+        let pool = db::init_db(&config).await.unwrap();
+        let mut health_check = HealthCheck::new(config, pool.clone());
+        let mut pools = health_check.subscribe();
+
+        assert!(health_check.check().await);
+        assert!(!pools.has_changed().unwrap());
+
+        pool.close().await;
+        assert!(health_check.check().await);
+        assert!(pools.has_changed().unwrap());
+        let reopened = pools.borrow_and_update().clone();
+        assert!(!reopened.is_closed());
+
+        assert!(health_check.check().await);
+        assert_eq!(health_check.failures, 0);
+    }
+}
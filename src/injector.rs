@@ -0,0 +1,43 @@
+use frankenstein::Update;
+use std::path::PathBuf;
+use tokio::sync::mpsc;
+use tracing::error;
+
+/// Replays updates from a JSON file into a running bot, for scripted manual testing.
+///
+/// The file holds a JSON array of Telegram `Update` objects, in the same shape
+/// `getUpdates` returns them.
+pub struct MockUpdateInjector {
+    path: PathBuf,
+}
+
+impl MockUpdateInjector {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Sends every update in the file to `tx`, in order.
+    pub async fn run(self, tx: mpsc::UnboundedSender<Update>) {
+        let contents = match tokio::fs::read(&self.path).await {
+            Ok(contents) => contents,
+            Err(err) => {
+                error!(path = %self.path.display(), %err, "failed to read injected updates");
+                return;
+            }
+        };
+
+        let updates: Vec<Update> = match serde_json::from_slice(&contents) {
+            Ok(updates) => updates,
+            Err(err) => {
+                error!(path = %self.path.display(), %err, "failed to parse injected updates");
+                return;
+            }
+        };
+
+        for update in updates {
+            if tx.send(update).is_err() {
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use crate::bot::Bot;
+use crate::cleanup::Cleanup;
+use crate::config::Config;
+use crate::error::BotError;
+use crate::health::HealthCheck;
+use crate::injector::MockUpdateInjector;
+use crate::reminder::Reminders;
+use crate::telegram::LiveClient;
+use tokio::sync::mpsc;
+use tracing_subscriber::EnvFilter;
+
+pub mod bot;
+pub mod cleanup;
+pub mod config;
+pub mod conflict;
+pub mod db;
+pub mod error;
+pub mod event;
+pub mod health;
+pub mod injector;
+pub mod reminder;
+pub mod settings;
+pub mod telegram;
+pub mod util;
+pub mod webhook;
+
+/// Sets up log output filtered by `RUST_LOG` (default `televent=info`).
+///
+/// sqlx logs every query, so its level is kept at `warn` unless `RUST_LOG`
+/// names the `sqlx` target or `SQLX_LOG_LEVEL` is set, e.g.
+/// `RUST_LOG=sqlx=debug,televent=info` enables query logging. The HTTP logs
+/// from frankenstein (and the reqwest client underneath it) are dropped unless
+/// `RUST_LOG` explicitly names `frankenstein`, e.g. `RUST_LOG=frankenstein=trace`.
+fn init_logging(config: &Config) {
+    let rust_log = &config.log_level;
+    let mut filter = EnvFilter::new(rust_log);
+
+    let sqlx_level = match &config.sqlx_log_level {
+        Some(level) => Some(level.clone()),
+        None if !rust_log.contains("sqlx") => Some("warn".to_string()),
+        None => None,
+    };
+    let mut invalid_sqlx_level = None;
+    if let Some(level) = sqlx_level {
+        match format!("sqlx={level}").parse() {
+            Ok(directive) => filter = filter.add_directive(directive),
+            Err(err) => invalid_sqlx_level = Some((level, err)),
+        }
+    }
+
+    if !rust_log.contains("frankenstein") {
+        filter = filter
+            .add_directive("frankenstein=off".parse().unwrap())
+            .add_directive("reqwest=off".parse().unwrap())
+            .add_directive("hyper_util=off".parse().unwrap());
+    }
+
+    tracing_subscriber::fmt().with_env_filter(filter).init();
+
+    if let Some((level, err)) = invalid_sqlx_level {
+        tracing::warn!(level, %err, "ignoring invalid SQLX_LOG_LEVEL");
+    }
+}
+
+/// Runs the bot until the process is stopped.
+///
+/// With `TELEVENT_TEST_MODE=1` the bot uses a throwaway in-memory database,
+/// leaves the registered command menu alone, and if `TELEVENT_TEST_UPDATES`
+/// names a JSON file of updates, those are injected as if they had been
+/// received from Telegram.
+pub async fn run() {
+    let config = match Config::from_env() {
+        Ok(config) => {
+            init_logging(&config);
+            config
+        }
+        Err(err) => {
+            init_logging(&Config::default());
+            tracing::error!(%err, "invalid configuration");
+            std::process::exit(1);
+        }
+    };
+
+    let pool = match db::init_db(&config).await {
+        Ok(pool) => pool,
+        Err(err) => {
+            tracing::error!(%err, "failed to open the database");
+            std::process::exit(1);
+        }
+    };
+    let api = LiveClient::new(&config.token);
+
+    let mut bot = match Bot::new(api.clone(), pool.clone(), &config).await {
+        Ok(bot) => bot,
+        Err(err @ BotError::Telegram(_)) => {
+            tracing::error!(%err, "failed to reach Telegram, check TELEGRAM_BOT_TOKEN");
+            std::process::exit(1);
+        }
+        Err(err) => {
+            tracing::error!(%err, "startup check failed");
+            std::process::exit(1);
+        }
+    };
+
+    let health_check = HealthCheck::new(config.clone(), pool.clone());
+    bot = bot.with_pool_updates(health_check.subscribe());
+    tokio::spawn(
+        Cleanup::new(pool.clone(), config.event_retention_days)
+            .with_dry_run(config.cleanup_dry_run)
+            .with_pool_updates(health_check.subscribe())
+            .run(),
+    );
+    tokio::spawn(
+        Reminders::new(api, pool, config.reminder_interval)
+            .with_pool_updates(health_check.subscribe())
+            .with_new_events(bot.subscribe_new_events())
+            .with_parse_mode(config.parse_mode)
+            .run(),
+    );
+    tokio::spawn(health_check.run(bot.stop_handle()));
+
+    if let Some(path) = config.test_updates {
+        let (tx, rx) = mpsc::unbounded_channel();
+        tokio::spawn(MockUpdateInjector::new(path).run(tx));
+        bot = bot.with_injected_updates(rx);
+    }
+
+    match config.webhook_url {
+        Some(url) => {
+            if let Err(err) = bot.run_webhook(&url, config.webhook_port).await {
+                tracing::error!(%err, "webhook mode failed");
+            }
+        }
+        None => bot.run().await,
+    }
+}
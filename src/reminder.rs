@@ -0,0 +1,248 @@
+use crate::db;
+use crate::error::BotError;
+use crate::event::ParseModeStrategy;
+use crate::telegram::TelegramClient;
+use crate::util::{api_call_with_retry, utc_to_local, DATETIME_FORMAT, MAX_API_RETRIES};
+use chrono::{NaiveDateTime, Utc};
+use chrono_tz::Tz;
+use frankenstein::{ReplyMarkup, SendMessageParams};
+use sqlx::SqlitePool;
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::watch;
+use tracing::{error, info, warn};
+
+/// How far ahead of an event its reminder becomes due.
+pub const REMINDER_WINDOW: chrono::Duration = chrono::Duration::hours(24);
+
+/// How far ahead of an event its group gets an attendance summary, give or
+/// take `SUMMARY_SLACK`.
+pub const SUMMARY_LEAD: chrono::Duration = chrono::Duration::hours(12);
+const SUMMARY_SLACK: chrono::Duration = chrono::Duration::hours(1);
+
+/// Periodically messages everyone who accepted an event that starts within
+/// `REMINDER_WINDOW`, and posts an attendance summary to the event's group
+/// `SUMMARY_LEAD` before it starts. Each is sent once per event.
+pub struct Reminders<C> {
+    api: C,
+    pool: SqlitePool,
+    interval: Duration,
+    pool_updates: Option<watch::Receiver<SqlitePool>>,
+    /// The IDs of events that were just created, checked without waiting
+    /// for the next tick.
+    new_events: Option<broadcast::Receiver<i64>>,
+    parse_mode: ParseModeStrategy,
+}
+
+impl<C: TelegramClient> Reminders<C> {
+    pub fn new(api: C, pool: SqlitePool, interval: Duration) -> Self {
+        Self {
+            api,
+            pool,
+            interval,
+            pool_updates: None,
+            new_events: None,
+            parse_mode: ParseModeStrategy::MarkdownV2,
+        }
+    }
+
+    /// Marks up the messages sent for `parse_mode`, MarkdownV2 by default.
+    pub fn with_parse_mode(mut self, parse_mode: ParseModeStrategy) -> Self {
+        self.parse_mode = parse_mode;
+        self
+    }
+
+    /// Switches to the pools received on `pools` when the database is reopened.
+    pub fn with_pool_updates(mut self, pools: watch::Receiver<SqlitePool>) -> Self {
+        self.pool_updates = Some(pools);
+        self
+    }
+
+    /// Also checks the events whose IDs are received on `new_events` as soon
+    /// as they arrive, so that one created shortly before it starts gets its
+    /// reminder without waiting for the next tick.
+    pub fn with_new_events(mut self, new_events: broadcast::Receiver<i64>) -> Self {
+        self.new_events = Some(new_events);
+        self
+    }
+
+    /// Checks for due reminders every `interval`, forever. Failures are
+    /// logged and retried on the next tick.
+    pub async fn run(mut self) {
+        let mut interval = tokio::time::interval(self.interval);
+        let mut new_events = self.new_events.take();
+
+        loop {
+            let new_event = tokio::select! {
+                _ = interval.tick() => None,
+                received = next_new_event(&mut new_events) => Some(received),
+            };
+            if let Some(pools) = &mut self.pool_updates {
+                if pools.has_changed().unwrap_or(false) {
+                    self.pool = pools.borrow_and_update().clone();
+                }
+            }
+
+            let now = Utc::now().naive_utc();
+            match new_event {
+                Some(Ok(event_id)) => {
+                    if let Err(err) = self.send_due_for(event_id, now).await {
+                        error!(event_id, %err, "failed to send reminders");
+                    }
+                    continue;
+                }
+                Some(Err(RecvError::Closed)) => {
+                    new_events = None;
+                    continue;
+                }
+                // Some IDs were dropped, so every event is checked instead.
+                Some(Err(RecvError::Lagged(missed))) => {
+                    warn!(missed, "missed new events, checking all reminders");
+                }
+                None => {}
+            }
+
+            if let Err(err) = self.send_due(now).await {
+                error!(%err, "failed to send reminders");
+            }
+            if let Err(err) = self.send_summaries(now).await {
+                error!(%err, "failed to send attendance summaries");
+            }
+        }
+    }
+
+    /// Sends the reminders that are due at `now` (in UTC).
+    pub async fn send_due(&self, now: NaiveDateTime) -> Result<(), BotError> {
+        for event_id in
+            db::get_events_needing_reminder(&self.pool, now, now + REMINDER_WINDOW).await?
+        {
+            self.send_reminder(event_id).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Sends the reminders of `event_id` if they are due at `now` (in UTC).
+    /// Returns whether they were.
+    pub async fn send_due_for(&self, event_id: i64, now: NaiveDateTime) -> Result<bool, BotError> {
+        if !db::get_events_needing_reminder(&self.pool, now, now + REMINDER_WINDOW)
+            .await?
+            .contains(&event_id)
+        {
+            return Ok(false);
+        }
+
+        self.send_reminder(event_id).await?;
+        Ok(true)
+    }
+
+    /// Messages everyone who accepted `event_id` and marks its reminders as
+    /// sent.
+    async fn send_reminder(&self, event_id: i64) -> Result<(), BotError> {
+        let Some(event) = db::get_event(&self.pool, event_id).await? else {
+            return Ok(());
+        };
+
+        let timezone = db::get_group_timezone(&self.pool, event.chat_id)
+            .await?
+            .and_then(|timezone| timezone.parse().ok())
+            .unwrap_or(Tz::UTC);
+        let time = NaiveDateTime::parse_from_str(&event.time, DATETIME_FORMAT)?;
+        let mut text = format!(
+            "Reminder: «{}» starts at {} ({timezone}).",
+            event.name,
+            utc_to_local(time, timezone).format(DATETIME_FORMAT)
+        );
+        if !event.location.is_empty() {
+            text.push_str(&format!("\n📍 {}", event.location));
+        }
+
+        let attendees = db::get_attendees(&self.pool, event_id).await?;
+        for (user_id, _) in attendees.iter().filter(|(_, status)| status == "accepted") {
+            let params = SendMessageParams::builder()
+                .chat_id(*user_id)
+                .text(&text)
+                .build();
+
+            // Users who never started a chat with the bot can't be messaged.
+            if let Err(err) =
+                api_call_with_retry(|| self.api.send_message(&params), MAX_API_RETRIES).await
+            {
+                warn!(event_id, user_id, %err, "failed to send reminder");
+            }
+        }
+
+        db::mark_reminders_sent(&self.pool, event_id).await?;
+        info!(event_id, "sent event reminders");
+
+        Ok(())
+    }
+
+    /// Posts the attendance summaries that are due at `now` (in UTC).
+    pub async fn send_summaries(&self, now: NaiveDateTime) -> Result<(), BotError> {
+        for event_id in db::get_events_needing_summary(
+            &self.pool,
+            now + SUMMARY_LEAD - SUMMARY_SLACK,
+            now + SUMMARY_LEAD + SUMMARY_SLACK,
+        )
+        .await?
+        {
+            let Some(event) = db::get_event(&self.pool, event_id).await? else {
+                continue;
+            };
+
+            // Events created in a private chat have no group to summarize to.
+            if event.chat_id != event.creator {
+                let timezone = db::get_group_timezone(&self.pool, event.chat_id)
+                    .await?
+                    .and_then(|timezone| timezone.parse().ok())
+                    .unwrap_or(Tz::UTC);
+                let attendees = db::get_attendees(&self.pool, event_id).await?;
+                let count = |status: &str| attendees.iter().filter(|(_, s)| s == status).count();
+                let summary = format!(
+                    "Reminder: «{}» starts in about {} hours — ✅ {} accepted, ❌ {} declined, ❓ {} maybe.",
+                    event.name,
+                    SUMMARY_LEAD.num_hours(),
+                    count("accepted"),
+                    count("declined"),
+                    count("maybe")
+                );
+
+                let mut params = SendMessageParams::builder()
+                    .chat_id(event.chat_id)
+                    .text(format!(
+                        "{}\n\n{}",
+                        self.parse_mode.escape(&summary),
+                        event.format_message_with(timezone, None, self.parse_mode)
+                    ))
+                    // Posted on the day of the event, so it comes with the check-in button.
+                    .reply_markup(ReplyMarkup::InlineKeyboardMarkup(
+                        event.create_keyboard(false),
+                    ))
+                    .build();
+                params.parse_mode = self.parse_mode.parse_mode();
+                if let Err(err) =
+                    api_call_with_retry(|| self.api.send_message(&params), MAX_API_RETRIES).await
+                {
+                    warn!(event_id, %err, "failed to send attendance summary");
+                }
+            }
+
+            db::mark_summary_sent(&self.pool, event_id).await?;
+            info!(event_id, "sent attendance summary");
+        }
+
+        Ok(())
+    }
+}
+
+/// Waits for the next ID on `new_events`, or forever if there is no
+/// channel to wait on.
+async fn next_new_event(
+    new_events: &mut Option<broadcast::Receiver<i64>>,
+) -> Result<i64, RecvError> {
+    match new_events {
+        Some(new_events) => new_events.recv().await,
+        None => std::future::pending().await,
+    }
+}
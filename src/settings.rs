@@ -0,0 +1,29 @@
+use crate::db::group_settings::GroupSettings;
+
+#[derive(Debug, PartialEq)]
+#[allow(clippy::enum_variant_names)]
+pub enum SettingsState {
+    AwaitingMaxEvents,
+    AwaitingRequireAdmin,
+    AwaitingTrustedCreatorsOnly,
+    AwaitingNotifyCreator,
+    AwaitingPinned,
+    AwaitingTimezone,
+}
+
+/// An admin's `/settings` conversation in progress. The questions are asked
+/// in a private chat, and the answers saved once all of them are given.
+#[derive(Debug)]
+pub struct SettingsContext {
+    pub state: SettingsState,
+    pub settings: GroupSettings,
+}
+
+impl SettingsContext {
+    pub fn new(settings: GroupSettings) -> Self {
+        Self {
+            state: SettingsState::AwaitingMaxEvents,
+            settings,
+        }
+    }
+}
@@ -0,0 +1,434 @@
+use async_trait::async_trait;
+use frankenstein::{
+    AnswerCallbackQueryParams, AnswerInlineQueryParams, AsyncApi, AsyncTelegramApi, ChatMember,
+    DeleteMessageParams, DeleteMyCommandsParams, DeleteWebhookParams, EditMessageReplyMarkupParams,
+    EditMessageResponse, EditMessageTextParams, GetChatAdministratorsParams, GetChatMemberParams,
+    GetUpdatesParams, Message, MethodResponse, PinChatMessageParams, SendDocumentParams,
+    SendMessageParams, SetMyCommandsParams, SetWebhookParams, UnpinChatMessageParams, Update, User,
+};
+
+#[cfg(test)]
+pub(crate) use mock::MockClient;
+
+/// The answer to a Telegram request, or why it failed.
+pub type TelegramResult<T> = Result<T, frankenstein::Error>;
+
+/// The Telegram Bot API methods the bot calls. `LiveClient` sends them to
+/// Telegram, while tests use `MockClient`, which records them instead.
+#[async_trait]
+pub trait TelegramClient: Send + Sync {
+    async fn get_me(&self) -> TelegramResult<MethodResponse<User>>;
+
+    async fn get_updates(
+        &self,
+        params: &GetUpdatesParams,
+    ) -> TelegramResult<MethodResponse<Vec<Update>>>;
+
+    async fn set_webhook(&self, params: &SetWebhookParams) -> TelegramResult<MethodResponse<bool>>;
+
+    async fn delete_webhook(
+        &self,
+        params: &DeleteWebhookParams,
+    ) -> TelegramResult<MethodResponse<bool>>;
+
+    async fn set_my_commands(
+        &self,
+        params: &SetMyCommandsParams,
+    ) -> TelegramResult<MethodResponse<bool>>;
+
+    async fn delete_my_commands(
+        &self,
+        params: &DeleteMyCommandsParams,
+    ) -> TelegramResult<MethodResponse<bool>>;
+
+    async fn send_message(
+        &self,
+        params: &SendMessageParams,
+    ) -> TelegramResult<MethodResponse<Message>>;
+
+    async fn send_document(
+        &self,
+        params: &SendDocumentParams,
+    ) -> TelegramResult<MethodResponse<Message>>;
+
+    async fn edit_message_text(
+        &self,
+        params: &EditMessageTextParams,
+    ) -> TelegramResult<EditMessageResponse>;
+
+    async fn edit_message_reply_markup(
+        &self,
+        params: &EditMessageReplyMarkupParams,
+    ) -> TelegramResult<EditMessageResponse>;
+
+    async fn delete_message(
+        &self,
+        params: &DeleteMessageParams,
+    ) -> TelegramResult<MethodResponse<bool>>;
+
+    async fn pin_chat_message(
+        &self,
+        params: &PinChatMessageParams,
+    ) -> TelegramResult<MethodResponse<bool>>;
+
+    async fn unpin_chat_message(
+        &self,
+        params: &UnpinChatMessageParams,
+    ) -> TelegramResult<MethodResponse<bool>>;
+
+    async fn answer_callback_query(
+        &self,
+        params: &AnswerCallbackQueryParams,
+    ) -> TelegramResult<MethodResponse<bool>>;
+
+    async fn answer_inline_query(
+        &self,
+        params: &AnswerInlineQueryParams,
+    ) -> TelegramResult<MethodResponse<bool>>;
+
+    async fn get_chat_member(
+        &self,
+        params: &GetChatMemberParams,
+    ) -> TelegramResult<MethodResponse<ChatMember>>;
+
+    async fn get_chat_administrators(
+        &self,
+        params: &GetChatAdministratorsParams,
+    ) -> TelegramResult<MethodResponse<Vec<ChatMember>>>;
+}
+
+/// Sends requests to Telegram with frankenstein's async client.
+#[derive(Clone)]
+pub struct LiveClient(AsyncApi);
+
+impl LiveClient {
+    pub fn new(token: &str) -> Self {
+        Self(AsyncApi::new(token))
+    }
+}
+
+#[async_trait]
+impl TelegramClient for LiveClient {
+    async fn get_me(&self) -> TelegramResult<MethodResponse<User>> {
+        self.0.get_me().await
+    }
+
+    async fn get_updates(
+        &self,
+        params: &GetUpdatesParams,
+    ) -> TelegramResult<MethodResponse<Vec<Update>>> {
+        self.0.get_updates(params).await
+    }
+
+    async fn set_webhook(&self, params: &SetWebhookParams) -> TelegramResult<MethodResponse<bool>> {
+        self.0.set_webhook(params).await
+    }
+
+    async fn delete_webhook(
+        &self,
+        params: &DeleteWebhookParams,
+    ) -> TelegramResult<MethodResponse<bool>> {
+        self.0.delete_webhook(params).await
+    }
+
+    async fn set_my_commands(
+        &self,
+        params: &SetMyCommandsParams,
+    ) -> TelegramResult<MethodResponse<bool>> {
+        self.0.set_my_commands(params).await
+    }
+
+    async fn delete_my_commands(
+        &self,
+        params: &DeleteMyCommandsParams,
+    ) -> TelegramResult<MethodResponse<bool>> {
+        self.0.delete_my_commands(params).await
+    }
+
+    async fn send_message(
+        &self,
+        params: &SendMessageParams,
+    ) -> TelegramResult<MethodResponse<Message>> {
+        self.0.send_message(params).await
+    }
+
+    async fn send_document(
+        &self,
+        params: &SendDocumentParams,
+    ) -> TelegramResult<MethodResponse<Message>> {
+        self.0.send_document(params).await
+    }
+
+    async fn edit_message_text(
+        &self,
+        params: &EditMessageTextParams,
+    ) -> TelegramResult<EditMessageResponse> {
+        self.0.edit_message_text(params).await
+    }
+
+    async fn edit_message_reply_markup(
+        &self,
+        params: &EditMessageReplyMarkupParams,
+    ) -> TelegramResult<EditMessageResponse> {
+        self.0.edit_message_reply_markup(params).await
+    }
+
+    async fn delete_message(
+        &self,
+        params: &DeleteMessageParams,
+    ) -> TelegramResult<MethodResponse<bool>> {
+        self.0.delete_message(params).await
+    }
+
+    async fn pin_chat_message(
+        &self,
+        params: &PinChatMessageParams,
+    ) -> TelegramResult<MethodResponse<bool>> {
+        self.0.pin_chat_message(params).await
+    }
+
+    async fn unpin_chat_message(
+        &self,
+        params: &UnpinChatMessageParams,
+    ) -> TelegramResult<MethodResponse<bool>> {
+        self.0.unpin_chat_message(params).await
+    }
+
+    async fn answer_callback_query(
+        &self,
+        params: &AnswerCallbackQueryParams,
+    ) -> TelegramResult<MethodResponse<bool>> {
+        self.0.answer_callback_query(params).await
+    }
+
+    async fn answer_inline_query(
+        &self,
+        params: &AnswerInlineQueryParams,
+    ) -> TelegramResult<MethodResponse<bool>> {
+        self.0.answer_inline_query(params).await
+    }
+
+    async fn get_chat_member(
+        &self,
+        params: &GetChatMemberParams,
+    ) -> TelegramResult<MethodResponse<ChatMember>> {
+        self.0.get_chat_member(params).await
+    }
+
+    async fn get_chat_administrators(
+        &self,
+        params: &GetChatAdministratorsParams,
+    ) -> TelegramResult<MethodResponse<Vec<ChatMember>>> {
+        self.0.get_chat_administrators(params).await
+    }
+}
+
+#[cfg(test)]
+mod mock {
+    use super::*;
+    use serde::de::DeserializeOwned;
+    use serde::Serialize;
+    use serde_json::json;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    /// A request made to `MockClient`.
+    #[derive(Debug, Clone)]
+    struct RecordedCall {
+        method: String,
+        params: serde_json::Value,
+    }
+
+    /// Answers every request with a canned `result` and records the calls
+    /// made.
+    pub(crate) struct MockClient {
+        responses: HashMap<&'static str, serde_json::Value>,
+        /// The descriptions of the 400 Bad Request errors some methods fail
+        /// with.
+        failures: HashMap<&'static str, &'static str>,
+        calls: Mutex<Vec<RecordedCall>>,
+    }
+
+    impl MockClient {
+        pub fn new() -> Self {
+            Self {
+                responses: HashMap::new(),
+                failures: HashMap::new(),
+                calls: Mutex::new(Vec::new()),
+            }
+            .respond(
+                "getMe",
+                json!({ "id": 100, "is_bot": true, "first_name": "Televent", "username": "televent_bot" }),
+            )
+        }
+
+        pub fn respond(mut self, method: &'static str, result: serde_json::Value) -> Self {
+            self.responses.insert(method, result);
+            self
+        }
+
+        pub fn fail(mut self, method: &'static str, description: &'static str) -> Self {
+            self.failures.insert(method, description);
+            self
+        }
+
+        pub fn calls_to(&self, method: &str) -> usize {
+            self.recorded(method).len()
+        }
+
+        /// The parameters of every call to `method`, oldest first.
+        pub fn recorded(&self, method: &str) -> Vec<serde_json::Value> {
+            self.calls
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|call| call.method == method)
+                .map(|call| call.params.clone())
+                .collect()
+        }
+
+        /// Records a call to `method` and answers it. Methods without a
+        /// canned result answer `true`.
+        fn call<T: DeserializeOwned>(
+            &self,
+            method: &str,
+            params: Option<&impl Serialize>,
+        ) -> TelegramResult<T> {
+            self.calls.lock().unwrap().push(RecordedCall {
+                method: method.to_string(),
+                params: serde_json::to_value(params).unwrap_or_default(),
+            });
+
+            if let Some(description) = self.failures.get(method) {
+                return Err(frankenstein::Error::Api(frankenstein::ErrorResponse {
+                    ok: false,
+                    description: description.to_string(),
+                    error_code: 400,
+                    parameters: None,
+                }));
+            }
+            let result = self.responses.get(method).cloned().unwrap_or(json!(true));
+            serde_json::from_value(json!({ "ok": true, "result": result }))
+                .map_err(|err| frankenstein::Error::Decode(err.to_string()))
+        }
+    }
+
+    #[async_trait]
+    impl TelegramClient for MockClient {
+        async fn get_me(&self) -> TelegramResult<MethodResponse<User>> {
+            self.call("getMe", None::<&()>)
+        }
+
+        async fn get_updates(
+            &self,
+            params: &GetUpdatesParams,
+        ) -> TelegramResult<MethodResponse<Vec<Update>>> {
+            self.call("getUpdates", Some(params))
+        }
+
+        async fn set_webhook(
+            &self,
+            params: &SetWebhookParams,
+        ) -> TelegramResult<MethodResponse<bool>> {
+            self.call("setWebhook", Some(params))
+        }
+
+        async fn delete_webhook(
+            &self,
+            params: &DeleteWebhookParams,
+        ) -> TelegramResult<MethodResponse<bool>> {
+            self.call("deleteWebhook", Some(params))
+        }
+
+        async fn set_my_commands(
+            &self,
+            params: &SetMyCommandsParams,
+        ) -> TelegramResult<MethodResponse<bool>> {
+            self.call("setMyCommands", Some(params))
+        }
+
+        async fn delete_my_commands(
+            &self,
+            params: &DeleteMyCommandsParams,
+        ) -> TelegramResult<MethodResponse<bool>> {
+            self.call("deleteMyCommands", Some(params))
+        }
+
+        async fn send_message(
+            &self,
+            params: &SendMessageParams,
+        ) -> TelegramResult<MethodResponse<Message>> {
+            self.call("sendMessage", Some(params))
+        }
+
+        async fn send_document(
+            &self,
+            params: &SendDocumentParams,
+        ) -> TelegramResult<MethodResponse<Message>> {
+            self.call("sendDocument", Some(params))
+        }
+
+        async fn edit_message_text(
+            &self,
+            params: &EditMessageTextParams,
+        ) -> TelegramResult<EditMessageResponse> {
+            self.call("editMessageText", Some(params))
+        }
+
+        async fn edit_message_reply_markup(
+            &self,
+            params: &EditMessageReplyMarkupParams,
+        ) -> TelegramResult<EditMessageResponse> {
+            self.call("editMessageReplyMarkup", Some(params))
+        }
+
+        async fn delete_message(
+            &self,
+            params: &DeleteMessageParams,
+        ) -> TelegramResult<MethodResponse<bool>> {
+            self.call("deleteMessage", Some(params))
+        }
+
+        async fn pin_chat_message(
+            &self,
+            params: &PinChatMessageParams,
+        ) -> TelegramResult<MethodResponse<bool>> {
+            self.call("pinChatMessage", Some(params))
+        }
+
+        async fn unpin_chat_message(
+            &self,
+            params: &UnpinChatMessageParams,
+        ) -> TelegramResult<MethodResponse<bool>> {
+            self.call("unpinChatMessage", Some(params))
+        }
+
+        async fn answer_callback_query(
+            &self,
+            params: &AnswerCallbackQueryParams,
+        ) -> TelegramResult<MethodResponse<bool>> {
+            self.call("answerCallbackQuery", Some(params))
+        }
+
+        async fn answer_inline_query(
+            &self,
+            params: &AnswerInlineQueryParams,
+        ) -> TelegramResult<MethodResponse<bool>> {
+            self.call("answerInlineQuery", Some(params))
+        }
+
+        async fn get_chat_member(
+            &self,
+            params: &GetChatMemberParams,
+        ) -> TelegramResult<MethodResponse<ChatMember>> {
+            self.call("getChatMember", Some(params))
+        }
+
+        async fn get_chat_administrators(
+            &self,
+            params: &GetChatAdministratorsParams,
+        ) -> TelegramResult<MethodResponse<Vec<ChatMember>>> {
+            self.call("getChatAdministrators", Some(params))
+        }
+    }
+}
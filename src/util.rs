@@ -0,0 +1,136 @@
+use crate::error::BotError;
+use chrono::{NaiveDateTime, TimeZone};
+use chrono_tz::Tz;
+use std::future::Future;
+use std::time::Duration;
+use tracing::warn;
+
+/// How many times a Telegram API call is retried after being rate limited.
+pub const MAX_API_RETRIES: u8 = 3;
+
+/// The format event times are stored in, e.g. `2025-08-15 19:00`.
+pub const DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// The formats event times may be entered in, tried in order.
+pub const DATETIME_INPUT_FORMATS: &[&str] = &[
+    DATETIME_FORMAT,
+    "%Y/%m/%d %H:%M",
+    "%d-%m-%Y %H:%M",
+    "%Y-%m-%d %I:%M %p",
+];
+
+/// Parses an event time entered by a user, ignoring surrounding whitespace.
+///
+/// The time may be in any of `DATETIME_INPUT_FORMATS`, and followed by an
+/// IANA timezone name, as in `2025-08-15 19:00 America/Toronto`, which is
+/// returned alongside it.
+pub fn parse_datetime_string(text: &str) -> Result<(NaiveDateTime, Option<Tz>), BotError> {
+    let text = text.trim();
+
+    if let Some((time, timezone)) = text.rsplit_once(char::is_whitespace) {
+        if let Ok(timezone) = timezone.parse::<Tz>() {
+            return Ok((parse_naive_datetime(time.trim_end())?, Some(timezone)));
+        }
+    }
+
+    Ok((parse_naive_datetime(text)?, None))
+}
+
+fn parse_naive_datetime(text: &str) -> Result<NaiveDateTime, BotError> {
+    let mut last_err = None;
+    for format in DATETIME_INPUT_FORMATS {
+        match NaiveDateTime::parse_from_str(text, format) {
+            Ok(time) => return Ok(time),
+            Err(err) => last_err = Some(err),
+        }
+    }
+
+    Err(BotError::DateTime(
+        last_err.expect("there is at least one format"),
+    ))
+}
+
+/// Converts a wall-clock time in `timezone` to UTC.
+///
+/// Returns `None` for times skipped by a DST change. Times repeated by one
+/// resolve to the earlier instant.
+pub fn local_to_utc(time: NaiveDateTime, timezone: Tz) -> Option<NaiveDateTime> {
+    timezone
+        .from_local_datetime(&time)
+        .earliest()
+        .map(|time| time.naive_utc())
+}
+
+/// Converts a UTC time to wall-clock time in `timezone`.
+pub fn utc_to_local(time: NaiveDateTime, timezone: Tz) -> NaiveDateTime {
+    timezone.from_utc_datetime(&time).naive_local()
+}
+
+/// Returns the arguments of `text` if it is the bot command `command`.
+pub fn command_args<'a>(text: &'a str, command: &str) -> Option<&'a str> {
+    let rest = text.strip_prefix(command)?;
+
+    if rest.is_empty() || rest.starts_with(char::is_whitespace) {
+        Some(rest.trim())
+    } else {
+        None
+    }
+}
+
+/// Parses the page number given to a list command, counting from 1. Anything
+/// that isn't a positive number, including nothing, is page 1.
+pub fn parse_page_number(text: &str) -> usize {
+    text.trim()
+        .parse()
+        .ok()
+        .filter(|&page| page > 0)
+        .unwrap_or(1)
+}
+
+/// Splits a trailing page number, as in `/list sports 2`, off a list
+/// command's arguments. Without one, the page is 1.
+pub fn split_page_number(args: &str) -> (&str, usize) {
+    match args.rsplit_once(char::is_whitespace).unwrap_or(("", args)) {
+        (rest, page) if !page.is_empty() && page.bytes().all(|b| b.is_ascii_digit()) => {
+            (rest.trim_end(), parse_page_number(page))
+        }
+        _ => (args, 1),
+    }
+}
+
+/// Runs the Telegram API call made by `f`, retrying it up to `max_retries`
+/// times while Telegram answers 429 Too Many Requests.
+///
+/// Each retry waits for the `retry_after` Telegram asks for, or, if it didn't
+/// give one, for 1 second doubled on every retry. Other errors are returned
+/// straight away.
+pub async fn api_call_with_retry<F, Fut, T>(mut f: F, max_retries: u8) -> Result<T, BotError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, frankenstein::Error>>,
+{
+    let mut backoff = Duration::from_secs(1);
+    let mut retries = 0;
+
+    loop {
+        match f().await {
+            Err(frankenstein::Error::Api(response))
+                if response.error_code == 429 && retries < max_retries =>
+            {
+                let delay = response
+                    .parameters
+                    .and_then(|parameters| parameters.retry_after)
+                    .map_or(backoff, |secs| Duration::from_secs(secs.into()));
+                retries += 1;
+                warn!(
+                    retry = retries,
+                    delay_secs = delay.as_secs(),
+                    "rate limited by Telegram, retrying"
+                );
+                tokio::time::sleep(delay).await;
+                backoff *= 2;
+            }
+            result => return result.map_err(BotError::from),
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::post;
+use axum::{Json, Router};
+use frankenstein::Update;
+use std::net::SocketAddr;
+use tokio::sync::mpsc;
+
+/// Receives updates pushed by Telegram to the bot's webhook and forwards them to `tx`.
+pub struct WebhookServer {
+    port: u16,
+}
+
+impl WebhookServer {
+    pub fn new(port: u16) -> Self {
+        Self { port }
+    }
+
+    /// Serves until `tx` is closed. Every path accepts `POST`ed updates, so
+    /// the webhook URL may include any path the reverse proxy forwards.
+    pub async fn run(self, tx: mpsc::UnboundedSender<Update>) -> std::io::Result<()> {
+        let app = Router::new()
+            .route("/", post(receive_update))
+            .route("/*path", post(receive_update))
+            .with_state(tx.clone());
+        let listener =
+            tokio::net::TcpListener::bind(SocketAddr::from(([0, 0, 0, 0], self.port))).await?;
+
+        axum::serve(listener, app)
+            .with_graceful_shutdown(async move { tx.closed().await })
+            .await
+    }
+}
+
+async fn receive_update(
+    State(tx): State<mpsc::UnboundedSender<Update>>,
+    Json(update): Json<Update>,
+) -> StatusCode {
+    match tx.send(update) {
+        Ok(_) => StatusCode::OK,
+        Err(_) => StatusCode::SERVICE_UNAVAILABLE,
+    }
+}
@@ -0,0 +1,171 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use chrono_tz::Tz;
+use televent::util::{local_to_utc, parse_datetime_string, utc_to_local};
+
+fn datetime(year: i32, month: u32, day: u32, hour: u32, min: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(year, month, day)
+        .unwrap()
+        .and_hms_opt(hour, min, 0)
+        .unwrap()
+}
+
+#[test]
+fn parses_date_and_time() {
+    assert_eq!(
+        parse_datetime_string("2025-08-15 19:00").unwrap(),
+        (datetime(2025, 8, 15, 19, 0), None)
+    );
+}
+
+#[test]
+fn rejects_time_only() {
+    assert!(parse_datetime_string("19:00").is_err());
+}
+
+#[test]
+fn rejects_date_only() {
+    assert!(parse_datetime_string("2025-08-15").is_err());
+}
+
+#[test]
+fn accepts_leap_day() {
+    assert_eq!(
+        parse_datetime_string("2024-02-29 12:30").unwrap(),
+        (datetime(2024, 2, 29, 12, 30), None)
+    );
+}
+
+#[test]
+fn rejects_leap_day_in_common_year() {
+    assert!(parse_datetime_string("2025-02-29 12:30").is_err());
+}
+
+#[test]
+fn rejects_out_of_range_fields() {
+    assert!(parse_datetime_string("2025-13-01 12:00").is_err());
+    assert!(parse_datetime_string("2025-08-15 24:00").is_err());
+    assert!(parse_datetime_string("2025-08-15 19:60").is_err());
+}
+
+#[test]
+fn accepts_dst_boundary_times() {
+    // Times are naive, so wall-clock times skipped or repeated by a DST change
+    // in some timezone are still valid input.
+    assert_eq!(
+        parse_datetime_string("2025-03-09 02:30").unwrap(),
+        (datetime(2025, 3, 9, 2, 30), None)
+    );
+    assert_eq!(
+        parse_datetime_string("2025-11-02 01:30").unwrap(),
+        (datetime(2025, 11, 2, 1, 30), None)
+    );
+}
+
+#[test]
+fn accepts_timezone_suffix() {
+    assert_eq!(
+        parse_datetime_string("2025-08-15 19:00 America/Toronto").unwrap(),
+        (datetime(2025, 8, 15, 19, 0), Some(Tz::America__Toronto))
+    );
+    assert_eq!(
+        parse_datetime_string(" 2025-08-15 19:00   UTC ").unwrap(),
+        (datetime(2025, 8, 15, 19, 0), Some(Tz::UTC))
+    );
+}
+
+#[test]
+fn rejects_unknown_timezone_suffix() {
+    assert!(parse_datetime_string("2025-08-15 19:00 Mars/Olympus_Mons").is_err());
+    assert!(parse_datetime_string("2025-08-15 19:00+02:00").is_err());
+    assert!(parse_datetime_string("19:00 America/Toronto").is_err());
+}
+
+#[test]
+fn converts_local_time_to_utc() {
+    // Toronto is on EDT (UTC-4) in August.
+    assert_eq!(
+        local_to_utc(datetime(2025, 8, 15, 19, 0), Tz::America__Toronto),
+        Some(datetime(2025, 8, 15, 23, 0))
+    );
+    assert_eq!(
+        utc_to_local(datetime(2025, 8, 15, 23, 0), Tz::America__Toronto),
+        datetime(2025, 8, 15, 19, 0)
+    );
+}
+
+#[test]
+fn rejects_times_skipped_by_dst() {
+    assert_eq!(
+        local_to_utc(datetime(2025, 3, 9, 2, 30), Tz::America__Toronto),
+        None
+    );
+}
+
+#[test]
+fn repeated_dst_times_resolve_to_earliest() {
+    // 01:30 happens twice on 2025-11-02 in Toronto, first in EDT (UTC-4).
+    assert_eq!(
+        local_to_utc(datetime(2025, 11, 2, 1, 30), Tz::America__Toronto),
+        Some(datetime(2025, 11, 2, 5, 30))
+    );
+}
+
+#[test]
+fn ignores_surrounding_whitespace() {
+    assert_eq!(
+        parse_datetime_string("  2025-08-15 19:00\n").unwrap(),
+        (datetime(2025, 8, 15, 19, 0), None)
+    );
+}
+
+#[test]
+fn accepts_slashed_dates() {
+    assert_eq!(
+        parse_datetime_string("2025/08/15 19:00").unwrap(),
+        (datetime(2025, 8, 15, 19, 0), None)
+    );
+}
+
+#[test]
+fn accepts_day_first_dates() {
+    assert_eq!(
+        parse_datetime_string("15-08-2025 19:00").unwrap(),
+        (datetime(2025, 8, 15, 19, 0), None)
+    );
+    assert!(parse_datetime_string("32-08-2025 19:00").is_err());
+}
+
+#[test]
+fn accepts_twelve_hour_times() {
+    assert_eq!(
+        parse_datetime_string("2025-08-15 07:30 PM").unwrap(),
+        (datetime(2025, 8, 15, 19, 30), None)
+    );
+    assert_eq!(
+        parse_datetime_string("2025-08-15 12:00 am").unwrap(),
+        (datetime(2025, 8, 15, 0, 0), None)
+    );
+    assert!(parse_datetime_string("2025-08-15 13:00 PM").is_err());
+}
+
+#[test]
+fn accepts_single_digit_hours() {
+    assert_eq!(
+        parse_datetime_string("2025-08-15 9:05").unwrap(),
+        (datetime(2025, 8, 15, 9, 5), None)
+    );
+    assert_eq!(
+        parse_datetime_string("15-08-2025 9:05").unwrap(),
+        (datetime(2025, 8, 15, 9, 5), None)
+    );
+    assert_eq!(
+        parse_datetime_string("2025-08-15 7:00 pm America/Toronto").unwrap(),
+        (datetime(2025, 8, 15, 19, 0), Some(Tz::America__Toronto))
+    );
+}
+
+#[test]
+fn rejects_empty_input() {
+    assert!(parse_datetime_string("").is_err());
+    assert!(parse_datetime_string("   ").is_err());
+}
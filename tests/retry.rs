@@ -0,0 +1,74 @@
+use frankenstein::{Error, ErrorResponse, ResponseParameters};
+use std::cell::Cell;
+use televent::error::BotError;
+use televent::util::api_call_with_retry;
+
+fn api_error(error_code: u64, retry_after: Option<u16>) -> Error {
+    Error::Api(ErrorResponse {
+        ok: false,
+        description: "Too Many Requests".to_string(),
+        error_code,
+        parameters: Some(ResponseParameters {
+            migrate_to_chat_id: None,
+            retry_after,
+        }),
+    })
+}
+
+#[tokio::test]
+async fn retries_rate_limited_calls() {
+    let calls = Cell::new(0);
+
+    let result = api_call_with_retry(
+        || {
+            calls.set(calls.get() + 1);
+            let attempt = calls.get();
+            async move {
+                if attempt < 3 {
+                    Err(api_error(429, Some(0)))
+                } else {
+                    Ok(attempt)
+                }
+            }
+        },
+        3,
+    )
+    .await;
+
+    assert_eq!(result.unwrap(), 3);
+    assert_eq!(calls.get(), 3);
+}
+
+#[tokio::test]
+async fn gives_up_after_max_retries() {
+    let calls = Cell::new(0);
+
+    let result: Result<(), BotError> = api_call_with_retry(
+        || {
+            calls.set(calls.get() + 1);
+            async { Err(api_error(429, Some(0))) }
+        },
+        2,
+    )
+    .await;
+
+    assert!(matches!(result, Err(BotError::Telegram(Error::Api(_)))));
+    assert_eq!(calls.get(), 3);
+}
+
+#[tokio::test]
+async fn does_not_retry_other_errors() {
+    let calls = Cell::new(0);
+
+    let result: Result<(), BotError> = api_call_with_retry(
+        || {
+            calls.set(calls.get() + 1);
+            async { Err(api_error(400, None)) }
+        },
+        3,
+    )
+    .await;
+
+    assert!(result.is_err());
+    assert_eq!(calls.get(), 1);
+}
@@ -0,0 +1,175 @@
+use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::{Row, SqlitePool};
+
+/// A single connection keeps every query on the same in-memory database.
+async fn memory_pool() -> SqlitePool {
+    SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap()
+}
+
+async fn columns(pool: &SqlitePool, table: &str) -> Vec<(String, String)> {
+    sqlx::query(&format!("PRAGMA table_info({table})"))
+        .fetch_all(pool)
+        .await
+        .unwrap()
+        .iter()
+        .map(|row| (row.get("name"), row.get("type")))
+        .collect()
+}
+
+fn expected(columns: &[(&str, &str)]) -> Vec<(String, String)> {
+    columns
+        .iter()
+        .map(|(name, ty)| (name.to_string(), ty.to_string()))
+        .collect()
+}
+
+#[tokio::test]
+async fn events_table_matches_queries() {
+    let pool = memory_pool().await;
+    televent::db::create_schema(&pool).await.unwrap();
+
+    assert_eq!(
+        columns(&pool, "events").await,
+        expected(&[
+            ("id", "INTEGER"),
+            ("chat_id", "INTEGER"),
+            ("user_id", "INTEGER"),
+            ("name", "TEXT"),
+            ("description", "TEXT"),
+            ("location", "TEXT"),
+            ("time", "TEXT"),
+            ("max_capacity", "INTEGER"),
+            ("message_id", "INTEGER"),
+            ("reminders_sent", "INTEGER"),
+            ("rsvp_deadline", "TEXT"),
+            ("cancelled_at", "TEXT"),
+            ("url", "TEXT"),
+            ("summary_sent", "INTEGER"),
+            ("anonymous", "INTEGER"),
+            ("tags", "TEXT"),
+            ("last_reminded_at", "TEXT"),
+            ("cancel_reason", "TEXT"),
+            ("guests_per_rsvp", "INTEGER"),
+            ("message_hash", "TEXT"),
+        ])
+    );
+}
+
+#[tokio::test]
+async fn attendees_table_matches_queries() {
+    let pool = memory_pool().await;
+    televent::db::create_schema(&pool).await.unwrap();
+
+    assert_eq!(
+        columns(&pool, "attendees").await,
+        expected(&[
+            ("event_id", "INTEGER"),
+            ("user_id", "INTEGER"),
+            ("status", "TEXT"),
+            ("decline_reason", "TEXT"),
+            ("checked_in", "INTEGER"),
+            ("guests", "INTEGER"),
+        ])
+    );
+}
+
+#[tokio::test]
+async fn group_settings_table_matches_queries() {
+    let pool = memory_pool().await;
+    televent::db::create_schema(&pool).await.unwrap();
+
+    assert_eq!(
+        columns(&pool, "group_settings").await,
+        expected(&[
+            ("chat_id", "INTEGER"),
+            ("timezone", "TEXT"),
+            ("max_events", "INTEGER"),
+            ("require_admin", "INTEGER"),
+            ("notify_creator", "INTEGER"),
+            ("pinned", "INTEGER"),
+            ("trusted_creators_only", "INTEGER"),
+        ])
+    );
+}
+
+#[tokio::test]
+async fn trusted_creators_table_matches_queries() {
+    let pool = memory_pool().await;
+    televent::db::create_schema(&pool).await.unwrap();
+
+    assert_eq!(
+        columns(&pool, "trusted_creators").await,
+        expected(&[("chat_id", "INTEGER"), ("user_id", "INTEGER")])
+    );
+}
+
+#[tokio::test]
+async fn feedback_log_table_matches_queries() {
+    let pool = memory_pool().await;
+    televent::db::create_schema(&pool).await.unwrap();
+
+    assert_eq!(
+        columns(&pool, "feedback_log").await,
+        expected(&[("user_id", "INTEGER"), ("sent_at", "TEXT")])
+    );
+}
+
+#[tokio::test]
+async fn chat_names_table_matches_queries() {
+    let pool = memory_pool().await;
+    televent::db::create_schema(&pool).await.unwrap();
+
+    assert_eq!(
+        columns(&pool, "chat_names").await,
+        expected(&[("chat_id", "INTEGER"), ("title", "TEXT")])
+    );
+}
+
+#[tokio::test]
+async fn blocked_attendees_table_matches_queries() {
+    let pool = memory_pool().await;
+    televent::db::create_schema(&pool).await.unwrap();
+
+    assert_eq!(
+        columns(&pool, "blocked_attendees").await,
+        expected(&[("creator_id", "INTEGER"), ("blocked_user_id", "INTEGER")])
+    );
+}
+
+#[tokio::test]
+async fn drafts_table_matches_queries() {
+    let pool = memory_pool().await;
+    televent::db::create_schema(&pool).await.unwrap();
+
+    assert_eq!(
+        columns(&pool, "drafts").await,
+        expected(&[
+            ("user_id", "INTEGER"),
+            ("state", "TEXT"),
+            ("origin_chat_id", "INTEGER"),
+            ("title", "TEXT"),
+            ("description", "TEXT"),
+            ("location", "TEXT"),
+            ("datetime", "TEXT"),
+            ("max_capacity", "INTEGER"),
+            ("rsvp_deadline", "TEXT"),
+            ("url", "TEXT"),
+            ("timezone", "TEXT"),
+            ("updated_at", "TEXT"),
+            ("anonymous", "INTEGER"),
+            ("tags", "TEXT"),
+            ("guests_per_rsvp", "INTEGER"),
+        ])
+    );
+}
+
+#[tokio::test]
+async fn schema_creation_is_idempotent() {
+    let pool = memory_pool().await;
+    televent::db::create_schema(&pool).await.unwrap();
+    televent::db::create_schema(&pool).await.unwrap();
+}